@@ -22,4 +22,4 @@ pub use types::{
     TypeDescriptorProducer
 };
 
-pub use statement::{Query};
+pub use statement::{Query, ToSqlBind};
@@ -4,7 +4,9 @@ use libc;
 use crate::connection::Connection;
 use crate::types::{
     DescriptorsProvider,
-    TypeDescriptor
+    TypeDescriptor,
+    SQLT_CHR,
+    SQLT_INT
 };
 use crate::values::{
     FromResultSet,
@@ -22,9 +24,53 @@ pub struct Query<'conn,R> where R: DescriptorsProvider + FromResultSet {
     conn:    &'conn Connection,
     stmthp:  *mut oci::OCIStmt,
     fetcher: Option<Box<Fetcher<'conn>>>,
+    binds:   Vec<BindValue>,
     _result: std::marker::PhantomData<R>
 }
 
+/// Value bound to placeholder; kept by query, as OCI reads it on every execute
+struct BindValue {
+    data:      Vec<u8>,
+    indicator: Box<libc::c_short>,
+    length:    Box<u32>
+}
+
+/// Rust types which can be bound to placeholders of statement
+pub trait ToSqlBind {
+    /// bytes and Oracle type of value, None for NULL
+    fn to_bind(&self) -> Option<(Vec<u8>, u16)>;
+}
+
+impl ToSqlBind for &str {
+    fn to_bind(&self) -> Option<(Vec<u8>, u16)> {
+        Some((self.as_bytes().to_vec(), SQLT_CHR))
+    }
+}
+
+impl ToSqlBind for String {
+    fn to_bind(&self) -> Option<(Vec<u8>, u16)> {
+        self.as_str().to_bind()
+    }
+}
+
+impl ToSqlBind for i32 {
+    fn to_bind(&self) -> Option<(Vec<u8>, u16)> {
+        Some((self.to_ne_bytes().to_vec(), SQLT_INT))
+    }
+}
+
+impl ToSqlBind for i64 {
+    fn to_bind(&self) -> Option<(Vec<u8>, u16)> {
+        Some((self.to_ne_bytes().to_vec(), SQLT_INT))
+    }
+}
+
+impl <T: ToSqlBind> ToSqlBind for Option<T> {
+    fn to_bind(&self) -> Option<(Vec<u8>, u16)> {
+        self.as_ref().and_then(|v| v.to_bind())
+    }
+}
+
 struct Fetcher<'conn> {
     conn:    &'conn Connection,
     stmthp:  *mut oci::OCIStmt,
@@ -68,7 +114,7 @@ impl <'conn,R> Query<'conn,R> where R: DescriptorsProvider + FromResultSet {
         }
         */
 
-        Ok( Query { conn, stmthp, fetcher: None, _result: PhantomData } )
+        Ok( Query { conn, stmthp, fetcher: None, binds: Vec::new(), _result: PhantomData } )
     }
 
     fn inner_prefetch_rows(&mut self, prefetch_rows: usize) -> Result<(), oci::OracleError> {
@@ -93,6 +139,26 @@ impl <'conn,R> Query<'conn,R> where R: DescriptorsProvider + FromResultSet {
         Ok(self)
     }
 
+    /// Binds value to placeholder at position, starting from 1; in SQL every occurrence
+    /// of a placeholder has own position, even when placeholders have the same name
+    pub fn bind<V: ToSqlBind>(mut self, position: u32, value: V) -> Result<Self, oci::OracleError> {
+        let (data, dtype, indicator) = match value.to_bind() {
+            Some((data, dtype)) => (data, dtype, 0),
+            None => (Vec::new(), SQLT_CHR, -1)
+        };
+        let mut bind = BindValue { length: Box::new(data.len() as u32), indicator: Box::new(indicator), data };
+
+        // heap buffers of the value do not move when it is pushed to binds
+        oci::bind_by_pos(self.stmthp, self.conn.errhp, position,
+                         bind.data.as_mut_ptr() as *mut oci::c_void,
+                         &mut *bind.indicator as *mut libc::c_short as *mut oci::c_void,
+                         bind.data.len() as i64,
+                         &mut *bind.length as *mut u32,
+                         dtype)?;
+        self.binds.push(bind);
+        Ok(self)
+    }
+
     pub fn fetch_iter<'iter>(&'iter mut self) -> Result<QueryIterator<'iter, 'conn, R>, oci::OracleError> {
         self.execute(10)?;
         Ok(QueryIterator::new(self.fetcher.as_mut().unwrap().as_mut()))
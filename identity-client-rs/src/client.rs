@@ -0,0 +1,110 @@
+use reqwest::header::{HeaderMap, AUTHORIZATION};
+use reqwest::{Response, StatusCode};
+
+use crate::domain::{default_realm, AuthenticatedUser, Introspection};
+use crate::errors::IdentityClientError;
+
+/// Typed HTTP client of identity server
+#[derive(Clone)]
+pub struct IdentityClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl IdentityClient {
+    /// `base_url` - address of identity server, e.g. `https://identity.local:8443`
+    pub fn new(base_url: &str) -> Self {
+        Self::with_http_client(base_url, reqwest::Client::new())
+    }
+
+    /// uses preconfigured client, e.g. with custom root certificates or timeouts
+    pub fn with_http_client(base_url: &str, http: reqwest::Client) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            http,
+        }
+    }
+
+    /// Session info of the token; restricted sessions are rejected with `Forbidden`
+    pub async fn auth_info(&self, token: &str) -> Result<AuthenticatedUser, IdentityClientError> {
+        let response = self
+            .http
+            .get(format!("{}/auth/info", self.base_url))
+            .header(AUTHORIZATION, format!("Token {}", token))
+            .send()
+            .await?;
+
+        let response = check_status(response)?;
+        Ok(response.json().await?)
+    }
+
+    /// Validates the token via forward auth endpoint `/auth/verify`;
+    /// with `role` the user must also have this role
+    pub async fn introspect(
+        &self,
+        token: &str,
+        role: Option<&str>,
+    ) -> Result<Introspection, IdentityClientError> {
+        let mut request = self
+            .http
+            .get(format!("{}/auth/verify", self.base_url))
+            .header(AUTHORIZATION, format!("Token {}", token));
+        if let Some(role) = role {
+            request = request.query(&[("role", role)]);
+        }
+
+        let response = check_status(request.send().await?)?;
+        let headers = response.headers();
+
+        let realm = header_value(headers, "X-Auth-Realm")?.unwrap_or_else(default_realm);
+        let personnel_nr = header_value(headers, "X-Auth-Personnel-Nr")?
+            .ok_or_else(|| IdentityClientError::invalid_response("X-Auth-Personnel-Nr not found"))?
+            .parse()
+            .map_err(|_| IdentityClientError::invalid_response("invalid X-Auth-Personnel-Nr"))?;
+        let username = header_value(headers, "X-Auth-User")?
+            .ok_or_else(|| IdentityClientError::invalid_response("X-Auth-User not found"))?;
+        let roles = header_value(headers, "X-Auth-Roles")?
+            .map(|roles| {
+                roles
+                    .split(',')
+                    .filter(|it| !it.is_empty())
+                    .map(|it| it.to_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let email = header_value(headers, "X-Auth-Email")?;
+
+        Ok(Introspection {
+            realm,
+            personnel_nr,
+            username,
+            roles,
+            email,
+        })
+    }
+}
+
+fn check_status(response: Response) -> Result<Response, IdentityClientError> {
+    match response.status() {
+        status if status.is_success() => Ok(response),
+        // malformed token is reported by server as bad request
+        StatusCode::UNAUTHORIZED | StatusCode::BAD_REQUEST => {
+            Err(IdentityClientError::Unauthorized)
+        }
+        StatusCode::FORBIDDEN => Err(IdentityClientError::Forbidden),
+        status => Err(IdentityClientError::UnexpectedStatus {
+            status: status.as_u16(),
+        }),
+    }
+}
+
+// server passes names as raw UTF-8
+fn header_value(headers: &HeaderMap, name: &str) -> Result<Option<String>, IdentityClientError> {
+    headers
+        .get(name)
+        .map(|value| {
+            String::from_utf8(value.as_bytes().to_vec())
+                .map_err(|_| IdentityClientError::invalid_response(&format!("invalid {}", name)))
+        })
+        .transpose()
+}
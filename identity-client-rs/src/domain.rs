@@ -0,0 +1,120 @@
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::future::{ready, Ready};
+
+use crate::middleware::AuthenticattionInfoContext;
+
+/// Session info as returned by `/auth/info`
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthenticatedUser {
+    #[serde(default = "default_realm")]
+    realm: String,
+    user: User,
+    roles: Vec<String>,
+    // real user behind an impersonation session
+    #[serde(default)]
+    actor: Option<Actor>,
+    authenticated: DateTime<Utc>,
+    #[serde(default)]
+    password_change_required: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct User {
+    pub personnel_nr: i32,
+    pub username: String,
+    pub email: Option<String>,
+    pub password_changed: DateTime<Utc>,
+    pub must_change_password: bool,
+    pub disabled: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Actor {
+    pub personnel_nr: i32,
+    pub username: String,
+}
+
+/// Session info as returned by `/auth/verify` in response headers
+#[derive(Debug, Clone)]
+pub struct Introspection {
+    pub realm: String,
+    pub personnel_nr: i32,
+    pub username: String,
+    pub roles: Vec<String>,
+    pub email: Option<String>,
+}
+
+impl AuthenticatedUser {
+    pub fn realm(&self) -> &str {
+        &self.realm
+    }
+
+    pub fn user(&self) -> &User {
+        &self.user
+    }
+
+    pub fn personnel_nr(&self) -> i32 {
+        self.user.personnel_nr
+    }
+
+    pub fn username(&self) -> &str {
+        &self.user.username
+    }
+
+    pub fn email(&self) -> Option<&str> {
+        self.user.email.as_deref()
+    }
+
+    pub fn roles(&self) -> &[String] {
+        &self.roles
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|it| it == role)
+    }
+
+    pub fn actor(&self) -> Option<&Actor> {
+        self.actor.as_ref()
+    }
+
+    pub fn authenticated(&self) -> DateTime<Utc> {
+        self.authenticated
+    }
+
+    pub fn password_change_required(&self) -> bool {
+        self.password_change_required
+    }
+}
+
+// servers without realms only have the default one
+pub(crate) fn default_realm() -> String {
+    "default".to_owned()
+}
+
+impl Introspection {
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|it| it == role)
+    }
+}
+
+/// Extracts user validated by `Authorization` middleware;
+/// fails with 401 if the handler is not wrapped by the middleware
+impl FromRequest for AuthenticatedUser {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let auth_info = req
+            .extensions()
+            .get::<AuthenticattionInfoContext>()
+            .map(|ctx| ctx.auth_info.as_ref().clone());
+
+        ready(
+            auth_info
+                .ok_or_else(|| actix_web::error::ErrorUnauthorized("You are not authenticated")),
+        )
+    }
+}
@@ -0,0 +1,51 @@
+use actix_web::http::header::ContentType;
+use actix_web::http::StatusCode;
+use actix_web::{error, HttpResponse};
+use derive_more::{Display, Error};
+
+#[derive(Display, Debug, Error)]
+pub enum IdentityClientError {
+    #[display(fmt = "You are not authenticated")]
+    Unauthorized,
+    #[display(fmt = "Access denied")]
+    Forbidden,
+    #[display(fmt = "Identity server responded with status {}", status)]
+    UnexpectedStatus {
+        status: u16,
+    },
+    #[display(fmt = "Invalid response of identity server: {}", reason)]
+    InvalidResponse {
+        reason: String,
+    },
+    RequestError(reqwest::Error),
+}
+
+impl std::convert::From<reqwest::Error> for IdentityClientError {
+    fn from(error: reqwest::Error) -> Self {
+        IdentityClientError::RequestError(error)
+    }
+}
+
+impl IdentityClientError {
+    pub fn invalid_response(reason: &str) -> IdentityClientError {
+        IdentityClientError::InvalidResponse {
+            reason: reason.to_owned(),
+        }
+    }
+}
+
+impl error::ResponseError for IdentityClientError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            IdentityClientError::Unauthorized => StatusCode::UNAUTHORIZED,
+            IdentityClientError::Forbidden => StatusCode::FORBIDDEN,
+            // identity server is unavailable or misbehaves
+            _ => StatusCode::BAD_GATEWAY,
+        }
+    }
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code())
+            .insert_header(ContentType::html())
+            .body(self.to_string())
+    }
+}
@@ -0,0 +1,12 @@
+//! Client of identity-server-rs for other services:
+//! typed HTTP client and actix middleware, which validates session tokens
+//! and injects `AuthenticatedUser` into handlers.
+mod client;
+mod domain;
+mod errors;
+mod middleware;
+
+pub use client::IdentityClient;
+pub use domain::{Actor, AuthenticatedUser, Introspection, User};
+pub use errors::IdentityClientError;
+pub use middleware::{AuthenticattionInfoContext, Authorization, AUTH_TOKEN_COOKIE};
@@ -0,0 +1,196 @@
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{Error, HttpMessage};
+use dashmap::DashMap;
+
+use crate::client::IdentityClient;
+use crate::domain::AuthenticatedUser;
+
+/// cookie with session token, used when request has no `Authorization` header
+/// and cookies are accepted with `Authorization::accept_cookie`
+pub const AUTH_TOKEN_COOKIE: &str = "auth_token";
+
+/// default time a validated token is trusted without asking identity server
+const DEFAULT_TTL_SECONDS: u64 = 60;
+/// expired tokens are purged when cache grows over this size
+const CACHE_PURGE_SIZE: usize = 10_000;
+
+#[derive(Clone)]
+pub struct AuthenticattionInfoContext {
+    pub auth_info: Arc<AuthenticatedUser>,
+}
+
+impl AuthenticattionInfoContext {
+    pub fn new(auth_info: Arc<AuthenticatedUser>) -> Self {
+        Self { auth_info }
+    }
+}
+
+struct CachedSession {
+    auth_info: Arc<AuthenticatedUser>,
+    validated: Instant,
+}
+
+/// Validated tokens; revoked sessions stay valid here until TTL passes
+struct TokenCache {
+    ttl: Duration,
+    sessions: DashMap<String, CachedSession>,
+}
+
+impl TokenCache {
+    fn get(&self, token: &str) -> Option<Arc<AuthenticatedUser>> {
+        let auth_info = self
+            .sessions
+            .get(token)
+            .filter(|it| it.validated.elapsed() < self.ttl)
+            .map(|it| it.auth_info.clone());
+
+        if auth_info.is_none() {
+            self.sessions
+                .remove_if(token, |_, it| it.validated.elapsed() >= self.ttl);
+        }
+        auth_info
+    }
+
+    fn insert(&self, token: String, auth_info: Arc<AuthenticatedUser>) {
+        if self.sessions.len() >= CACHE_PURGE_SIZE {
+            self.sessions
+                .retain(|_, it| it.validated.elapsed() < self.ttl);
+        }
+        self.sessions.insert(
+            token,
+            CachedSession {
+                auth_info,
+                validated: Instant::now(),
+            },
+        );
+    }
+}
+
+/// Validates session token of request against identity server
+/// and puts `AuthenticattionInfoContext` into request extensions.
+///
+/// Create it once outside of `HttpServer::new` and clone into workers,
+/// so all workers share the same cache.
+#[derive(Clone)]
+pub struct Authorization {
+    client: IdentityClient,
+    cache: Arc<TokenCache>,
+    accept_cookie: bool,
+}
+
+impl Authorization {
+    pub fn enable(client: IdentityClient) -> Self {
+        Self::with_ttl(client, Duration::from_secs(DEFAULT_TTL_SECONDS))
+    }
+
+    /// `ttl` - how long a validated token is trusted without asking identity server
+    pub fn with_ttl(client: IdentityClient, ttl: Duration) -> Self {
+        Self {
+            client,
+            cache: Arc::new(TokenCache {
+                ttl,
+                sessions: DashMap::new(),
+            }),
+            accept_cookie: false,
+        }
+    }
+
+    /// Accepts `auth_token` cookie when request has no `Authorization` header.
+    ///
+    /// Browsers send the cookie with cross-site requests too, so enable it only
+    /// for services protected against CSRF (e.g. `SameSite=Strict` or CSRF tokens).
+    pub fn accept_cookie(mut self) -> Self {
+        self.accept_cookie = true;
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Authorization
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AuthorizationMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuthorizationMiddleware {
+            service: Rc::new(service),
+            client: self.client.clone(),
+            cache: self.cache.clone(),
+            accept_cookie: self.accept_cookie,
+        }))
+    }
+}
+
+pub struct AuthorizationMiddleware<S> {
+    service: Rc<S>,
+    client: IdentityClient,
+    cache: Arc<TokenCache>,
+    accept_cookie: bool,
+}
+
+impl<S, B> Service<ServiceRequest> for AuthorizationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let client = self.client.clone();
+        let cache = self.cache.clone();
+        let accept_cookie = self.accept_cookie;
+
+        Box::pin(async move {
+            let token = request_token(&req, accept_cookie)
+                .ok_or_else(|| actix_web::error::ErrorUnauthorized("You are not authenticated"))?;
+
+            let auth_info = match cache.get(&token) {
+                Some(auth_info) => auth_info,
+                None => {
+                    let auth_info = Arc::new(client.auth_info(&token).await?);
+                    cache.insert(token, auth_info.clone());
+                    auth_info
+                }
+            };
+
+            req.extensions_mut()
+                .insert(AuthenticattionInfoContext::new(auth_info));
+            service.call(req).await
+        })
+    }
+}
+
+// `Authorization: Token <token>` header or, when accepted, `auth_token` cookie
+fn request_token(req: &ServiceRequest, accept_cookie: bool) -> Option<String> {
+    match req.headers().get("Authorization") {
+        Some(header) => {
+            let mut segments = header.to_str().ok()?.split(' ');
+            match (segments.next(), segments.next()) {
+                (Some("Token"), Some(token)) => Some(token.to_owned()),
+                _ => None,
+            }
+        }
+        None if accept_cookie => req
+            .cookie(AUTH_TOKEN_COOKIE)
+            .map(|cookie| cookie.value().to_owned()),
+        None => None,
+    }
+}
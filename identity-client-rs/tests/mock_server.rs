@@ -0,0 +1,237 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Data;
+use actix_web::{get, test, web, App, HttpRequest, HttpResponse, HttpServer};
+use identity_client_rs::{
+    AuthenticatedUser, Authorization, IdentityClient, IdentityClientError, AUTH_TOKEN_COOKIE,
+};
+use serde::Deserialize;
+
+const TOKEN: &str = "7f6c1a8e-3b0e-4c2a-9d55-0c2f9b7f1e11";
+const RESTRICTED_TOKEN: &str = "0b8e42f1-6d5c-4f0a-8a43-5e9c3d2b7a90";
+
+/// count of requests to `/auth/info` of mock identity server
+#[derive(Default)]
+struct Calls(AtomicUsize);
+
+fn token(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get("Authorization")?
+        .to_str()
+        .ok()?
+        .strip_prefix("Token ")
+}
+
+#[get("/auth/info")]
+async fn auth_info(req: HttpRequest, calls: Data<Calls>) -> HttpResponse {
+    calls.0.fetch_add(1, Ordering::SeqCst);
+    match token(&req) {
+        Some(TOKEN) => HttpResponse::Ok().json(serde_json::json!({
+            "realm": "default",
+            "user": {
+                "personnel_nr": 42,
+                "username": "Ion Popescu",
+                "email": "ion@example.md",
+                "password_changed": "2024-01-01T00:00:00Z",
+                "must_change_password": false,
+                "disabled": false
+            },
+            "roles": ["IDENTITY_ADMIN", "REPORTS"],
+            "authenticated": "2024-01-02T08:00:00Z",
+            "password_change_required": false
+        })),
+        Some(RESTRICTED_TOKEN) => HttpResponse::Forbidden().body("Password change required"),
+        _ => HttpResponse::Unauthorized().finish(),
+    }
+}
+
+#[derive(Deserialize)]
+struct VerifyQuery {
+    role: Option<String>,
+}
+
+#[get("/auth/verify")]
+async fn verify(req: HttpRequest, query: web::Query<VerifyQuery>) -> HttpResponse {
+    if token(&req) != Some(TOKEN) {
+        return HttpResponse::Unauthorized().finish();
+    }
+    if let Some(role) = &query.role {
+        if role != "REPORTS" {
+            return HttpResponse::Forbidden().finish();
+        }
+    }
+    HttpResponse::Ok()
+        .insert_header(("X-Auth-Realm", "default"))
+        .insert_header(("X-Auth-User", "Ion Popescu"))
+        .insert_header(("X-Auth-Personnel-Nr", "42"))
+        .insert_header(("X-Auth-Roles", "IDENTITY_ADMIN,REPORTS"))
+        .insert_header(("X-Auth-Email", "ion@example.md"))
+        .finish()
+}
+
+/// Starts mock identity server on a random local port
+fn start_identity_server() -> (IdentityClient, Arc<Calls>) {
+    let calls = Data::new(Calls::default());
+    let app_calls = calls.clone();
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(app_calls.clone())
+            .service(auth_info)
+            .service(verify)
+    })
+    .workers(1)
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+
+    let addr = server.addrs()[0];
+    actix_web::rt::spawn(server.run());
+
+    let client = IdentityClient::new(&format!("http://{}", addr));
+    (client, calls.into_inner())
+}
+
+#[get("/me")]
+async fn me(user: AuthenticatedUser) -> HttpResponse {
+    HttpResponse::Ok().body(format!("{}:{}", user.personnel_nr(), user.username()))
+}
+
+#[actix_web::test]
+async fn client_reads_auth_info() {
+    let (client, _) = start_identity_server();
+
+    let user = client.auth_info(TOKEN).await.unwrap();
+    assert_eq!(user.realm(), "default");
+    assert_eq!(user.personnel_nr(), 42);
+    assert_eq!(user.username(), "Ion Popescu");
+    assert_eq!(user.email(), Some("ion@example.md"));
+    assert!(user.has_role("REPORTS"));
+    assert!(user.actor().is_none());
+
+    assert!(matches!(
+        client.auth_info("unknown").await,
+        Err(IdentityClientError::Unauthorized)
+    ));
+    assert!(matches!(
+        client.auth_info(RESTRICTED_TOKEN).await,
+        Err(IdentityClientError::Forbidden)
+    ));
+}
+
+#[actix_web::test]
+async fn client_introspects_token() {
+    let (client, _) = start_identity_server();
+
+    let info = client.introspect(TOKEN, None).await.unwrap();
+    assert_eq!(info.realm, "default");
+    assert_eq!(info.personnel_nr, 42);
+    assert_eq!(info.username, "Ion Popescu");
+    assert_eq!(info.roles, vec!["IDENTITY_ADMIN", "REPORTS"]);
+    assert_eq!(info.email.as_deref(), Some("ion@example.md"));
+
+    assert!(client.introspect(TOKEN, Some("REPORTS")).await.is_ok());
+    assert!(matches!(
+        client.introspect(TOKEN, Some("ACCOUNTING")).await,
+        Err(IdentityClientError::Forbidden)
+    ));
+    assert!(matches!(
+        client.introspect("unknown", None).await,
+        Err(IdentityClientError::Unauthorized)
+    ));
+}
+
+#[actix_web::test]
+async fn middleware_injects_user_and_caches_token() {
+    let (client, calls) = start_identity_server();
+    let app = test::init_service(
+        App::new()
+            .wrap(Authorization::with_ttl(client, Duration::from_secs(60)))
+            .service(me),
+    )
+    .await;
+
+    for _ in 0..3 {
+        let req = test::TestRequest::get()
+            .uri("/me")
+            .insert_header(("Authorization", format!("Token {}", TOKEN)))
+            .to_request();
+        let body = test::call_and_read_body(&app, req).await;
+        assert_eq!(body, "42:Ion Popescu");
+    }
+    assert_eq!(calls.0.load(Ordering::SeqCst), 1);
+}
+
+#[actix_web::test]
+async fn middleware_ignores_cookie_by_default() {
+    let (client, calls) = start_identity_server();
+    let app = test::init_service(App::new().wrap(Authorization::enable(client)).service(me)).await;
+
+    let req = test::TestRequest::get()
+        .uri("/me")
+        .cookie(actix_web::cookie::Cookie::new(AUTH_TOKEN_COOKIE, TOKEN))
+        .to_request();
+    let err = test::try_call_service(&app, req).await.err().unwrap();
+    assert_eq!(
+        err.as_response_error().status_code(),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(calls.0.load(Ordering::SeqCst), 0);
+}
+
+#[actix_web::test]
+async fn middleware_accepts_cookie_when_enabled() {
+    let (client, _) = start_identity_server();
+    let app = test::init_service(
+        App::new()
+            .wrap(Authorization::enable(client).accept_cookie())
+            .service(me),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/me")
+        .cookie(actix_web::cookie::Cookie::new(AUTH_TOKEN_COOKIE, TOKEN))
+        .to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    assert_eq!(body, "42:Ion Popescu");
+}
+
+#[actix_web::test]
+async fn middleware_rejects_invalid_sessions() {
+    let (client, calls) = start_identity_server();
+    let app = test::init_service(
+        App::new()
+            .wrap(Authorization::with_ttl(client, Duration::ZERO))
+            .service(me),
+    )
+    .await;
+
+    let cases = [
+        (None, StatusCode::UNAUTHORIZED),
+        (Some("unknown"), StatusCode::UNAUTHORIZED),
+        (Some(RESTRICTED_TOKEN), StatusCode::FORBIDDEN),
+    ];
+    for (token, status) in cases {
+        let mut req = test::TestRequest::get().uri("/me");
+        if let Some(token) = token {
+            req = req.insert_header(("Authorization", format!("Token {}", token)));
+        }
+        let err = test::try_call_service(&app, req.to_request())
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.as_response_error().status_code(), status);
+    }
+
+    // without TTL every request asks identity server
+    for _ in 0..2 {
+        let req = test::TestRequest::get()
+            .uri("/me")
+            .insert_header(("Authorization", format!("Token {}", TOKEN)))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+    assert_eq!(calls.0.load(Ordering::SeqCst), 4);
+}
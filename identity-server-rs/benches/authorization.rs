@@ -0,0 +1,84 @@
+use chrono::Utc;
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::thread;
+
+use identity_server_rs::config::DEFAULT_REALM;
+use identity_server_rs::database::domain::User;
+use identity_server_rs::identity::Identity;
+
+const SALT: &str = "c2FsdHNhbHRzYWx0";
+const PASSWORD: &str = "secret";
+const USERS: i32 = 64;
+const THREADS: usize = 16;
+const CALLS_PER_THREAD: usize = 1000;
+
+fn login_users(identity: &Identity) -> Vec<String> {
+    let password_hash = identity
+        .realm(DEFAULT_REALM)
+        .unwrap()
+        .generate_password_hash(PASSWORD, SALT);
+    (0..USERS)
+        .map(|personnel_nr| {
+            let user = User {
+                personnel_nr,
+                salt: SALT.to_owned(),
+                password: password_hash.clone(),
+                username: format!("user{}", personnel_nr),
+                email: None,
+                password_changed: Utc::now(),
+                must_change_password: false,
+                disabled: false,
+            };
+            let response = identity
+                .authenticate(DEFAULT_REALM, user, vec![], PASSWORD)
+                .unwrap();
+            serde_json::to_value(&response).unwrap()["token"]
+                .as_str()
+                .unwrap()
+                .to_owned()
+        })
+        .collect()
+}
+
+fn concurrent_authorization_info(c: &mut Criterion) {
+    let identity = Identity::new();
+    let tokens = login_users(&identity);
+
+    c.bench_function("authorization_info 16 threads x 1000 calls", |b| {
+        b.iter(|| {
+            thread::scope(|scope| {
+                for t in 0..THREADS {
+                    let identity = &identity;
+                    let tokens = &tokens;
+                    scope.spawn(move || {
+                        for i in 0..CALLS_PER_THREAD {
+                            let token = &tokens[(t + i) % tokens.len()];
+                            identity.authorization_info(token).unwrap();
+                        }
+                    });
+                }
+            })
+        })
+    });
+
+    c.bench_function("authorization_info with concurrent logins", |b| {
+        b.iter(|| {
+            thread::scope(|scope| {
+                let identity = &identity;
+                let tokens = &tokens;
+                for t in 0..THREADS - 1 {
+                    scope.spawn(move || {
+                        for i in 0..CALLS_PER_THREAD {
+                            let token = &tokens[(t + i) % tokens.len()];
+                            identity.authorization_info(token).unwrap();
+                        }
+                    });
+                }
+                scope.spawn(move || login_users(identity));
+            })
+        })
+    });
+}
+
+criterion_group!(benches, concurrent_authorization_info);
+criterion_main!(benches);
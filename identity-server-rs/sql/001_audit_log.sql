@@ -0,0 +1,12 @@
+-- audit trail of privileged actions (impersonation etc.)
+CREATE TABLE IF NOT EXISTS security.audit_log (
+    id                  BIGSERIAL PRIMARY KEY,
+    created             TIMESTAMPTZ NOT NULL DEFAULT now(),
+    actor_personnel_nr  SMALLINT NOT NULL,
+    action              VARCHAR(64) NOT NULL,
+    target_personnel_nr SMALLINT,
+    details             TEXT
+);
+
+CREATE INDEX IF NOT EXISTS audit_log_actor_idx ON security.audit_log (actor_personnel_nr, created);
+CREATE INDEX IF NOT EXISTS audit_log_target_idx ON security.audit_log (target_personnel_nr, created);
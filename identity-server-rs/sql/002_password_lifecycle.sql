@@ -0,0 +1,14 @@
+-- password expiry, forced change and history of previous hashes
+ALTER TABLE security.users
+    ADD COLUMN IF NOT EXISTS password_changed TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ADD COLUMN IF NOT EXISTS must_change_password BOOLEAN NOT NULL DEFAULT false;
+
+CREATE TABLE IF NOT EXISTS security.password_history (
+    id           BIGSERIAL PRIMARY KEY,
+    personnel_nr SMALLINT NOT NULL,
+    salt         VARCHAR NOT NULL,
+    password     VARCHAR NOT NULL,
+    created      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS password_history_user_idx ON security.password_history (personnel_nr, created);
@@ -0,0 +1,3 @@
+-- disabled users can not login; their sessions are revoked
+ALTER TABLE security.users
+    ADD COLUMN IF NOT EXISTS disabled BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,13 @@
+-- persisted mirror of server sessions; tokens are stored only as SHA-256 hashes
+CREATE TABLE IF NOT EXISTS security.sessions (
+    token_hash         CHAR(64) PRIMARY KEY,
+    personnel_nr       SMALLINT NOT NULL,
+    actor_personnel_nr SMALLINT,
+    created            TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires            TIMESTAMPTZ NOT NULL,
+    revoked            TIMESTAMPTZ,
+    revoke_reason      VARCHAR(16)
+);
+
+CREATE INDEX IF NOT EXISTS sessions_user_idx ON security.sessions (personnel_nr) WHERE revoked IS NULL;
+CREATE INDEX IF NOT EXISTS sessions_revoked_idx ON security.sessions (revoked);
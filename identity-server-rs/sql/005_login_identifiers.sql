@@ -0,0 +1,14 @@
+-- personnel numbers above 32767; login by personnel nr, username or email
+ALTER TABLE security.users ALTER COLUMN personnel_nr TYPE INTEGER;
+ALTER TABLE security.user_roles ALTER COLUMN personnel_nr TYPE INTEGER;
+ALTER TABLE security.audit_log
+    ALTER COLUMN actor_personnel_nr TYPE INTEGER,
+    ALTER COLUMN target_personnel_nr TYPE INTEGER;
+ALTER TABLE security.password_history ALTER COLUMN personnel_nr TYPE INTEGER;
+ALTER TABLE security.sessions
+    ALTER COLUMN personnel_nr TYPE INTEGER,
+    ALTER COLUMN actor_personnel_nr TYPE INTEGER;
+
+-- usernames and emails are matched case-insensitive, so they must be unique in lower case
+CREATE UNIQUE INDEX IF NOT EXISTS users_username_lower_idx ON security.users (lower(username));
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_lower_idx ON security.users (lower(email)) WHERE email IS NOT NULL;
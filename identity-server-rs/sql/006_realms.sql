@@ -0,0 +1,12 @@
+-- realms: separate populations of users with own tables, e.g.
+--   CREATE TABLE contractors.users (LIKE security.users INCLUDING ALL);
+--   CREATE TABLE contractors.user_roles (LIKE security.user_roles INCLUDING ALL);
+--   CREATE TABLE contractors.password_history (LIKE security.password_history INCLUDING ALL);
+-- personnel nr is unique only within a realm
+ALTER TABLE security.sessions
+    ADD COLUMN IF NOT EXISTS realm VARCHAR(64) NOT NULL DEFAULT 'default';
+ALTER TABLE security.audit_log
+    ADD COLUMN IF NOT EXISTS realm VARCHAR(64) NOT NULL DEFAULT 'default';
+
+DROP INDEX IF EXISTS security.sessions_user_idx;
+CREATE INDEX IF NOT EXISTS sessions_user_idx ON security.sessions (realm, personnel_nr) WHERE revoked IS NULL;
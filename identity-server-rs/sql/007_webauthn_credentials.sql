@@ -0,0 +1,12 @@
+-- passkeys registered by users; public key is kept as COSE key of the authenticator
+CREATE TABLE IF NOT EXISTS security.webauthn_credentials (
+    credential_id VARCHAR(1024) PRIMARY KEY,
+    realm         VARCHAR(64) NOT NULL DEFAULT 'default',
+    personnel_nr  INTEGER NOT NULL,
+    public_key    BYTEA NOT NULL,
+    sign_count    BIGINT NOT NULL DEFAULT 0,
+    created       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_used     TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS webauthn_credentials_user_idx ON security.webauthn_credentials (realm, personnel_nr);
@@ -0,0 +1,335 @@
+use clap::{Args, Parser, Subcommand};
+use deadpool_postgres::{Client, Pool};
+use std::io::BufRead;
+
+use crate::config::{try_load_rustls_config, IdentityServerConfig, DEFAULT_REALM};
+use crate::database::domain::NewUser;
+use crate::database::{
+    count_of_roles, create_db_pool, find_user_by_personnel_nr, find_users_by_login, insert_user,
+    list_sessions, revoke_session, revoke_user_sessions, set_user_disabled, update_password,
+    UserSource, DISABLED_REVOKE_REASON,
+};
+use crate::errors::IdentityServerError;
+use crate::identity::{Identity, Realm};
+
+/// Identity server and its administration commands.
+/// Configuration is read from environment variables (and `.env` file)
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run identity server (default)
+    Serve,
+    /// Print salt and hash of password for `security.users` (default hashing parameters)
+    HashPassword(PasswordArgs),
+    /// Manage users
+    #[command(subcommand)]
+    User(UserCommand),
+    /// Manage sessions persisted in `security.sessions`
+    #[command(subcommand)]
+    Sessions(SessionsCommand),
+    /// Validate configuration, TLS files and database connection
+    CheckConfig,
+}
+
+#[derive(Subcommand)]
+pub enum UserCommand {
+    /// Add new user
+    Add {
+        #[arg(long, default_value = DEFAULT_REALM)]
+        realm: String,
+        personnel_nr: i32,
+        username: String,
+        #[arg(long)]
+        email: Option<String>,
+        #[command(flatten)]
+        password: PasswordArgs,
+        /// user must not change password on first login
+        #[arg(long)]
+        permanent: bool,
+    },
+    /// Disable user and revoke all sessions
+    Disable {
+        #[arg(long, default_value = DEFAULT_REALM)]
+        realm: String,
+        personnel_nr: i32,
+    },
+    /// Reset password of user
+    SetPassword {
+        #[arg(long, default_value = DEFAULT_REALM)]
+        realm: String,
+        personnel_nr: i32,
+        #[command(flatten)]
+        password: PasswordArgs,
+        /// user must not change password on next login
+        #[arg(long)]
+        permanent: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SessionsCommand {
+    /// List active sessions
+    List {
+        #[arg(long)]
+        realm: Option<String>,
+        #[arg(long)]
+        personnel_nr: Option<i32>,
+    },
+    /// Revoke session by token hash or all sessions of user
+    Revoke {
+        #[arg(
+            long,
+            conflicts_with = "personnel_nr",
+            required_unless_present = "personnel_nr"
+        )]
+        token_hash: Option<String>,
+        #[arg(long)]
+        personnel_nr: Option<i32>,
+        /// realm of `--personnel-nr`
+        #[arg(long, default_value = DEFAULT_REALM)]
+        realm: String,
+    },
+}
+
+#[derive(Args)]
+pub struct PasswordArgs {
+    /// password; read from stdin when omitted
+    #[arg(long)]
+    password: Option<String>,
+}
+
+impl PasswordArgs {
+    fn read(&self) -> Result<String, String> {
+        if let Some(ref password) = self.password {
+            return Ok(password.clone());
+        }
+        eprint!("password: ");
+        let mut line = String::new();
+        std::io::stdin()
+            .lock()
+            .read_line(&mut line)
+            .map_err(|err| format!("can not read password: {}", err))?;
+        let password = line.trim_end_matches(&['\r', '\n'][..]).to_owned();
+        if password.is_empty() {
+            return Err("password must not be empty".to_owned());
+        }
+        Ok(password)
+    }
+}
+
+/// Runs administration command; `serve` is handled by the binary itself
+pub async fn run(command: Command, config: IdentityServerConfig) -> Result<(), String> {
+    match command {
+        Command::Serve => Err("serve is not an administration command".to_owned()),
+        Command::HashPassword(password) => hash_password(&password),
+        Command::CheckConfig => check_config(config).await,
+        Command::User(command) => {
+            let identity = Identity::with_realms(config.realms())?;
+            let pool = create_db_pool(config.pg);
+            user(command, &identity, &pool).await
+        }
+        Command::Sessions(command) => {
+            let pool = create_db_pool(config.pg);
+            sessions(command, &pool).await
+        }
+    }
+}
+
+pub fn hash_password(password: &PasswordArgs) -> Result<(), String> {
+    let identity = Identity::new();
+    let realm = identity
+        .realm(DEFAULT_REALM)
+        .map_err(|err| err.to_string())?;
+    let hash = realm.new_password_hash(&password.read()?);
+    println!("salt:     {}", hash.salt);
+    println!("password: {}", hash.password);
+    Ok(())
+}
+
+async fn client(pool: &Pool) -> Result<Client, String> {
+    pool.get()
+        .await
+        .map_err(|err| format!("can not connect to database: {}", err))
+}
+
+fn db_error(err: IdentityServerError) -> String {
+    format!("database error: {}", err)
+}
+
+// every identifier of new user must not be a login of an existing user
+async fn check_login_unique(
+    client: &Client,
+    source: &UserSource,
+    login: &str,
+) -> Result<(), String> {
+    let users = find_users_by_login(client, source, login)
+        .await
+        .map_err(db_error)?;
+    match users.first() {
+        Some(user) => Err(format!(
+            "'{}' is already used as login of user {}",
+            login, user.personnel_nr
+        )),
+        None => Ok(()),
+    }
+}
+
+fn realm<'a>(identity: &'a Identity, name: &str) -> Result<&'a Realm, String> {
+    identity
+        .realm(name)
+        .map_err(|_| format!("unknown realm: {}", name))
+}
+
+async fn user(command: UserCommand, identity: &Identity, pool: &Pool) -> Result<(), String> {
+    let mut client = client(pool).await?;
+
+    match command {
+        UserCommand::Add {
+            realm: realm_name,
+            personnel_nr,
+            username,
+            email,
+            password,
+            permanent,
+        } => {
+            let realm = realm(identity, &realm_name)?;
+            let source = realm.user_source();
+            check_login_unique(&client, source, &personnel_nr.to_string()).await?;
+            check_login_unique(&client, source, &username).await?;
+            if let Some(email) = &email {
+                check_login_unique(&client, source, email).await?;
+            }
+
+            let user = NewUser {
+                personnel_nr,
+                username,
+                email,
+                password: realm.new_password_hash(&password.read()?),
+                must_change_password: !permanent,
+            };
+            insert_user(&client, source, &user)
+                .await
+                .map_err(db_error)?;
+            println!("user {} added", personnel_nr);
+        }
+        UserCommand::Disable {
+            realm: realm_name,
+            personnel_nr,
+        } => {
+            let source = realm(identity, &realm_name)?.user_source();
+            if !set_user_disabled(&client, source, personnel_nr, true)
+                .await
+                .map_err(db_error)?
+            {
+                return Err(format!("user {} not found", personnel_nr));
+            }
+            // running servers revoke sessions and publish `user-disabled` by this reason
+            let revoked =
+                revoke_user_sessions(&client, &realm_name, personnel_nr, DISABLED_REVOKE_REASON)
+                    .await
+                    .map_err(db_error)?;
+            println!(
+                "user {} disabled, {} sessions revoked",
+                personnel_nr, revoked
+            );
+        }
+        UserCommand::SetPassword {
+            realm: realm_name,
+            personnel_nr,
+            password,
+            permanent,
+        } => {
+            let realm = realm(identity, &realm_name)?;
+            let source = realm.user_source();
+            find_user_by_personnel_nr(&client, source, personnel_nr)
+                .await
+                .map_err(db_error)?
+                .ok_or(format!("user {} not found", personnel_nr))?;
+            let hash = realm.new_password_hash(&password.read()?);
+            update_password(&mut client, source, personnel_nr, &hash, !permanent)
+                .await
+                .map_err(db_error)?;
+            println!("password of user {} changed", personnel_nr);
+        }
+    }
+    Ok(())
+}
+
+async fn sessions(command: SessionsCommand, pool: &Pool) -> Result<(), String> {
+    let client = client(pool).await?;
+
+    match command {
+        SessionsCommand::List {
+            realm,
+            personnel_nr,
+        } => {
+            let sessions = list_sessions(&client, realm.as_deref(), personnel_nr)
+                .await
+                .map_err(db_error)?;
+            println!(
+                "{:<64}  {:<12}  {:>12}  {:>5}  {:<25}  {:<25}",
+                "token hash", "realm", "personnel nr", "actor", "created", "expires"
+            );
+            for session in sessions {
+                let actor = session
+                    .actor_personnel_nr
+                    .map(|it| it.to_string())
+                    .unwrap_or_default();
+                println!(
+                    "{:<64}  {:<12}  {:>12}  {:>5}  {:<25}  {:<25}",
+                    session.token_hash,
+                    session.realm,
+                    session.personnel_nr,
+                    actor,
+                    session.created.to_rfc3339(),
+                    session.expires.to_rfc3339()
+                );
+            }
+        }
+        SessionsCommand::Revoke {
+            token_hash,
+            personnel_nr,
+            realm,
+        } => {
+            let revoked = match (token_hash, personnel_nr) {
+                (Some(token_hash), _) => revoke_session(&client, &token_hash, "revoked")
+                    .await
+                    .map_err(db_error)? as u64,
+                (None, Some(personnel_nr)) => {
+                    revoke_user_sessions(&client, &realm, personnel_nr, "revoked")
+                        .await
+                        .map_err(db_error)?
+                }
+                (None, None) => return Err("token hash or personnel nr required".to_owned()),
+            };
+            println!("{} sessions revoked", revoked);
+        }
+    }
+    Ok(())
+}
+
+async fn check_config(config: IdentityServerConfig) -> Result<(), String> {
+    println!("server address: {}", config.server_addr);
+    println!("cors origins:   {:?}", config.cors.origins());
+    // the same check refuses to start the server
+    config
+        .cors
+        .validate()
+        .map_err(|err| format!("invalid cors configuration: {}", err))?;
+
+    try_load_rustls_config(&config.ssl)?;
+    println!("tls:            ok");
+
+    let pool = create_db_pool(config.pg);
+    let client = client(&pool).await?;
+    let roles = count_of_roles(&client).await.map_err(db_error)?;
+    println!("database:       ok ({} roles)", roles);
+
+    Ok(())
+}
@@ -1,10 +1,79 @@
 use serde::Deserialize;
+use std::collections::HashMap;
+
+/// realm of `/login` and of users in `security.users`
+pub const DEFAULT_REALM: &str = "default";
+
+/// Loads server config from environment variables
+pub fn load_config() -> Result<IdentityServerConfig, ::config::ConfigError> {
+    ::config::Config::builder()
+        .add_source(::config::Environment::default())
+        .build()?
+        .try_deserialize()
+}
 
 #[derive(Debug, Default, Deserialize)]
 pub struct IdentityServerConfig {
     pub server_addr: String,
     pub ssl: SSLConfig,
     pub pg: deadpool_postgres::Config,
+    /// source of users of `default` realm, `postgres` or `oracle`
+    #[serde(default)]
+    pub user_store: UserStoreKind,
+    #[serde(default)]
+    pub oracle: OracleConfig,
+    #[serde(default)]
+    pub cors: CorsConfig,
+    #[serde(default)]
+    pub headers: SecurityHeadersConfig,
+    #[serde(default)]
+    pub impersonation: ImpersonationConfig,
+    #[serde(default)]
+    pub password: PasswordPolicyConfig,
+    #[serde(default)]
+    pub admin: AdminConfig,
+    #[serde(default)]
+    pub events: EventsConfig,
+    #[serde(default)]
+    pub webauthn: WebAuthnConfig,
+    /// named realms, e.g. `REALMS.CONTRACTORS.USERS_TABLE=contractors.users`
+    #[serde(default)]
+    pub realms: HashMap<String, RealmConfig>,
+}
+
+impl IdentityServerConfig {
+    /// Configured realms and `default` realm, which uses top level password policy
+    /// and user store unless it is configured explicitly;
+    /// password history and length not set in a realm are those of top level policy
+    pub fn realms(&self) -> Vec<(String, RealmConfig)> {
+        let mut realms: Vec<(String, RealmConfig)> = self
+            .realms
+            .iter()
+            .map(|(name, realm)| {
+                let realm = RealmConfig {
+                    password_history: realm.password_history.or(Some(self.password.history)),
+                    password_min_length: realm
+                        .password_min_length
+                        .or(Some(self.password.min_length)),
+                    ..realm.clone()
+                };
+                (name.clone(), realm)
+            })
+            .collect();
+        if !self.realms.contains_key(DEFAULT_REALM) {
+            let realm = RealmConfig {
+                password_max_age_days: self.password.max_age_days,
+                password_history: Some(self.password.history),
+                password_min_length: Some(self.password.min_length),
+                user_store: self.user_store,
+                oracle: self.oracle.clone(),
+                ..RealmConfig::default()
+            };
+            realms.push((DEFAULT_REALM.to_owned(), realm));
+        }
+        realms.sort_by(|a, b| a.0.cmp(&b.0));
+        realms
+    }
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -14,6 +83,253 @@ pub struct SSLConfig {
     pub certfile: String,
 }
 
+// list values are comma separated, so they can be passed via environment
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CorsConfig {
+    pub allowed_origins: String,
+    pub allowed_methods: String,
+    pub allowed_headers: String,
+    pub supports_credentials: bool,
+    pub max_age: Option<usize>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: String::new(),
+            // DELETE ends impersonation
+            allowed_methods: "GET,POST,DELETE,OPTIONS".to_owned(),
+            allowed_headers: "Authorization,Content-Type".to_owned(),
+            supports_credentials: false,
+            max_age: Some(3600),
+        }
+    }
+}
+
+impl CorsConfig {
+    pub fn origins(&self) -> Vec<&str> {
+        split_list(&self.allowed_origins)
+    }
+    pub fn methods(&self) -> Vec<&str> {
+        split_list(&self.allowed_methods)
+    }
+    pub fn headers(&self) -> Vec<&str> {
+        split_list(&self.allowed_headers)
+    }
+
+    /// any origin with credentials would let every site make requests with cookies of user
+    pub fn validate(&self) -> Result<(), String> {
+        if self.supports_credentials && self.origins().contains(&"*") {
+            return Err("wildcard origin can not be allowed with supports_credentials".to_owned());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SecurityHeadersConfig {
+    pub hsts_max_age: u64,
+    pub hsts_include_subdomains: bool,
+    pub referrer_policy: String,
+    pub content_security_policy: String,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            hsts_max_age: 31536000,
+            hsts_include_subdomains: true,
+            referrer_policy: "no-referrer".to_owned(),
+            content_security_policy: "default-src 'none'; frame-ancestors 'none'".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ImpersonationConfig {
+    /// role required to impersonate other users
+    pub role: String,
+    /// lifetime of impersonation session
+    pub minutes: i64,
+}
+
+impl Default for ImpersonationConfig {
+    fn default() -> Self {
+        Self {
+            role: "IDENTITY_IMPERSONATOR".to_owned(),
+            minutes: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PasswordPolicyConfig {
+    /// password expires after this count of days; 0 - never expires
+    pub max_age_days: i64,
+    /// new password must differ from this count of previous passwords
+    pub history: i64,
+    pub min_length: usize,
+}
+
+impl Default for PasswordPolicyConfig {
+    fn default() -> Self {
+        Self {
+            max_age_days: 0,
+            history: 5,
+            min_length: 8,
+        }
+    }
+}
+
+/// Separate population of users with own credential source and session policy
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RealmConfig {
+    /// tables with columns of `security.users`, `security.user_roles`
+    /// and `security.password_history`
+    pub users_table: String,
+    pub user_roles_table: String,
+    pub password_history_table: String,
+    pub session_hours: i64,
+    /// PBKDF2 iterations of password hashes
+    pub password_iterations: u32,
+    /// password expires after this count of days; 0 - never expires
+    pub password_max_age_days: i64,
+    /// new password must differ from this count of previous passwords
+    pub password_history: Option<i64>,
+    pub password_min_length: Option<usize>,
+    /// prepended to session tokens, so the realm of a token is known;
+    /// only one realm may have an empty prefix
+    pub token_prefix: String,
+    /// with `oracle` users are read from `users_table` in Oracle,
+    /// roles and password history stay in Postgres
+    pub user_store: UserStoreKind,
+    pub oracle: OracleConfig,
+}
+
+impl Default for RealmConfig {
+    fn default() -> Self {
+        Self {
+            users_table: "security.users".to_owned(),
+            user_roles_table: "security.user_roles".to_owned(),
+            password_history_table: "security.password_history".to_owned(),
+            session_hours: 12,
+            password_iterations: 1000,
+            password_max_age_days: 0,
+            password_history: None,
+            password_min_length: None,
+            token_prefix: String::new(),
+            user_store: UserStoreKind::default(),
+            oracle: OracleConfig::default(),
+        }
+    }
+}
+
+impl RealmConfig {
+    pub fn password_max_age(&self) -> Option<chrono::Duration> {
+        if self.password_max_age_days > 0 {
+            Some(chrono::Duration::days(self.password_max_age_days))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserStoreKind {
+    #[default]
+    Postgres,
+    /// requires `oracle` feature
+    Oracle,
+}
+
+/// Connection to Oracle user store, e.g. `ORACLE.DB=//hr-db:1521/HR`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct OracleConfig {
+    pub db: String,
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AdminConfig {
+    /// role required to manage users
+    pub role: String,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            role: "IDENTITY_ADMIN".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EventsConfig {
+    /// role required to subscribe to session events
+    pub role: String,
+    pub keep_alive_seconds: u64,
+    /// interval of expired sessions cleanup and polling of revoked sessions
+    pub expiry_check_seconds: u64,
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            role: "IDENTITY_EVENTS".to_owned(),
+            keep_alive_seconds: 15,
+            expiry_check_seconds: 60,
+        }
+    }
+}
+
+/// Relying party of passkey login
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WebAuthnConfig {
+    /// domain of the web application, e.g. `identity.example.md`
+    pub rp_id: String,
+    pub rp_name: String,
+    /// origins allowed in client data, comma separated, e.g. `https://identity.example.md`
+    pub origins: String,
+    /// time to complete registration or login
+    pub timeout_seconds: i64,
+}
+
+impl Default for WebAuthnConfig {
+    fn default() -> Self {
+        Self {
+            rp_id: "localhost".to_owned(),
+            rp_name: "Identity Server".to_owned(),
+            origins: "https://localhost:8443".to_owned(),
+            timeout_seconds: 300,
+        }
+    }
+}
+
+impl WebAuthnConfig {
+    pub fn origins(&self) -> Vec<&str> {
+        split_list(&self.origins)
+    }
+}
+
+fn split_list(value: &str) -> Vec<&str> {
+    value
+        .split(',')
+        .map(|it| it.trim())
+        .filter(|it| !it.is_empty())
+        .collect()
+}
+
 use rustls::{Certificate, PrivateKey, ServerConfig};
 use rustls_pemfile::{certs, pkcs8_private_keys};
 use std::fs::File;
@@ -21,6 +337,16 @@ use std::io::BufReader;
 use std::path::Path;
 
 pub fn load_rustls_config(ssl: &SSLConfig) -> ServerConfig {
+    match try_load_rustls_config(ssl) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+pub fn try_load_rustls_config(ssl: &SSLConfig) -> Result<ServerConfig, String> {
     // init server config builder with safe defaults
     let config = ServerConfig::builder()
         .with_safe_defaults()
@@ -31,26 +357,33 @@ pub fn load_rustls_config(ssl: &SSLConfig) -> ServerConfig {
     let certfilepath = keypath.join(&ssl.certfile);
 
     // load TLS key/cert files
-    let cert_file = &mut BufReader::new(File::open(certfilepath.as_path()).unwrap());
-    let key_file = &mut BufReader::new(File::open(keyfilepath.as_path()).unwrap());
+    let cert_file = &mut BufReader::new(
+        File::open(certfilepath.as_path())
+            .map_err(|err| format!("Could not open {:?}: {}", certfilepath, err))?,
+    );
+    let key_file = &mut BufReader::new(
+        File::open(keyfilepath.as_path())
+            .map_err(|err| format!("Could not open {:?}: {}", keyfilepath, err))?,
+    );
 
     // convert files to key/cert objects
     let cert_chain = certs(cert_file)
-        .unwrap()
+        .map_err(|err| format!("Could not read certificates: {}", err))?
         .into_iter()
         .map(Certificate)
         .collect();
     let mut keys: Vec<PrivateKey> = pkcs8_private_keys(key_file)
-        .unwrap()
+        .map_err(|err| format!("Could not read private keys: {}", err))?
         .into_iter()
         .map(PrivateKey)
         .collect();
 
-    // exit if no keys could be parsed
+    // fail if no keys could be parsed
     if keys.is_empty() {
-        eprintln!("Could not locate PKCS 8 private keys.");
-        std::process::exit(1);
+        return Err("Could not locate PKCS 8 private keys.".to_owned());
     }
 
-    config.with_single_cert(cert_chain, keys.remove(0)).unwrap()
+    config
+        .with_single_cert(cert_chain, keys.remove(0))
+        .map_err(|err| format!("Invalid certificate or key: {}", err))
 }
@@ -1,13 +1,21 @@
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 use tokio_postgres::Row;
+use utoipa::ToSchema;
 
-#[derive(Debug,Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct User {
-    pub personnel_nr: i16,
+    pub personnel_nr: i32,
+    // password hash never leaves the server
+    #[serde(skip_serializing)]
     pub salt: String,
+    #[serde(skip_serializing)]
     pub password: String,
     pub username: String,
     pub email: Option<String>,
+    pub password_changed: DateTime<Utc>,
+    pub must_change_password: bool,
+    pub disabled: bool,
 }
 
 impl From<Row> for User {
@@ -18,6 +26,95 @@ impl From<Row> for User {
             password: row.get(2),
             username: row.get(3),
             email: row.get(4),
+            password_changed: row.get(5),
+            must_change_password: row.get(6),
+            disabled: row.get(7),
+        }
+    }
+}
+
+/// Row of `security.audit_log`
+#[derive(Debug)]
+pub struct AuditRecord {
+    pub realm: String,
+    pub actor_personnel_nr: i32,
+    pub action: &'static str,
+    pub target_personnel_nr: Option<i32>,
+    pub details: Option<String>,
+}
+
+/// Previous password of user from `security.password_history`
+#[derive(Debug)]
+pub struct PasswordHash {
+    pub salt: String,
+    pub password: String,
+}
+
+impl From<Row> for PasswordHash {
+    fn from(row: Row) -> Self {
+        Self {
+            salt: row.get(0),
+            password: row.get(1),
+        }
+    }
+}
+
+/// New row of `security.users`
+#[derive(Debug)]
+pub struct NewUser {
+    pub personnel_nr: i32,
+    pub username: String,
+    pub email: Option<String>,
+    pub password: PasswordHash,
+    pub must_change_password: bool,
+}
+
+/// Row of `security.sessions`
+#[derive(Debug)]
+pub struct StoredSession {
+    pub token_hash: String,
+    pub realm: String,
+    pub personnel_nr: i32,
+    pub actor_personnel_nr: Option<i32>,
+    pub created: DateTime<Utc>,
+    pub expires: DateTime<Utc>,
+    pub revoked: Option<DateTime<Utc>>,
+    pub revoke_reason: Option<String>,
+}
+
+impl From<Row> for StoredSession {
+    fn from(row: Row) -> Self {
+        Self {
+            token_hash: row.get(0),
+            realm: row.get(1),
+            personnel_nr: row.get(2),
+            actor_personnel_nr: row.get(3),
+            created: row.get(4),
+            expires: row.get(5),
+            revoked: row.get(6),
+            revoke_reason: row.get(7),
+        }
+    }
+}
+
+/// Row of `security.webauthn_credentials`; `credential_id` is base64url encoded
+#[derive(Debug)]
+pub struct WebAuthnCredential {
+    pub credential_id: String,
+    pub realm: String,
+    pub personnel_nr: i32,
+    pub public_key: Vec<u8>,
+    pub sign_count: i64,
+}
+
+impl From<Row> for WebAuthnCredential {
+    fn from(row: Row) -> Self {
+        Self {
+            credential_id: row.get(0),
+            realm: row.get(1),
+            personnel_nr: row.get(2),
+            public_key: row.get(3),
+            sign_count: row.get(4),
         }
     }
 }
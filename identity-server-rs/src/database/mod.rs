@@ -1,11 +1,88 @@
 pub mod domain;
+#[cfg(feature = "oracle")]
+pub mod oracle_store;
 
+use chrono::{DateTime, Utc};
 use deadpool_postgres::{Client, Pool};
 use postgres_native_tls::MakeTlsConnector;
-use std::str::FromStr;
 
 use crate::errors::IdentityServerError;
 
+/// columns of `security.users` in order of `domain::User::from`
+const USER_COLUMNS: &str = "personnel_nr, salt, password, username, email, \
+    password_changed, must_change_password, disabled";
+
+/// Tables with users of a realm; all of them have the columns of `security.*` tables.
+/// With Oracle store users are read from Oracle and can not be modified
+#[derive(Debug, Clone)]
+pub struct UserSource {
+    users: String,
+    user_roles: String,
+    password_history: String,
+    #[cfg(feature = "oracle")]
+    oracle: Option<oracle_store::OracleUsers>,
+}
+
+impl Default for UserSource {
+    fn default() -> Self {
+        Self {
+            users: "security.users".to_owned(),
+            user_roles: "security.user_roles".to_owned(),
+            password_history: "security.password_history".to_owned(),
+            #[cfg(feature = "oracle")]
+            oracle: None,
+        }
+    }
+}
+
+impl UserSource {
+    /// Table names come from configuration and are put into SQL as is,
+    /// so only plain `[schema.]table` names are accepted
+    pub fn new(users: &str, user_roles: &str, password_history: &str) -> Result<Self, String> {
+        for table in [users, user_roles, password_history] {
+            if !is_table_name(table) {
+                return Err(format!("invalid table name: '{}'", table));
+            }
+        }
+        Ok(Self {
+            users: users.to_owned(),
+            user_roles: user_roles.to_owned(),
+            password_history: password_history.to_owned(),
+            #[cfg(feature = "oracle")]
+            oracle: None,
+        })
+    }
+
+    /// Users table is read from Oracle
+    #[cfg(feature = "oracle")]
+    pub fn with_oracle(mut self, config: &crate::config::OracleConfig) -> Self {
+        self.oracle = Some(oracle_store::OracleUsers::new(config, &self.users));
+        self
+    }
+
+    fn check_writable(&self) -> Result<(), IdentityServerError> {
+        #[cfg(feature = "oracle")]
+        if self.oracle.is_some() {
+            return Err(IdentityServerError::validationError(
+                "Utilizatorii sunt administrati in Oracle",
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn is_table_name(name: &str) -> bool {
+    let parts: Vec<&str> = name.split('.').collect();
+    parts.len() <= 2
+        && parts.iter().all(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
 pub fn create_db_pool(pg: deadpool_postgres::Config) -> Pool {
     let connector = native_tls::TlsConnector::builder()
         .danger_accept_invalid_certs(true)
@@ -26,29 +103,456 @@ pub async fn count_of_roles(client: &Client) -> Result<i64, IdentityServerError>
     Ok(value)
 }
 
-pub async fn find_user_by_name(
+/// Users whose personnel nr, username or email match `login`;
+/// username and email are compared case-insensitive
+pub async fn find_users_by_login(
     client: &Client,
-    username: &str,
-) -> Result<Option<domain::User>, IdentityServerError> {
+    source: &UserSource,
+    login: &str,
+) -> Result<Vec<domain::User>, IdentityServerError> {
+    #[cfg(feature = "oracle")]
+    if let Some(oracle) = &source.oracle {
+        return oracle.find_users_by_login(login).await;
+    }
+
+    let login = login.trim();
     let stmt = client
-        .prepare(
-            "SELECT personnel_nr, salt, password, username, email \
-        FROM security.users \
-        WHERE personnel_nr = $1",
-        )
-        .await
-        .unwrap();
+        .prepare(&format!(
+            "SELECT {} FROM {} \
+        WHERE personnel_nr = $1 OR lower(username) = lower($2) OR lower(email) = lower($2)",
+            USER_COLUMNS, source.users
+        ))
+        .await?;
 
-    log::info!("authentication statement prepared");
+    let personnel_nr: Option<i32> = login.parse().ok();
+    let rows = client.query(&stmt, &[&personnel_nr, &login]).await?;
 
-    let personnel_nr: i16 = FromStr::from_str(username).map_err(|_| {
-        IdentityServerError::validationError("username must be personnel nr: number")
-    })?;
+    Ok(rows.into_iter().map(|r| r.into()).collect())
+}
 
-    let result = client.query_opt(&stmt, &[&personnel_nr]).await?;
+/// User identified by `login`; fails if login matches more than one user,
+/// e.g. numeric username of one user equals personnel nr of another
+pub async fn find_user_by_login(
+    client: &Client,
+    source: &UserSource,
+    login: &str,
+) -> Result<Option<domain::User>, IdentityServerError> {
+    let mut users = find_users_by_login(client, source, login).await?;
 
     log::info!("authentication result retrieved");
 
+    if users.len() > 1 {
+        return Err(IdentityServerError::authanticationError(
+            "Numele utilizatorului nu este unic",
+        ));
+    }
+    Ok(users.pop())
+}
+
+pub async fn find_user_by_personnel_nr(
+    client: &Client,
+    source: &UserSource,
+    personnel_nr: i32,
+) -> Result<Option<domain::User>, IdentityServerError> {
+    #[cfg(feature = "oracle")]
+    if let Some(oracle) = &source.oracle {
+        return oracle.find_user_by_personnel_nr(personnel_nr).await;
+    }
+
+    let stmt = client
+        .prepare(&format!(
+            "SELECT {} FROM {} WHERE personnel_nr = $1",
+            USER_COLUMNS, source.users
+        ))
+        .await?;
+
+    let result = client.query_opt(&stmt, &[&personnel_nr]).await?;
+
     let user = result.map(|r| r.into());
     Ok(user)
 }
+
+pub async fn find_user_roles(
+    client: &Client,
+    source: &UserSource,
+    personnel_nr: i32,
+) -> Result<Vec<String>, IdentityServerError> {
+    let stmt = client
+        .prepare(&format!(
+            "SELECT r.name \
+        FROM {} ur \
+        JOIN security.roles r ON r.id = ur.role_id \
+        WHERE ur.personnel_nr = $1 \
+        ORDER BY r.name",
+            source.user_roles
+        ))
+        .await?;
+
+    let rows = client.query(&stmt, &[&personnel_nr]).await?;
+
+    Ok(rows.iter().map(|r| r.get(0)).collect())
+}
+
+const INSERT_AUDIT_RECORD: &str = "INSERT INTO security.audit_log \
+    (realm, actor_personnel_nr, action, target_personnel_nr, details) VALUES ($1, $2, $3, $4, $5)";
+
+pub async fn insert_audit_record(
+    client: &Client,
+    record: &domain::AuditRecord,
+) -> Result<(), IdentityServerError> {
+    let stmt = client.prepare(INSERT_AUDIT_RECORD).await?;
+
+    client
+        .execute(
+            &stmt,
+            &[
+                &record.realm,
+                &record.actor_personnel_nr,
+                &record.action,
+                &record.target_personnel_nr,
+                &record.details,
+            ],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Last `limit` previous passwords of user, newest first
+pub async fn find_password_history(
+    client: &Client,
+    source: &UserSource,
+    personnel_nr: i32,
+    limit: i64,
+) -> Result<Vec<domain::PasswordHash>, IdentityServerError> {
+    let stmt = client
+        .prepare(&format!(
+            "SELECT salt, password \
+        FROM {} \
+        WHERE personnel_nr = $1 \
+        ORDER BY created DESC \
+        LIMIT $2",
+            source.password_history
+        ))
+        .await?;
+
+    let rows = client.query(&stmt, &[&personnel_nr, &limit]).await?;
+
+    Ok(rows.into_iter().map(|r| r.into()).collect())
+}
+
+/// Stores new password hash, moves the current one into history.
+/// `must_change` marks password as temporary (reset by admin)
+pub async fn update_password(
+    client: &mut Client,
+    source: &UserSource,
+    personnel_nr: i32,
+    new_password: &domain::PasswordHash,
+    must_change: bool,
+) -> Result<(), IdentityServerError> {
+    source.check_writable()?;
+    let transaction = client.transaction().await?;
+
+    transaction
+        .execute(
+            &format!(
+                "INSERT INTO {} (personnel_nr, salt, password, created) \
+        SELECT personnel_nr, salt, password, password_changed \
+        FROM {} WHERE personnel_nr = $1",
+                source.password_history, source.users
+            ),
+            &[&personnel_nr],
+        )
+        .await?;
+
+    transaction
+        .execute(
+            &format!(
+                "UPDATE {} \
+        SET salt = $2, password = $3, password_changed = now(), must_change_password = $4 \
+        WHERE personnel_nr = $1",
+                source.users
+            ),
+            &[
+                &personnel_nr,
+                &new_password.salt,
+                &new_password.password,
+                &must_change,
+            ],
+        )
+        .await?;
+
+    transaction.commit().await?;
+    Ok(())
+}
+
+pub async fn set_user_disabled(
+    client: &Client,
+    source: &UserSource,
+    personnel_nr: i32,
+    disabled: bool,
+) -> Result<bool, IdentityServerError> {
+    source.check_writable()?;
+    let updated = client
+        .execute(&set_disabled_sql(source), &[&personnel_nr, &disabled])
+        .await?;
+    Ok(updated > 0)
+}
+
+/// Disables user and writes audit record of it in one transaction;
+/// `false` when user does not exist, then nothing is written
+pub async fn disable_user_audited(
+    client: &mut Client,
+    source: &UserSource,
+    personnel_nr: i32,
+    record: &domain::AuditRecord,
+) -> Result<bool, IdentityServerError> {
+    source.check_writable()?;
+    let transaction = client.transaction().await?;
+    let updated = transaction
+        .execute(&set_disabled_sql(source), &[&personnel_nr, &true])
+        .await?;
+    if updated == 0 {
+        return Ok(false);
+    }
+    transaction
+        .execute(
+            INSERT_AUDIT_RECORD,
+            &[
+                &record.realm,
+                &record.actor_personnel_nr,
+                &record.action,
+                &record.target_personnel_nr,
+                &record.details,
+            ],
+        )
+        .await?;
+    transaction.commit().await?;
+    Ok(true)
+}
+
+fn set_disabled_sql(source: &UserSource) -> String {
+    format!(
+        "UPDATE {} SET disabled = $2 WHERE personnel_nr = $1",
+        source.users
+    )
+}
+
+pub async fn insert_user(
+    client: &Client,
+    source: &UserSource,
+    user: &domain::NewUser,
+) -> Result<(), IdentityServerError> {
+    source.check_writable()?;
+    client
+        .execute(
+            &format!(
+                "INSERT INTO {} \
+        (personnel_nr, salt, password, username, email, password_changed, must_change_password, disabled) \
+        VALUES ($1, $2, $3, $4, $5, now(), $6, false)",
+                source.users
+            ),
+            &[
+                &user.personnel_nr,
+                &user.password.salt,
+                &user.password.password,
+                &user.username,
+                &user.email,
+                &user.must_change_password,
+            ],
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn insert_session(
+    client: &Client,
+    session: &domain::StoredSession,
+) -> Result<(), IdentityServerError> {
+    client
+        .execute(
+            "INSERT INTO security.sessions \
+        (token_hash, realm, personnel_nr, actor_personnel_nr, created, expires) \
+        VALUES ($1, $2, $3, $4, $5, $6) \
+        ON CONFLICT (token_hash) DO NOTHING",
+            &[
+                &session.token_hash,
+                &session.realm,
+                &session.personnel_nr,
+                &session.actor_personnel_nr,
+                &session.created,
+                &session.expires,
+            ],
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn revoke_session(
+    client: &Client,
+    token_hash: &str,
+    reason: &str,
+) -> Result<bool, IdentityServerError> {
+    let updated = client
+        .execute(
+            "UPDATE security.sessions SET revoked = now(), revoke_reason = $2 \
+        WHERE token_hash = $1 AND revoked IS NULL",
+            &[&token_hash, &reason],
+        )
+        .await?;
+    Ok(updated > 0)
+}
+
+/// Revocation reason of sessions of user disabled by admin CLI;
+/// servers apply it as if user was disabled through them
+pub const DISABLED_REVOKE_REASON: &str = "disabled";
+
+pub async fn revoke_user_sessions(
+    client: &Client,
+    realm: &str,
+    personnel_nr: i32,
+    reason: &str,
+) -> Result<u64, IdentityServerError> {
+    let updated = client
+        .execute(
+            "UPDATE security.sessions SET revoked = now(), revoke_reason = $3 \
+        WHERE realm = $1 AND (personnel_nr = $2 OR actor_personnel_nr = $2) AND revoked IS NULL",
+            &[&realm, &personnel_nr, &reason],
+        )
+        .await?;
+    Ok(updated)
+}
+
+/// Active sessions, of all users or of one user of the realm
+pub async fn list_sessions(
+    client: &Client,
+    realm: Option<&str>,
+    personnel_nr: Option<i32>,
+) -> Result<Vec<domain::StoredSession>, IdentityServerError> {
+    let rows = client
+        .query(
+            "SELECT token_hash, realm, personnel_nr, actor_personnel_nr, created, expires, revoked, revoke_reason \
+        FROM security.sessions \
+        WHERE revoked IS NULL AND expires > now() \
+        AND ($1::VARCHAR IS NULL OR realm = $1) \
+        AND ($2::INTEGER IS NULL OR personnel_nr = $2) \
+        ORDER BY realm, personnel_nr, created",
+            &[&realm, &personnel_nr],
+        )
+        .await?;
+    Ok(rows.into_iter().map(|r| r.into()).collect())
+}
+
+/// Extends expiry of sessions renewed since they were stored
+pub async fn renew_sessions(
+    client: &Client,
+    token_hashes: &[String],
+    expires: &[DateTime<Utc>],
+) -> Result<u64, IdentityServerError> {
+    let updated = client
+        .execute(
+            "UPDATE security.sessions s SET expires = r.expires \
+        FROM unnest($1::TEXT[], $2::TIMESTAMPTZ[]) AS r(token_hash, expires) \
+        WHERE s.token_hash = r.token_hash AND s.revoked IS NULL AND s.expires < r.expires",
+            &[&token_hashes, &expires],
+        )
+        .await?;
+    Ok(updated)
+}
+
+/// Realm and personnel nr of users disabled by admin CLI after `since`
+pub async fn find_disabled_session_users(
+    client: &Client,
+    since: DateTime<Utc>,
+) -> Result<Vec<(String, i32)>, IdentityServerError> {
+    let rows = client
+        .query(
+            "SELECT DISTINCT realm, personnel_nr FROM security.sessions \
+        WHERE revoked > $1 AND revoke_reason = $2",
+            &[&since, &DISABLED_REVOKE_REASON],
+        )
+        .await?;
+    Ok(rows.iter().map(|r| (r.get(0), r.get(1))).collect())
+}
+
+/// Hashes of tokens revoked after `since`
+pub async fn find_revoked_sessions(
+    client: &Client,
+    since: DateTime<Utc>,
+) -> Result<Vec<String>, IdentityServerError> {
+    let rows = client
+        .query(
+            "SELECT token_hash FROM security.sessions WHERE revoked > $1",
+            &[&since],
+        )
+        .await?;
+    Ok(rows.iter().map(|r| r.get(0)).collect())
+}
+
+pub async fn insert_webauthn_credential(
+    client: &Client,
+    credential: &domain::WebAuthnCredential,
+) -> Result<(), IdentityServerError> {
+    client
+        .execute(
+            "INSERT INTO security.webauthn_credentials \
+        (credential_id, realm, personnel_nr, public_key, sign_count) \
+        VALUES ($1, $2, $3, $4, $5)",
+            &[
+                &credential.credential_id,
+                &credential.realm,
+                &credential.personnel_nr,
+                &credential.public_key,
+                &credential.sign_count,
+            ],
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn find_webauthn_credential(
+    client: &Client,
+    credential_id: &str,
+) -> Result<Option<domain::WebAuthnCredential>, IdentityServerError> {
+    let row = client
+        .query_opt(
+            "SELECT credential_id, realm, personnel_nr, public_key, sign_count \
+        FROM security.webauthn_credentials WHERE credential_id = $1",
+            &[&credential_id],
+        )
+        .await?;
+    Ok(row.map(|r| r.into()))
+}
+
+/// Ids of passkeys of user of the realm
+pub async fn find_webauthn_credential_ids(
+    client: &Client,
+    realm: &str,
+    personnel_nr: i32,
+) -> Result<Vec<String>, IdentityServerError> {
+    let rows = client
+        .query(
+            "SELECT credential_id FROM security.webauthn_credentials \
+        WHERE realm = $1 AND personnel_nr = $2 \
+        ORDER BY created",
+            &[&realm, &personnel_nr],
+        )
+        .await?;
+    Ok(rows.iter().map(|r| r.get(0)).collect())
+}
+
+/// Stores grown signature counter (authenticators without counter stay at 0);
+/// `false` when counter was meanwhile advanced by another login
+pub async fn update_webauthn_sign_count(
+    client: &Client,
+    credential_id: &str,
+    sign_count: i64,
+) -> Result<bool, IdentityServerError> {
+    let updated = client
+        .execute(
+            "UPDATE security.webauthn_credentials SET sign_count = $2, last_used = now() \
+        WHERE credential_id = $1 AND (sign_count < $2 OR (sign_count = 0 AND $2 = 0))",
+            &[&credential_id, &sign_count],
+        )
+        .await?;
+    Ok(updated > 0)
+}
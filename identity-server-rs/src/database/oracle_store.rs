@@ -0,0 +1,148 @@
+use actix_web::web;
+use chrono::{TimeZone, Utc};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::domain::User;
+use crate::config::OracleConfig;
+use crate::errors::IdentityServerError;
+
+// OCI connections can not leave the thread they were opened on,
+// so every thread of the blocking pool keeps own connections
+thread_local! {
+    static CONNECTIONS: RefCell<HashMap<String, oracle::Connection>> = RefCell::new(HashMap::new());
+}
+
+/// columns in order of `OracleUser::from_resultset`;
+/// `password_changed` as seconds since epoch, flags as numbers
+const USER_COLUMNS: &str = "personnel_nr, salt, password, username, email, \
+    ROUND((CAST(SYS_EXTRACT_UTC(CAST(password_changed AS TIMESTAMP)) AS DATE) - DATE '1970-01-01') * 86400), \
+    NVL(must_change_password, 0), NVL(disabled, 0)";
+
+/// Read-only users table in Oracle, queried on the blocking thread pool
+#[derive(Debug, Clone)]
+pub struct OracleUsers {
+    config: Arc<OracleConfig>,
+    users: String,
+}
+
+impl OracleUsers {
+    pub fn new(config: &OracleConfig, users: &str) -> Self {
+        Self {
+            config: Arc::new(config.clone()),
+            users: users.to_owned(),
+        }
+    }
+
+    pub async fn find_users_by_login(&self, login: &str) -> Result<Vec<User>, IdentityServerError> {
+        let login = login.trim();
+        let personnel_nr = login.parse::<i32>().ok();
+        // every occurrence of a placeholder is bound by own position
+        let sql = format!(
+            "SELECT {} FROM {} \
+        WHERE personnel_nr = :1 OR lower(username) = lower(:2) OR lower(email) = lower(:3)",
+            USER_COLUMNS, self.users
+        );
+        self.query_users(sql, personnel_nr, Some(login.to_owned()))
+            .await
+    }
+
+    pub async fn find_user_by_personnel_nr(
+        &self,
+        personnel_nr: i32,
+    ) -> Result<Option<User>, IdentityServerError> {
+        let sql = format!(
+            "SELECT {} FROM {} WHERE personnel_nr = :1",
+            USER_COLUMNS, self.users
+        );
+        Ok(self.query_users(sql, Some(personnel_nr), None).await?.pop())
+    }
+
+    /// Runs query of users with personnel nr bound to `:1` and login to `:2` and `:3`
+    async fn query_users(
+        &self,
+        sql: String,
+        personnel_nr: Option<i32>,
+        login: Option<String>,
+    ) -> Result<Vec<User>, IdentityServerError> {
+        let config = self.config.clone();
+        let users = web::block(move || {
+            with_connection(&config, |conn| {
+                let mut query = conn
+                    .make_query::<OracleUser>(&sql)?
+                    .prefetch_rows(10)?
+                    .bind(1, personnel_nr)?;
+                if let Some(login) = &login {
+                    query = query.bind(2, login.as_str())?.bind(3, login.as_str())?;
+                }
+                query.fetch_list()
+            })
+        })
+        .await??;
+        Ok(users.into_iter().map(|it| it.0).collect())
+    }
+}
+
+/// Runs `f` with connection of current thread; connection is dropped after an error,
+/// so the next call reconnects
+fn with_connection<R>(
+    config: &OracleConfig,
+    f: impl FnOnce(&oracle::Connection) -> Result<R, oracle::OracleError>,
+) -> Result<R, oracle::OracleError> {
+    let key = format!("{}@{}", config.username, config.db);
+    CONNECTIONS.with(|connections| {
+        let mut connections = connections.borrow_mut();
+        if !connections.contains_key(&key) {
+            let conn = oracle::connect(&config.db, &config.username, &config.password)?;
+            connections.insert(key.clone(), conn);
+        }
+        let result = f(&connections[&key]);
+        if result.is_err() {
+            connections.remove(&key);
+        }
+        result
+    })
+}
+
+struct OracleUser(User);
+
+impl oracle::FromResultSet for OracleUser {
+    fn from_resultset(rs: &oracle::ResultSet) -> Self {
+        let email: String = (&rs[4]).into();
+        let password_changed: i64 = (&rs[5]).into();
+        let must_change_password: i32 = (&rs[6]).into();
+        let disabled: i32 = (&rs[7]).into();
+        OracleUser(User {
+            personnel_nr: (&rs[0]).into(),
+            salt: (&rs[1]).into(),
+            password: (&rs[2]).into(),
+            username: (&rs[3]).into(),
+            // in Oracle NULL string is empty string
+            email: Some(email).filter(|it| !it.is_empty()),
+            password_changed: Utc
+                .timestamp_opt(password_changed, 0)
+                .single()
+                .unwrap_or_default(),
+            must_change_password: must_change_password != 0,
+            disabled: disabled != 0,
+        })
+    }
+}
+
+impl oracle::DescriptorsProvider for OracleUser {
+    fn sql_descriptors() -> Vec<oracle::TypeDescriptor> {
+        use oracle::TypeDescriptorProducer;
+
+        vec![
+            i32::produce(),
+            String::produce_sized(64),
+            String::produce_sized(128),
+            String::produce_sized(128),
+            String::produce_sized(256),
+            i64::produce(),
+            i32::produce(),
+            i32::produce(),
+        ]
+    }
+}
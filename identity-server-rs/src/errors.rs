@@ -1,3 +1,4 @@
+use actix_web::error::BlockingError;
 use actix_web::http::header::ContentType;
 use actix_web::http::StatusCode;
 use actix_web::{error, HttpResponse};
@@ -10,6 +11,9 @@ pub enum IdentityServerError {
     NotFound,
     PGError(PGError),
     PoolError(PoolError),
+    #[cfg(feature = "oracle")]
+    OracleError(oracle::OracleError),
+    BlockingError(BlockingError),
     #[display(fmt = "Validation error with: {}", reason)]
     ValidationError {
         reason: String,
@@ -25,12 +29,29 @@ impl std::convert::From<tokio_postgres::Error> for IdentityServerError {
     }
 }
 
+#[cfg(feature = "oracle")]
+impl std::convert::From<oracle::OracleError> for IdentityServerError {
+    fn from(error: oracle::OracleError) -> Self {
+        IdentityServerError::OracleError(error)
+    }
+}
+
+impl std::convert::From<BlockingError> for IdentityServerError {
+    fn from(error: BlockingError) -> Self {
+        IdentityServerError::BlockingError(error)
+    }
+}
+
 impl IdentityServerError {
     pub fn authanticationError(reason: &str) -> IdentityServerError {
-        IdentityServerError::AuthenticationError { reason: reason.to_owned() }
+        IdentityServerError::AuthenticationError {
+            reason: reason.to_owned(),
+        }
     }
     pub fn validationError(reason: &str) -> IdentityServerError {
-        IdentityServerError::ValidationError { reason: reason.to_owned() }
+        IdentityServerError::ValidationError {
+            reason: reason.to_owned(),
+        }
     }
 }
 
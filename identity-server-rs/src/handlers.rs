@@ -1,13 +1,79 @@
 use actix_web::dev::HttpServiceFactory;
+use actix_web::http::header;
 use actix_web::web::{Data, ReqData};
-use actix_web::{get, post, web, HttpMessage, HttpRequest, HttpResponse, Responder, Result};
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder, Result};
+use chrono::Duration;
 use deadpool_postgres::{Client, Pool};
 use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-use crate::database::{count_of_roles, find_user_by_name};
+use crate::config::{AdminConfig, EventsConfig, ImpersonationConfig, DEFAULT_REALM};
+use crate::database::domain::{AuditRecord, PasswordHash};
+use crate::database::{
+    count_of_roles, disable_user_audited, find_password_history, find_user_by_login,
+    find_user_by_personnel_nr, find_user_roles, find_webauthn_credential,
+    find_webauthn_credential_ids, insert_audit_record, insert_webauthn_credential, update_password,
+    update_webauthn_sign_count,
+};
 use crate::errors::IdentityServerError;
-use crate::identity::{AuthTokenContext, AuthenticattionInfoContext, Authorization, Identity};
+use crate::identity::{
+    AuthTokenContext, AuthenticatedUser, AuthenticationResponse, AuthenticattionInfoContext,
+    Authorization, Identity, SessionEvent, AUTH_TOKEN_COOKIE,
+};
+use crate::openapi;
+use crate::sse::session_event_stream;
+use crate::webauthn::{
+    CreationOptions, LoginResponse, RegistrationResponse, RequestOptions, WebAuthn,
+};
 
+/// Registers all routes of the API; used by server and by route checks of OpenAPI document
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(hello)
+        .service(login)
+        .service(logout)
+        // before `/auth` scope, which would shadow it
+        .service(verify)
+        .service(auth_scope())
+        .service(admin_scope())
+        .service(password_scope())
+        .service(events_scope())
+        .service(realm_scope())
+        .service(webauthn_scope())
+        .service(openapi::openapi_json);
+
+    #[cfg(feature = "swagger-ui")]
+    cfg.service(openapi::swagger_ui());
+}
+
+/// Method and path of every route registered by `configure`, except Swagger UI;
+/// route checks compare it with the routed app and with the OpenAPI document
+pub const ROUTES: [(&str, &str); 17] = [
+    ("GET", "/"),
+    ("POST", "/login"),
+    ("POST", "/logout"),
+    ("GET", "/auth/verify"),
+    ("GET", "/auth/info"),
+    ("POST", "/admin/impersonate/{personnel_nr}"),
+    ("DELETE", "/admin/impersonate"),
+    ("POST", "/admin/users/{personnel_nr}/disable"),
+    ("POST", "/password/change"),
+    ("GET", "/events/sessions"),
+    ("POST", "/realms/{realm}/login"),
+    ("GET", "/realms/{realm}/auth/info"),
+    ("POST", "/webauthn/login/start"),
+    ("POST", "/webauthn/login/finish"),
+    ("POST", "/webauthn/register/start"),
+    ("POST", "/webauthn/register/finish"),
+    ("GET", "/openapi.json"),
+];
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "health",
+    responses((status = 200, description = "Database is reachable", body = String))
+)]
 #[get("/")]
 pub async fn hello(db_pool: Data<Pool>) -> Result<HttpResponse> {
     let client: Client = db_pool
@@ -21,45 +87,143 @@ pub async fn hello(db_pool: Data<Pool>) -> Result<HttpResponse> {
     Ok(HttpResponse::Ok().body(response))
 }
 
+pub fn admin_scope() -> impl HttpServiceFactory {
+    web::scope("/admin")
+        .wrap(Authorization::enable())
+        .service(impersonate)
+        .service(end_impersonation)
+        .service(disable_user)
+}
+
+pub fn events_scope() -> impl HttpServiceFactory {
+    web::scope("/events")
+        .wrap(Authorization::enable())
+        .service(session_events)
+}
+
+pub fn password_scope() -> impl HttpServiceFactory {
+    web::scope("/password")
+        .wrap(Authorization::enable_for_password_change())
+        .service(change_password)
+}
+
 pub fn auth_scope() -> impl HttpServiceFactory {
     web::scope("/auth")
         .wrap(Authorization::enable())
         .service(auth_info)
 }
 
-#[derive(Deserialize)]
+/// Login and session info of a named realm; routes without realm serve `default` realm
+pub fn realm_scope() -> impl HttpServiceFactory {
+    web::scope("/realms/{realm}").service(realm_login).service(
+        web::scope("/auth")
+            .wrap(Authorization::enable().with_path_realm())
+            .service(realm_auth_info),
+    )
+}
+
+#[derive(Deserialize, ToSchema)]
 pub struct UsernamePasswordCredentials {
+    /// personnel nr, username or email
     username: String,
     password: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/login",
+    tag = "auth",
+    request_body = UsernamePasswordCredentials,
+    responses(
+        (status = 200, description = "Session opened", body = AuthenticationResponse),
+        (status = 401, description = "Unknown user, wrong password or disabled user"),
+    )
+)]
 #[post("/login")]
 pub async fn login(
     db_pool: Data<Pool>,
     identity: Data<Identity>,
     credentials: web::Json<UsernamePasswordCredentials>,
 ) -> Result<impl Responder> {
+    let response = authenticate(DEFAULT_REALM, &db_pool, &identity, &credentials).await?;
+    Ok(web::Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/realms/{realm}/login",
+    tag = "auth",
+    params(("realm" = String, Path, description = "name of realm")),
+    request_body = UsernamePasswordCredentials,
+    responses(
+        (status = 200, description = "Session opened", body = AuthenticationResponse),
+        (status = 401, description = "Unknown user, wrong password or disabled user"),
+        (status = 404, description = "Unknown realm"),
+    )
+)]
+#[post("/login")]
+pub async fn realm_login(
+    db_pool: Data<Pool>,
+    identity: Data<Identity>,
+    realm: web::Path<String>,
+    credentials: web::Json<UsernamePasswordCredentials>,
+) -> Result<impl Responder> {
+    let response = authenticate(&realm, &db_pool, &identity, &credentials).await?;
+    Ok(web::Json(response))
+}
+
+async fn authenticate(
+    realm: &str,
+    db_pool: &Pool,
+    identity: &Identity,
+    credentials: &UsernamePasswordCredentials,
+) -> Result<AuthenticationResponse> {
+    let source = identity.realm(realm)?.user_source();
     let client: Client = db_pool
         .get()
         .await
         .map_err(IdentityServerError::PoolError)?;
-    let maybe_user = find_user_by_name(&client, &credentials.username).await?;
+    let maybe_user = find_user_by_login(&client, source, &credentials.username).await?;
 
     let user = maybe_user.ok_or(actix_web::error::ErrorUnauthorized(
         "Utilizatorul cu acest nume nu este autentificat",
     ))?;
 
+    if user.disabled {
+        return Err(actix_web::error::ErrorUnauthorized(
+            "Utilizatorul este blocat",
+        ));
+    }
+
     log::info!("authenticated user: {:?}", &user);
 
-    let response = identity.authenticate(user, &credentials.password)?;
+    let roles = find_user_roles(&client, source, user.personnel_nr).await?;
+    let response = identity.authenticate(realm, user, roles, &credentials.password)?;
 
-    Ok(web::Json(response))
+    Ok(response)
 }
 
+#[utoipa::path(
+    get,
+    path = "/auth/info",
+    tag = "auth",
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Current session", body = AuthenticatedUser),
+        (status = 401, description = "Session is invalid or expired"),
+        (status = 403, description = "Password change required"),
+    )
+)]
 #[get("/info")]
 pub async fn auth_info(
     auth_context: Option<ReqData<AuthenticattionInfoContext>>,
 ) -> Result<impl Responder> {
+    auth_info_response(auth_context)
+}
+
+fn auth_info_response(
+    auth_context: Option<ReqData<AuthenticattionInfoContext>>,
+) -> Result<web::Json<Arc<AuthenticatedUser>>> {
     let auth_context = auth_context.ok_or(actix_web::error::ErrorInternalServerError(
         "Authentication info context not found in application",
     ))?;
@@ -69,6 +233,111 @@ pub async fn auth_info(
     Ok(web::Json(auth_user))
 }
 
+#[utoipa::path(
+    get,
+    path = "/realms/{realm}/auth/info",
+    tag = "auth",
+    params(("realm" = String, Path, description = "name of realm")),
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Current session", body = AuthenticatedUser),
+        (status = 401, description = "Session is invalid, expired or of another realm"),
+        (status = 403, description = "Password change required"),
+    )
+)]
+#[get("/info")]
+pub async fn realm_auth_info(
+    auth_context: Option<ReqData<AuthenticattionInfoContext>>,
+) -> Result<impl Responder> {
+    auth_info_response(auth_context)
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct VerifyQuery {
+    /// user must have this role
+    role: Option<String>,
+    /// session must belong to this realm
+    realm: Option<String>,
+}
+
+/// Forward auth for reverse proxies (nginx `auth_request`, Traefik `ForwardAuth`).
+/// Registered outside of `/auth` scope: proxies expect 401 for every invalid session
+/// and the identity of the user in response headers instead of body.
+#[utoipa::path(
+    get,
+    path = "/auth/verify",
+    tag = "auth",
+    params(VerifyQuery),
+    security(("token" = []), ("cookie" = [])),
+    responses(
+        (status = 200, description = "Session is valid", headers(
+            ("X-Auth-Realm" = String, description = "realm of the session"),
+            ("X-Auth-User" = String, description = "username"),
+            ("X-Auth-Personnel-Nr" = i32, description = "personnel nr"),
+            ("X-Auth-Roles" = String, description = "comma separated roles"),
+            ("X-Auth-Email" = String, description = "email, if user has one"),
+        )),
+        (status = 401, description = "Session is invalid, expired, restricted or of another realm"),
+        (status = 403, description = "User has not the required role"),
+    )
+)]
+#[get("/auth/verify")]
+pub async fn verify(
+    req: HttpRequest,
+    identity: Data<Identity>,
+    token_context: Option<ReqData<AuthTokenContext>>,
+    query: web::Query<VerifyQuery>,
+) -> Result<HttpResponse> {
+    // the only endpoint falling back to cookie: it changes nothing, so ambient credentials are harmless
+    let token = match (token_context, req.cookie(AUTH_TOKEN_COOKIE)) {
+        (Some(token_context), _) => token_context.token.to_string(),
+        (None, Some(cookie)) => cookie.value().to_owned(),
+        (None, None) => return Ok(HttpResponse::Unauthorized().finish()),
+    };
+
+    let session = match identity.authorization_info(&token) {
+        Ok(session) if !session.password_change_required() => session,
+        _ => return Ok(HttpResponse::Unauthorized().finish()),
+    };
+
+    if let Some(realm) = &query.realm {
+        if realm != session.realm() {
+            return Ok(HttpResponse::Unauthorized().finish());
+        }
+    }
+
+    if let Some(role) = &query.role {
+        if !session.has_role(role) {
+            return Ok(HttpResponse::Forbidden().finish());
+        }
+    }
+
+    let mut response = HttpResponse::Ok();
+    response
+        .insert_header(("X-Auth-Realm", header_value(session.realm())?))
+        .insert_header(("X-Auth-User", header_value(session.username())?))
+        .insert_header(("X-Auth-Personnel-Nr", session.personnel_nr().to_string()))
+        .insert_header(("X-Auth-Roles", header_value(&session.roles().join(","))?));
+    if let Some(email) = session.email() {
+        response.insert_header(("X-Auth-Email", header_value(email)?));
+    }
+
+    Ok(response.finish())
+}
+
+// names are not restricted to ASCII, so raw UTF-8 bytes are passed through
+fn header_value(value: &str) -> Result<header::HeaderValue> {
+    header::HeaderValue::from_bytes(value.as_bytes())
+        .map_err(actix_web::error::ErrorInternalServerError)
+}
+
+#[utoipa::path(
+    post,
+    path = "/logout",
+    tag = "auth",
+    security(("token" = [])),
+    responses((status = 200, description = "Session closed"))
+)]
 #[post("/logout")]
 pub async fn logout(
     identity: Data<Identity>,
@@ -81,3 +350,511 @@ pub async fn logout(
     identity.logout(token)?;
     Ok(HttpResponse::Ok().finish())
 }
+
+#[utoipa::path(
+    post,
+    path = "/admin/impersonate/{personnel_nr}",
+    tag = "admin",
+    params(("personnel_nr" = i32, Path, description = "user to impersonate")),
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Impersonation session opened", body = AuthenticationResponse),
+        (status = 403, description = "Impersonation is not allowed or user is disabled"),
+        (status = 404, description = "User not found"),
+    )
+)]
+#[post("/impersonate/{personnel_nr}")]
+pub async fn impersonate(
+    db_pool: Data<Pool>,
+    identity: Data<Identity>,
+    config: Data<ImpersonationConfig>,
+    personnel_nr: web::Path<i32>,
+    token_context: ReqData<AuthTokenContext>,
+    auth_context: ReqData<AuthenticattionInfoContext>,
+) -> Result<impl Responder> {
+    let actor = &auth_context.auth_info;
+    if !actor.has_role(&config.role) {
+        return Err(actix_web::error::ErrorForbidden(
+            "Impersonation is not allowed for this user",
+        ));
+    }
+
+    let client: Client = db_pool
+        .get()
+        .await
+        .map_err(IdentityServerError::PoolError)?;
+
+    // impersonated user belongs to the realm of the actor
+    let source = identity.realm(actor.realm())?.user_source();
+    let personnel_nr = personnel_nr.into_inner();
+    let user = find_user_by_personnel_nr(&client, source, personnel_nr)
+        .await?
+        .ok_or(IdentityServerError::NotFound)?;
+    if user.disabled {
+        return Err(actix_web::error::ErrorForbidden(
+            "Disabled user can not be impersonated",
+        ));
+    }
+    let roles = find_user_roles(&client, source, personnel_nr).await?;
+
+    let response = identity.impersonate(
+        &token_context.token,
+        user,
+        roles,
+        Duration::minutes(config.minutes),
+    )?;
+
+    let record = AuditRecord {
+        realm: actor.realm().to_owned(),
+        actor_personnel_nr: actor.personnel_nr(),
+        action: "impersonation.start",
+        target_personnel_nr: Some(personnel_nr),
+        details: Some(format!("lifetime: {} minutes", config.minutes)),
+    };
+    // impersonation without audit record must not stay usable
+    if let Err(err) = insert_audit_record(&client, &record).await {
+        identity.logout(response.token())?;
+        return Err(err.into());
+    }
+
+    log::warn!(
+        "user {} impersonates user {}",
+        actor.personnel_nr(),
+        personnel_nr
+    );
+
+    Ok(web::Json(response))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/impersonate",
+    tag = "admin",
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Own session of the actor", body = AuthenticationResponse),
+        (status = 400, description = "Session is not an impersonation"),
+    )
+)]
+#[delete("/impersonate")]
+pub async fn end_impersonation(
+    db_pool: Data<Pool>,
+    identity: Data<Identity>,
+    token_context: ReqData<AuthTokenContext>,
+    auth_context: ReqData<AuthenticattionInfoContext>,
+) -> Result<impl Responder> {
+    let impersonated = &auth_context.auth_info;
+
+    // recorded before session ends, so a failed insert leaves impersonation as it was
+    if let Some(actor) = impersonated.actor() {
+        let client: Client = db_pool
+            .get()
+            .await
+            .map_err(IdentityServerError::PoolError)?;
+        let record = AuditRecord {
+            realm: impersonated.realm().to_owned(),
+            actor_personnel_nr: actor.personnel_nr(),
+            action: "impersonation.end",
+            target_personnel_nr: Some(impersonated.personnel_nr()),
+            details: None,
+        };
+        insert_audit_record(&client, &record).await?;
+    }
+
+    let response = identity.end_impersonation(&token_context.token)?;
+
+    if let Some(actor) = impersonated.actor() {
+        log::warn!(
+            "user {} ended impersonation of user {}",
+            actor.personnel_nr(),
+            impersonated.personnel_nr()
+        );
+    }
+
+    Ok(web::Json(response))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PasswordChange {
+    password: String,
+    new_password: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/password/change",
+    tag = "password",
+    request_body = PasswordChange,
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "New regular session", body = AuthenticationResponse),
+        (status = 400, description = "New password violates password policy"),
+        (status = 401, description = "Current password is wrong"),
+    )
+)]
+#[post("/change")]
+pub async fn change_password(
+    db_pool: Data<Pool>,
+    identity: Data<Identity>,
+    token_context: ReqData<AuthTokenContext>,
+    auth_context: ReqData<AuthenticattionInfoContext>,
+    change: web::Json<PasswordChange>,
+) -> Result<impl Responder> {
+    let personnel_nr = auth_context.auth_info.personnel_nr();
+    let realm = identity.realm(auth_context.auth_info.realm())?;
+    let source = realm.user_source();
+
+    if change.new_password.chars().count() < realm.password_min_length() {
+        return Err(IdentityServerError::validationError(&format!(
+            "Parola trebuie sa contina cel putin {} caractere",
+            realm.password_min_length()
+        ))
+        .into());
+    }
+
+    let mut client: Client = db_pool
+        .get()
+        .await
+        .map_err(IdentityServerError::PoolError)?;
+
+    let user = find_user_by_personnel_nr(&client, source, personnel_nr)
+        .await?
+        .ok_or(IdentityServerError::NotFound)?;
+
+    let current = PasswordHash {
+        salt: user.salt.clone(),
+        password: user.password.clone(),
+    };
+    if !realm.password_matches(&current, &change.password) {
+        return Err(actix_web::error::ErrorUnauthorized("Parola este incorecta"));
+    }
+
+    let history =
+        find_password_history(&client, source, personnel_nr, realm.password_history()).await?;
+    let reused = std::iter::once(&current)
+        .chain(history.iter())
+        .any(|hash| realm.password_matches(hash, &change.new_password));
+    if reused {
+        return Err(
+            IdentityServerError::validationError("Parola a fost deja folosita anterior").into(),
+        );
+    }
+
+    let new_password = realm.new_password_hash(&change.new_password);
+    update_password(&mut client, source, personnel_nr, &new_password, false).await?;
+
+    let user = find_user_by_personnel_nr(&client, source, personnel_nr)
+        .await?
+        .ok_or(IdentityServerError::NotFound)?;
+    let roles = find_user_roles(&client, source, personnel_nr).await?;
+
+    // replace restricted (or old) session with a regular one
+    identity.logout(&token_context.token)?;
+    let response = identity.open_session(realm.name(), user, roles)?;
+
+    Ok(web::Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/users/{personnel_nr}/disable",
+    tag = "admin",
+    params(("personnel_nr" = i32, Path, description = "user to disable")),
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "User disabled, sessions revoked"),
+        (status = 403, description = "User management is not allowed"),
+        (status = 404, description = "User not found"),
+    )
+)]
+#[post("/users/{personnel_nr}/disable")]
+pub async fn disable_user(
+    db_pool: Data<Pool>,
+    identity: Data<Identity>,
+    config: Data<AdminConfig>,
+    personnel_nr: web::Path<i32>,
+    auth_context: ReqData<AuthenticattionInfoContext>,
+) -> Result<HttpResponse> {
+    let actor = &auth_context.auth_info;
+    if !actor.has_role(&config.role) {
+        return Err(actix_web::error::ErrorForbidden(
+            "User management is not allowed for this user",
+        ));
+    }
+
+    let mut client: Client = db_pool
+        .get()
+        .await
+        .map_err(IdentityServerError::PoolError)?;
+
+    // admin manages users of own realm
+    let realm = actor.realm();
+    let personnel_nr = personnel_nr.into_inner();
+    let record = AuditRecord {
+        realm: realm.to_owned(),
+        actor_personnel_nr: actor.personnel_nr(),
+        action: "user.disable",
+        target_personnel_nr: Some(personnel_nr),
+        details: None,
+    };
+    if !disable_user_audited(
+        &mut client,
+        identity.realm(realm)?.user_source(),
+        personnel_nr,
+        &record,
+    )
+    .await?
+    {
+        return Err(IdentityServerError::NotFound.into());
+    }
+    // sessions are revoked only once disabling is committed together with its audit record
+    identity.disable_user(realm, personnel_nr)?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[utoipa::path(
+    get,
+    path = "/events/sessions",
+    tag = "events",
+    params(("Last-Event-ID" = Option<u64>, Header, description = "resume after this event")),
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Stream of session events", body = SessionEvent, content_type = "text/event-stream"),
+        (status = 403, description = "Session events are not allowed"),
+    )
+)]
+#[get("/sessions")]
+pub async fn session_events(
+    request: HttpRequest,
+    identity: Data<Identity>,
+    config: Data<EventsConfig>,
+    auth_context: ReqData<AuthenticattionInfoContext>,
+) -> Result<HttpResponse> {
+    if !auth_context.auth_info.has_role(&config.role) {
+        return Err(actix_web::error::ErrorForbidden(
+            "Session events are not allowed for this user",
+        ));
+    }
+
+    let last_event_id = request
+        .headers()
+        .get("Last-Event-ID")
+        .and_then(|it| it.to_str().ok())
+        .and_then(|it| it.trim().parse::<u64>().ok());
+
+    let subscription = identity.events().subscribe(last_event_id);
+    let keep_alive = std::time::Duration::from_secs(config.keep_alive_seconds);
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(session_event_stream(subscription, keep_alive)))
+}
+
+/// Passkey login is public, registration needs a regular session
+pub fn webauthn_scope() -> impl HttpServiceFactory {
+    web::scope("/webauthn")
+        .service(webauthn_login_start)
+        .service(webauthn_login_finish)
+        .service(
+            web::scope("/register")
+                .wrap(Authorization::enable())
+                .service(webauthn_register_start)
+                .service(webauthn_register_finish),
+        )
+}
+
+#[utoipa::path(
+    post,
+    path = "/webauthn/register/start",
+    tag = "webauthn",
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Options for navigator.credentials.create()", body = CreationOptions),
+        (status = 403, description = "Passkeys can not be registered during impersonation"),
+    )
+)]
+#[post("/start")]
+pub async fn webauthn_register_start(
+    db_pool: Data<Pool>,
+    webauthn: Data<WebAuthn>,
+    auth_context: ReqData<AuthenticattionInfoContext>,
+) -> Result<impl Responder> {
+    let session = &auth_context.auth_info;
+    if session.actor().is_some() {
+        return Err(actix_web::error::ErrorForbidden(
+            "Passkeys can not be registered during impersonation",
+        ));
+    }
+
+    let client: Client = db_pool
+        .get()
+        .await
+        .map_err(IdentityServerError::PoolError)?;
+    let registered =
+        find_webauthn_credential_ids(&client, session.realm(), session.personnel_nr()).await?;
+
+    let options = webauthn.start_registration(
+        session.realm(),
+        session.personnel_nr(),
+        session.username(),
+        &registered,
+    );
+    Ok(web::Json(options))
+}
+
+#[utoipa::path(
+    post,
+    path = "/webauthn/register/finish",
+    tag = "webauthn",
+    request_body = RegistrationResponse,
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Passkey registered"),
+        (status = 400, description = "Invalid or expired registration"),
+        (status = 403, description = "Passkeys can not be registered during impersonation"),
+    )
+)]
+#[post("/finish")]
+pub async fn webauthn_register_finish(
+    db_pool: Data<Pool>,
+    webauthn: Data<WebAuthn>,
+    auth_context: ReqData<AuthenticattionInfoContext>,
+    response: web::Json<RegistrationResponse>,
+) -> Result<HttpResponse> {
+    let session = &auth_context.auth_info;
+    // challenge of the target user may come from elsewhere, so start alone does not guard it
+    if session.actor().is_some() {
+        return Err(actix_web::error::ErrorForbidden(
+            "Passkeys can not be registered during impersonation",
+        ));
+    }
+
+    let credential =
+        webauthn.finish_registration(session.realm(), session.personnel_nr(), &response)?;
+
+    let client: Client = db_pool
+        .get()
+        .await
+        .map_err(IdentityServerError::PoolError)?;
+    insert_webauthn_credential(&client, &credential).await?;
+
+    let record = AuditRecord {
+        realm: session.realm().to_owned(),
+        actor_personnel_nr: session.personnel_nr(),
+        action: "webauthn.register",
+        target_personnel_nr: Some(session.personnel_nr()),
+        details: Some(format!("credential: {}", credential.credential_id)),
+    };
+    insert_audit_record(&client, &record).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct WebAuthnLoginStart {
+    /// personnel nr, username or email; without it a discoverable passkey is expected
+    username: Option<String>,
+    /// realm of the user, `default` if not set
+    realm: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/webauthn/login/start",
+    tag = "webauthn",
+    request_body = WebAuthnLoginStart,
+    responses(
+        (status = 200, description = "Options for navigator.credentials.get()", body = RequestOptions),
+        (status = 404, description = "Unknown realm"),
+    )
+)]
+#[post("/login/start")]
+pub async fn webauthn_login_start(
+    db_pool: Data<Pool>,
+    identity: Data<Identity>,
+    webauthn: Data<WebAuthn>,
+    request: web::Json<WebAuthnLoginStart>,
+) -> Result<impl Responder> {
+    let realm = identity.realm(request.realm.as_deref().unwrap_or(DEFAULT_REALM))?;
+
+    let (personnel_nr, allowed) = match &request.username {
+        Some(username) => {
+            let client: Client = db_pool
+                .get()
+                .await
+                .map_err(IdentityServerError::PoolError)?;
+            // unknown user gets the same answer as a user without passkeys
+            match find_user_by_login(&client, realm.user_source(), username).await? {
+                Some(user) => {
+                    let allowed =
+                        find_webauthn_credential_ids(&client, realm.name(), user.personnel_nr)
+                            .await?;
+                    (Some(user.personnel_nr), allowed)
+                }
+                None => (None, vec![]),
+            }
+        }
+        None => (None, vec![]),
+    };
+
+    Ok(web::Json(webauthn.start_login(
+        realm.name(),
+        personnel_nr,
+        &allowed,
+    )))
+}
+
+#[utoipa::path(
+    post,
+    path = "/webauthn/login/finish",
+    tag = "webauthn",
+    request_body = LoginResponse,
+    responses(
+        (status = 200, description = "Session opened", body = AuthenticationResponse),
+        (status = 401, description = "Unknown passkey, invalid assertion or disabled user"),
+    )
+)]
+#[post("/login/finish")]
+pub async fn webauthn_login_finish(
+    db_pool: Data<Pool>,
+    identity: Data<Identity>,
+    webauthn: Data<WebAuthn>,
+    response: web::Json<LoginResponse>,
+) -> Result<impl Responder> {
+    let client: Client = db_pool
+        .get()
+        .await
+        .map_err(IdentityServerError::PoolError)?;
+
+    let credential = find_webauthn_credential(&client, response.credential_id())
+        .await?
+        .ok_or(actix_web::error::ErrorUnauthorized(
+            "Cheia de acces nu este inregistrata",
+        ))?;
+    let sign_count = webauthn.finish_login(&response, &credential)?;
+    // counter only grows, a lost race means replayed or cloned authenticator
+    if !update_webauthn_sign_count(&client, &credential.credential_id, sign_count).await? {
+        return Err(actix_web::error::ErrorUnauthorized(
+            "Cheia de acces nu este valida",
+        ));
+    }
+
+    let source = identity.realm(&credential.realm)?.user_source();
+    let user = find_user_by_personnel_nr(&client, source, credential.personnel_nr)
+        .await?
+        .ok_or(actix_web::error::ErrorUnauthorized(
+            "Utilizatorul cu acest nume nu este autentificat",
+        ))?;
+    if user.disabled {
+        return Err(actix_web::error::ErrorUnauthorized(
+            "Utilizatorul este blocat",
+        ));
+    }
+
+    let roles = find_user_roles(&client, source, user.personnel_nr).await?;
+    let response = identity.login(&credential.realm, user, roles)?;
+
+    Ok(web::Json(response))
+}
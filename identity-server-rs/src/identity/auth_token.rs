@@ -2,18 +2,24 @@ use std::future::{ready, Ready};
 
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    web::Data,
     Error, HttpMessage,
 };
 use futures_util::future::LocalBoxFuture;
 
-use super::{AuthTokenContext, Identity};
+use super::AuthTokenContext;
+
+/// cookie with session token, read only by forward auth when request has no `Authorization` header
+/// (browser requests behind reverse proxy); other endpoints never accept ambient cookie credentials
+pub const AUTH_TOKEN_COOKIE: &str = "auth_token";
+
+/// path of forward auth; proxies treat 400 as failure of identity server, so it answers 401 instead
+pub const VERIFY_PATH: &str = "/auth/verify";
 
 // There are two steps in middleware processing.
 // 1. Middleware initialization, middleware factory gets called with
 //    next service in chain as parameter.
 // 2. Middleware's call method gets called with normal request.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct AuthTokenMiddlewareFactory;
 
 impl AuthTokenMiddlewareFactory {
@@ -68,6 +74,9 @@ where
                     Ok(res)
                 })
             }
+            Err(err) if req.path() == VERIFY_PATH => {
+                Box::pin(async { Err(actix_web::error::ErrorUnauthorized(err)) })
+            }
             Err(err) => Box::pin(async { Err(actix_web::error::ErrorBadRequest(err)) }),
         }
     }
@@ -89,7 +98,7 @@ where
         let token = auth_header
             .unwrap()
             .to_str()
-            .map_err(|e| actix_web::error::ErrorBadRequest(e))?;
+            .map_err(actix_web::error::ErrorBadRequest)?;
         let mut segments = token.split(" ");
 
         let auth_type = segments.next().unwrap();
@@ -9,6 +9,8 @@ use super::{AuthTokenContext, AuthenticattionInfoContext, Identity};
 
 pub struct AuthorizationMiddleware<S> {
     service: S,
+    allow_password_change: bool,
+    path_realm: bool,
 }
 
 impl<S, B> Service<ServiceRequest> for AuthorizationMiddleware<S>
@@ -50,6 +52,22 @@ where
 
         let auth_info = identity.unwrap().authorization_info(&auth_token.unwrap());
         match auth_info {
+            Ok(auth_info)
+                if self.path_realm && req.match_info().get("realm") != Some(auth_info.realm()) =>
+            {
+                return Box::pin(async {
+                    Err(actix_web::error::ErrorUnauthorized(
+                        "Session belongs to another realm",
+                    ))
+                });
+            }
+            Ok(auth_info)
+                if auth_info.password_change_required() && !self.allow_password_change =>
+            {
+                return Box::pin(async {
+                    Err(actix_web::error::ErrorForbidden("Password change required"))
+                });
+            }
             Ok(auth_info) => {
                 req.extensions_mut()
                     .insert(AuthenticattionInfoContext::new(auth_info.clone()));
@@ -69,11 +87,31 @@ where
 }
 
 #[derive(Clone)]
-pub struct Authorization {}
+pub struct Authorization {
+    allow_password_change: bool,
+    path_realm: bool,
+}
 
 impl Authorization {
     pub fn enable() -> Self {
-        Self {}
+        Self {
+            allow_password_change: false,
+            path_realm: false,
+        }
+    }
+
+    /// also accepts restricted sessions which must change password
+    pub fn enable_for_password_change() -> Self {
+        Self {
+            allow_password_change: true,
+            path_realm: false,
+        }
+    }
+
+    /// session must belong to the realm of `{realm}` path segment
+    pub fn with_path_realm(mut self) -> Self {
+        self.path_realm = true;
+        self
     }
 }
 
@@ -90,6 +128,10 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(AuthorizationMiddleware { service }))
+        ready(Ok(AuthorizationMiddleware {
+            service,
+            allow_password_change: self.allow_password_change,
+            path_realm: self.path_realm,
+        }))
     }
 }
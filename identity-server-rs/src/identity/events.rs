@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// count of last events kept for `Last-Event-ID` resume
+const REPLAY_CAPACITY: usize = 1024;
+
+#[derive(Serialize, Clone, Debug, ToSchema)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum SessionEventKind {
+    SessionCreated {
+        personnel_nr: i32,
+        // token of new session is never published
+        #[serde(skip)]
+        token: Uuid,
+        expires: DateTime<Utc>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        actor_personnel_nr: Option<i32>,
+    },
+    SessionRevoked {
+        personnel_nr: i32,
+        // without token prefix of the realm
+        token: Uuid,
+        reason: RevocationReason,
+    },
+    UserDisabled {
+        personnel_nr: i32,
+    },
+}
+
+#[derive(Serialize, Clone, Copy, Debug, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RevocationReason {
+    Logout,
+    Expired,
+    Revoked,
+}
+
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct SessionEvent {
+    pub id: u64,
+    pub timestamp: DateTime<Utc>,
+    pub realm: String,
+    #[serde(flatten)]
+    pub kind: SessionEventKind,
+}
+
+impl RevocationReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RevocationReason::Logout => "logout",
+            RevocationReason::Expired => "expired",
+            RevocationReason::Revoked => "revoked",
+        }
+    }
+}
+
+impl SessionEvent {
+    /// name of event in SSE stream
+    pub fn name(&self) -> &'static str {
+        match self.kind {
+            SessionEventKind::SessionCreated { .. } => "session-created",
+            SessionEventKind::SessionRevoked { .. } => "session-revoked",
+            SessionEventKind::UserDisabled { .. } => "user-disabled",
+        }
+    }
+}
+
+/// Subscription to session events
+pub struct Subscription {
+    /// events after requested `Last-Event-ID`
+    pub replay: Vec<SessionEvent>,
+    /// requested event is not in replay buffer anymore; subscriber must drop its cache
+    pub resync: bool,
+    pub receiver: broadcast::Receiver<SessionEvent>,
+}
+
+/// Bus of session events with short replay history.
+/// Publishing and subscribing happen under the same lock,
+/// so a subscriber never misses or duplicates events between replay and live stream.
+pub struct SessionEvents {
+    sender: broadcast::Sender<SessionEvent>,
+    state: Mutex<ReplayBuffer>,
+}
+
+struct ReplayBuffer {
+    last_id: u64,
+    events: VecDeque<SessionEvent>,
+}
+
+impl Default for SessionEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(REPLAY_CAPACITY);
+        Self {
+            sender,
+            state: Mutex::new(ReplayBuffer {
+                last_id: 0,
+                events: VecDeque::with_capacity(REPLAY_CAPACITY),
+            }),
+        }
+    }
+
+    pub fn publish(&self, realm: &str, kind: SessionEventKind) {
+        let mut state = self.state.lock().unwrap();
+        state.last_id += 1;
+        let event = SessionEvent {
+            id: state.last_id,
+            timestamp: Utc::now(),
+            realm: realm.to_owned(),
+            kind,
+        };
+
+        if state.events.len() == REPLAY_CAPACITY {
+            state.events.pop_front();
+        }
+        state.events.push_back(event.clone());
+
+        // error only means there are no subscribers
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self, last_event_id: Option<u64>) -> Subscription {
+        let state = self.state.lock().unwrap();
+
+        let (replay, resync) = match last_event_id {
+            Some(last_id) => {
+                let first_id = state
+                    .events
+                    .front()
+                    .map(|it| it.id)
+                    .unwrap_or(state.last_id.saturating_add(1));
+                let replay = state
+                    .events
+                    .iter()
+                    .filter(|it| it.id > last_id)
+                    .cloned()
+                    .collect();
+                // ids restart from 1 after server restart; id comes from client, so it must not overflow
+                let resync = last_id.saturating_add(1) < first_id || last_id > state.last_id;
+                (replay, resync)
+            }
+            None => (Vec::new(), false),
+        };
+
+        Subscription {
+            replay,
+            resync,
+            receiver: self.sender.subscribe(),
+        }
+    }
+}
@@ -1,6 +1,9 @@
 mod auth_token;
 mod authorization;
+mod events;
+mod realm;
 mod service;
+mod sessions;
 
 #[derive(Clone)]
 pub struct AuthTokenContext {
@@ -28,8 +31,8 @@ impl AuthenticattionInfoContext {
 
 use std::{rc::Rc, sync::Arc};
 
-pub use auth_token::AuthTokenMiddlewareFactory;
+pub use auth_token::{AuthTokenMiddlewareFactory, AUTH_TOKEN_COOKIE};
 pub use authorization::Authorization;
-pub use service::Identity;
-
-use self::service::AuthenticatedUser;
+pub use events::{RevocationReason, SessionEvent, SessionEventKind, SessionEvents, Subscription};
+pub use realm::Realm;
+pub use service::{token_hash, AuthenticatedUser, AuthenticationResponse, Identity};
@@ -0,0 +1,170 @@
+use base64::{decode, encode};
+use chrono::{Duration, Utc};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::{digest, pbkdf2};
+use std::num::NonZeroU32;
+use uuid::Uuid;
+
+use super::sessions::SessionRegistry;
+use crate::config::{PasswordPolicyConfig, RealmConfig, UserStoreKind};
+use crate::database::domain::{PasswordHash, User};
+use crate::database::UserSource;
+
+static PBKDF2_ALG: pbkdf2::Algorithm = pbkdf2::PBKDF2_HMAC_SHA256;
+
+const CREDENTIAL_LEN: usize = digest::SHA256_OUTPUT_LEN;
+type Credential = [u8; CREDENTIAL_LEN];
+
+const SALT_LEN: usize = 16;
+
+/// Population of users with own credential source, hashing parameters and sessions
+pub struct Realm {
+    name: String,
+    token_prefix: String,
+    iterations: NonZeroU32,
+    session_lifetime: Duration,
+    password_max_age: Option<Duration>,
+    password_history: i64,
+    password_min_length: usize,
+    user_source: UserSource,
+    pub(super) sessions: SessionRegistry,
+}
+
+impl Realm {
+    pub fn new(name: &str, config: &RealmConfig) -> Result<Realm, String> {
+        let iterations = NonZeroU32::new(config.password_iterations).ok_or(format!(
+            "realm {}: password iterations must be positive",
+            name
+        ))?;
+        if config.session_hours <= 0 {
+            return Err(format!("realm {}: session hours must be positive", name));
+        }
+        let user_source = UserSource::new(
+            &config.users_table,
+            &config.user_roles_table,
+            &config.password_history_table,
+        )
+        .map_err(|err| format!("realm {}: {}", name, err))?;
+        let user_source = match config.user_store {
+            UserStoreKind::Postgres => user_source,
+            #[cfg(feature = "oracle")]
+            UserStoreKind::Oracle => user_source.with_oracle(&config.oracle),
+            #[cfg(not(feature = "oracle"))]
+            UserStoreKind::Oracle => {
+                return Err(format!(
+                    "realm {}: server is built without oracle feature",
+                    name
+                ))
+            }
+        };
+
+        let policy = PasswordPolicyConfig::default();
+        Ok(Realm {
+            name: name.to_owned(),
+            token_prefix: config.token_prefix.clone(),
+            iterations,
+            session_lifetime: Duration::hours(config.session_hours),
+            password_max_age: config.password_max_age(),
+            password_history: config.password_history.unwrap_or(policy.history),
+            password_min_length: config.password_min_length.unwrap_or(policy.min_length),
+            user_source,
+            sessions: SessionRegistry::new(),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn token_prefix(&self) -> &str {
+        &self.token_prefix
+    }
+
+    pub fn session_lifetime(&self) -> Duration {
+        self.session_lifetime
+    }
+
+    /// count of previous passwords a new password must differ from
+    pub fn password_history(&self) -> i64 {
+        self.password_history
+    }
+
+    pub fn password_min_length(&self) -> usize {
+        self.password_min_length
+    }
+
+    pub fn user_source(&self) -> &UserSource {
+        &self.user_source
+    }
+
+    /// Token as seen by clients
+    pub fn token(&self, key: &Uuid) -> String {
+        format!("{}{}", self.token_prefix, key)
+    }
+
+    /// Session key of a token of this realm
+    pub fn parse_token(&self, token: &str) -> Option<Uuid> {
+        let key = token.strip_prefix(&self.token_prefix)?;
+        Uuid::parse_str(key).ok()
+    }
+
+    pub fn is_password_expired(&self, user: &User) -> bool {
+        if user.must_change_password {
+            return true;
+        }
+        match self.password_max_age {
+            Some(max_age) => Utc::now() - user.password_changed > max_age,
+            None => false,
+        }
+    }
+
+    pub fn verify_password(
+        &self,
+        salt: &str,
+        actual_password: &str,
+        attempted_password: &str,
+    ) -> Result<(), actix_web::Error> {
+        let decoded_salt = decode(salt).unwrap();
+        let decoded_actual_password = decode(actual_password).unwrap();
+
+        pbkdf2::verify(
+            PBKDF2_ALG,
+            self.iterations,
+            decoded_salt.as_slice(),
+            attempted_password.as_bytes(),
+            decoded_actual_password.as_slice(),
+        )
+        .map_err(|_| actix_web::error::ErrorUnauthorized("Parola este incorecta"))
+    }
+
+    pub fn password_matches(&self, hash: &PasswordHash, attempted_password: &str) -> bool {
+        self.verify_password(&hash.salt, &hash.password, attempted_password)
+            .is_ok()
+    }
+
+    /// Hash of new password with random salt
+    pub fn new_password_hash(&self, password: &str) -> PasswordHash {
+        let mut salt = [0u8; SALT_LEN];
+        SystemRandom::new().fill(&mut salt).unwrap();
+        let salt = encode(salt);
+
+        PasswordHash {
+            password: self.generate_password_hash(password, &salt),
+            salt,
+        }
+    }
+
+    pub fn generate_password_hash(&self, password: &str, salt: &str) -> String {
+        let decoded_salt = decode(salt).unwrap();
+
+        let mut to_store: Credential = [0u8; CREDENTIAL_LEN];
+        pbkdf2::derive(
+            PBKDF2_ALG,
+            self.iterations,
+            decoded_salt.as_slice(),
+            password.as_bytes(),
+            &mut to_store,
+        );
+        encode(to_store)
+    }
+}
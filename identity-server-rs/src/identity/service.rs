@@ -1,119 +1,469 @@
+use crate::config::{RealmConfig, DEFAULT_REALM};
 use crate::database::domain::User;
 
-use chrono::{DateTime, Utc};
-use serde::Serialize;
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex, RwLock};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ring::digest;
+use serde::{Serialize, Serializer};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-#[derive(Serialize)]
+use super::events::{RevocationReason, SessionEventKind, SessionEvents};
+use super::realm::Realm;
+
+/// maximal time to change password in restricted session
+const PASSWORD_CHANGE_MINUTES: i64 = 15;
+
+#[derive(Serialize, ToSchema)]
 pub struct AuthenticatedUser {
+    realm: String,
     user: User,
-    authenticated: RwLock<DateTime<Utc>>,
+    roles: Vec<String>,
+    // real user behind an impersonation session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actor: Option<Actor>,
+    // milliseconds since epoch; renewed without locks on every login
+    #[serde(serialize_with = "serialize_timestamp")]
+    #[schema(value_type = DateTime<Utc>)]
+    authenticated: AtomicI64,
+    #[serde(skip)]
+    lifetime: Duration,
+    // restricted session: only password change is allowed
+    password_change_required: bool,
 }
 
-#[derive(Serialize, Clone)]
-pub struct AuthenticationResponse {
+#[derive(Serialize, ToSchema)]
+pub struct Actor {
+    personnel_nr: i32,
+    username: String,
+    // own session of the actor, restored when impersonation ends
+    #[serde(skip)]
     token: Uuid,
+}
+
+#[derive(Serialize, Clone, ToSchema)]
+pub struct AuthenticationResponse {
+    /// session token, starts with token prefix of the realm
+    token: String,
+    #[schema(value_type = AuthenticatedUser)]
     auth_info: Arc<AuthenticatedUser>,
 }
 
-use base64::{decode, encode};
-use ring::{digest, pbkdf2};
-use std::num::NonZeroU32;
+impl AuthenticatedUser {
+    pub fn new(realm: &Realm, user: User, roles: Vec<String>) -> Self {
+        Self {
+            realm: realm.name().to_owned(),
+            user,
+            roles,
+            actor: None,
+            authenticated: AtomicI64::new(Utc::now().timestamp_millis()),
+            lifetime: realm.session_lifetime(),
+            password_change_required: false,
+        }
+    }
+
+    pub fn realm(&self) -> &str {
+        &self.realm
+    }
+
+    pub fn personnel_nr(&self) -> i32 {
+        self.user.personnel_nr
+    }
+
+    pub fn username(&self) -> &str {
+        &self.user.username
+    }
+
+    pub fn email(&self) -> Option<&str> {
+        self.user.email.as_deref()
+    }
+
+    pub fn roles(&self) -> &[String] {
+        &self.roles
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|it| it == role)
+    }
+
+    pub fn actor(&self) -> Option<&Actor> {
+        self.actor.as_ref()
+    }
+
+    pub fn password_change_required(&self) -> bool {
+        self.password_change_required
+    }
+
+    pub fn touch(&self) {
+        self.authenticated
+            .store(Utc::now().timestamp_millis(), Ordering::Release);
+    }
+
+    pub fn expires(&self) -> DateTime<Utc> {
+        let authenticated = self.authenticated.load(Ordering::Acquire);
+        Utc.timestamp_millis_opt(authenticated).unwrap() + self.lifetime
+    }
+
+    pub fn is_expired(&self) -> bool {
+        let authenticated = self.authenticated.load(Ordering::Acquire);
+        let elapsed = Utc::now().timestamp_millis() - authenticated;
+        elapsed > self.lifetime.num_milliseconds()
+    }
+}
+
+impl Actor {
+    pub fn personnel_nr(&self) -> i32 {
+        self.personnel_nr
+    }
+}
 
+impl AuthenticationResponse {
+    pub fn new(token: String, auth_info: Arc<AuthenticatedUser>) -> Self {
+        Self { token, auth_info }
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+/// Hex encoded SHA-256 of token; tokens are persisted and compared only as hashes
+pub fn token_hash(token: &Uuid) -> String {
+    let hash = digest::digest(&digest::SHA256, token.as_bytes());
+    hash.as_ref().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn serialize_timestamp<S: Serializer>(value: &AtomicI64, serializer: S) -> Result<S::Ok, S::Error> {
+    let millis = value.load(Ordering::Acquire);
+    let timestamp: DateTime<Utc> = Utc.timestamp_millis_opt(millis).unwrap();
+    timestamp.serialize(serializer)
+}
+
+/// Sessions of all realms. Sessions of each realm are kept in its own registry;
+/// the realm of a token is recognized by the token prefix
 #[derive(Clone)]
 pub struct Identity {
-    iterations: NonZeroU32,
-    users_by_uuid: Arc<RwLock<HashMap<Uuid, Arc<AuthenticatedUser>>>>,
-    users_by_personnel_nr: Arc<Mutex<HashMap<i16, AuthenticationResponse>>>,
+    // longest token prefix first, so a prefix never shadows a longer one
+    realms: Arc<Vec<Realm>>,
+    events: Arc<SessionEvents>,
 }
 
-static PBKDF2_ALG: pbkdf2::Algorithm = pbkdf2::PBKDF2_HMAC_SHA256;
-
-const CREDENTIAL_LEN: usize = digest::SHA256_OUTPUT_LEN;
-type Credential = [u8; CREDENTIAL_LEN];
+impl Default for Identity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Identity {
+    /// Only `default` realm with default settings
     pub fn new() -> Identity {
-        Identity {
-            iterations: NonZeroU32::new(1000).unwrap(),
-            users_by_uuid: Arc::new(RwLock::new(HashMap::new())),
-            users_by_personnel_nr: Arc::new(Mutex::new(HashMap::new())),
+        Self::with_realms(vec![(DEFAULT_REALM.to_owned(), RealmConfig::default())]).unwrap()
+    }
+
+    pub fn with_realms(realms: Vec<(String, RealmConfig)>) -> Result<Identity, String> {
+        let mut realms = realms
+            .iter()
+            .map(|(name, config)| Realm::new(name, config))
+            .collect::<Result<Vec<Realm>, String>>()?;
+
+        // a token without known prefix belongs to the realm with empty prefix,
+        // so only non-empty prefixes must not shadow each other
+        for realm in &realms {
+            let ambiguous = realms.iter().find(|other| {
+                other.name() != realm.name()
+                    && other.token_prefix().starts_with(realm.token_prefix())
+                    && (!realm.token_prefix().is_empty() || other.token_prefix().is_empty())
+            });
+            if let Some(other) = ambiguous {
+                return Err(format!(
+                    "token prefix of realm {} is a prefix of token prefix of realm {}",
+                    realm.name(),
+                    other.name()
+                ));
+            }
         }
+
+        realms.sort_by_key(|realm| std::cmp::Reverse(realm.token_prefix().len()));
+        Ok(Identity {
+            realms: Arc::new(realms),
+            events: Arc::new(SessionEvents::new()),
+        })
+    }
+
+    pub fn realm(&self, name: &str) -> Result<&Realm, actix_web::Error> {
+        self.realms
+            .iter()
+            .find(|realm| realm.name() == name)
+            .ok_or_else(|| actix_web::error::ErrorNotFound("Unknown realm"))
+    }
+
+    pub fn realms(&self) -> impl Iterator<Item = &Realm> {
+        self.realms.iter()
+    }
+
+    /// Realm and session key of token
+    fn resolve(&self, token: &str) -> Result<(&Realm, Uuid), actix_web::Error> {
+        self.realms
+            .iter()
+            .find_map(|realm| realm.parse_token(token).map(|key| (realm, key)))
+            .ok_or_else(|| actix_web::error::ErrorBadRequest("invalid auth token"))
     }
 
     pub fn authenticate(
         &self,
+        realm: &str,
         user: User,
+        roles: Vec<String>,
         attempted_password: &str,
     ) -> Result<AuthenticationResponse, actix_web::Error> {
-        self.verify_password(&user.salt, &user.password, attempted_password)?;
-
-        // guard hashmap for write
-        let mut guard = self.users_by_personnel_nr.lock().unwrap();
-        if let Some(auth_response) = guard.get_mut(&user.personnel_nr) {
-            // if auth record exists, renew auth timestamp
-            {
-                let mut guard = auth_response.auth_info.authenticated.write().unwrap();
-                *guard = Utc::now();
+        let realm = self.realm(realm)?;
+        realm.verify_password(&user.salt, &user.password, attempted_password)?;
+        Ok(self.login_realm(realm, user, roles))
+    }
+
+    /// Logs in user verified otherwise than by password (e.g. passkey),
+    /// restricted to password change as `authenticate` when password expired
+    pub fn login(
+        &self,
+        realm: &str,
+        user: User,
+        roles: Vec<String>,
+    ) -> Result<AuthenticationResponse, actix_web::Error> {
+        let realm = self.realm(realm)?;
+        Ok(self.login_realm(realm, user, roles))
+    }
+
+    fn login_realm(&self, realm: &Realm, user: User, roles: Vec<String>) -> AuthenticationResponse {
+        if realm.is_password_expired(&user) {
+            // restricted session, not registered as own session of user
+            let auth_info = Arc::new(AuthenticatedUser {
+                lifetime: Duration::minutes(PASSWORD_CHANGE_MINUTES),
+                password_change_required: true,
+                ..AuthenticatedUser::new(realm, user, roles)
+            });
+            let key = Uuid::new_v4();
+            self.insert_session(realm, key, auth_info.clone());
+            return AuthenticationResponse::new(realm.token(&key), auth_info);
+        }
+
+        self.open_realm_session(realm, user, roles)
+    }
+
+    /// Opens (or renews) own session of an already verified user
+    pub fn open_session(
+        &self,
+        realm: &str,
+        user: User,
+        roles: Vec<String>,
+    ) -> Result<AuthenticationResponse, actix_web::Error> {
+        let realm = self.realm(realm)?;
+        Ok(self.open_realm_session(realm, user, roles))
+    }
+
+    fn open_realm_session(
+        &self,
+        realm: &Realm,
+        user: User,
+        roles: Vec<String>,
+    ) -> AuthenticationResponse {
+        let personnel_nr = user.personnel_nr;
+        let (key, auth_info, created) = realm.sessions.open(personnel_nr, move || {
+            Arc::new(AuthenticatedUser::new(realm, user, roles))
+        });
+        if created {
+            self.publish_created(realm, key, &auth_info);
+        }
+        AuthenticationResponse::new(realm.token(&key), auth_info)
+    }
+
+    fn insert_session(&self, realm: &Realm, key: Uuid, auth_info: Arc<AuthenticatedUser>) {
+        realm.sessions.insert(key, auth_info.clone());
+        self.publish_created(realm, key, &auth_info);
+    }
+
+    fn publish_created(&self, realm: &Realm, key: Uuid, auth_info: &AuthenticatedUser) {
+        self.events.publish(
+            realm.name(),
+            SessionEventKind::SessionCreated {
+                personnel_nr: auth_info.personnel_nr(),
+                token: key,
+                expires: auth_info.expires(),
+                actor_personnel_nr: auth_info.actor().map(|actor| actor.personnel_nr()),
+            },
+        );
+    }
+
+    fn revoke_session(&self, realm: &Realm, key: &Uuid, reason: RevocationReason) {
+        if let Some(auth_info) = realm.sessions.remove(key) {
+            self.publish_revoked(realm, *key, &auth_info, reason);
+        }
+    }
+
+    fn publish_revoked(
+        &self,
+        realm: &Realm,
+        key: Uuid,
+        auth_info: &AuthenticatedUser,
+        reason: RevocationReason,
+    ) {
+        self.events.publish(
+            realm.name(),
+            SessionEventKind::SessionRevoked {
+                personnel_nr: auth_info.personnel_nr(),
+                token: key,
+                reason,
+            },
+        );
+    }
+
+    /// Revokes all sessions of disabled user of the realm
+    pub fn disable_user(&self, realm: &str, personnel_nr: i32) -> Result<(), actix_web::Error> {
+        let realm = self.realm(realm)?;
+        self.revoke_user_sessions(realm, personnel_nr);
+        self.publish_disabled(realm, personnel_nr);
+        Ok(())
+    }
+
+    /// Applies users disabled outside of this server (admin CLI); events are published
+    /// as by `disable_user` for users having sessions here, so repeated calls publish nothing
+    pub fn disable_users(&self, users: &HashSet<(String, i32)>) {
+        for realm in self.realms.iter() {
+            let personnel_nrs = users
+                .iter()
+                .filter(|(name, _)| name == realm.name())
+                .map(|(_, personnel_nr)| *personnel_nr);
+            for personnel_nr in personnel_nrs {
+                if self.revoke_user_sessions(realm, personnel_nr) > 0 {
+                    self.publish_disabled(realm, personnel_nr);
+                }
             }
-            return Ok(auth_response.clone());
         }
+    }
+
+    fn revoke_user_sessions(&self, realm: &Realm, personnel_nr: i32) -> usize {
+        let revoked = realm.sessions.remove_user(personnel_nr);
+        for (key, auth_info) in revoked.iter() {
+            self.publish_revoked(realm, *key, auth_info, RevocationReason::Revoked);
+        }
+        revoked.len()
+    }
 
-        // user not exists in hashmaps
-        // generate new auth info
+    fn publish_disabled(&self, realm: &Realm, personnel_nr: i32) {
+        self.events.publish(
+            realm.name(),
+            SessionEventKind::UserDisabled { personnel_nr },
+        );
+    }
+
+    /// Revokes sessions by hash of their tokens (revoked outside of this server)
+    pub fn revoke_sessions_by_hash(&self, token_hashes: &HashSet<String>) {
+        for realm in self.realms.iter() {
+            let revoked = realm
+                .sessions
+                .remove_matching(|key, _| token_hashes.contains(&token_hash(key)));
+            for (key, auth_info) in revoked {
+                self.publish_revoked(realm, key, &auth_info, RevocationReason::Revoked);
+            }
+        }
+    }
+
+    /// Hashes of tokens of all sessions with their current (possibly renewed) expiry
+    pub fn session_expiries(&self) -> Vec<(String, DateTime<Utc>)> {
+        self.realms
+            .iter()
+            .flat_map(|realm| realm.sessions.expiries())
+            .map(|(key, expires)| (token_hash(&key), expires))
+            .collect()
+    }
+
+    /// Drops expired sessions; called periodically, so expiry is published
+    /// even for sessions which are never used again
+    pub fn remove_expired_sessions(&self) {
+        for realm in self.realms.iter() {
+            for (key, auth_info) in realm.sessions.remove_expired() {
+                self.publish_revoked(realm, key, &auth_info, RevocationReason::Expired);
+            }
+        }
+    }
+
+    pub fn events(&self) -> &SessionEvents {
+        &self.events
+    }
+
+    /// Issues a time-limited session as `user` on behalf of the actor session `actor_token`.
+    /// The actor session stays valid and is returned back by `end_impersonation`.
+    /// `user` must belong to the realm of the actor
+    pub fn impersonate(
+        &self,
+        actor_token: &str,
+        user: User,
+        roles: Vec<String>,
+        lifetime: Duration,
+    ) -> Result<AuthenticationResponse, actix_web::Error> {
+        let (realm, actor_key) = self.resolve(actor_token)?;
+        let actor_info = self.authorization_info(actor_token)?;
+        if actor_info.actor.is_some() {
+            return Err(actix_web::error::ErrorForbidden(
+                "Impersonation session can not impersonate again",
+            ));
+        }
+
+        let actor = Actor {
+            personnel_nr: actor_info.personnel_nr(),
+            username: actor_info.username().to_owned(),
+            token: actor_key,
+        };
 
-        let key = user.personnel_nr;
         let auth_info = Arc::new(AuthenticatedUser {
-            user,
-            authenticated: RwLock::new(Utc::now()),
+            actor: Some(actor),
+            lifetime,
+            ..AuthenticatedUser::new(realm, user, roles)
         });
 
-        let token = Uuid::new_v4();
+        let key = Uuid::new_v4();
+        self.insert_session(realm, key, auth_info.clone());
 
-        let auth_response = AuthenticationResponse {
-            auth_info: auth_info.clone(),
-            token,
-        };
+        Ok(AuthenticationResponse::new(realm.token(&key), auth_info))
+    }
 
-        // insert into hashmap by personnel nr
-        guard.insert(key, auth_response.clone());
+    /// Ends impersonation session `token` and returns the own session of the actor
+    pub fn end_impersonation(
+        &self,
+        token: &str,
+    ) -> Result<AuthenticationResponse, actix_web::Error> {
+        let (realm, _) = self.resolve(token)?;
+        let auth_info = self.authorization_info(token)?;
+        let actor_token = match auth_info.actor {
+            Some(ref actor) => realm.token(&actor.token),
+            None => {
+                return Err(actix_web::error::ErrorBadRequest(
+                    "Session is not an impersonation",
+                ))
+            }
+        };
 
-        // insert into hashmap by uuid token
-        let mut guard = self.users_by_uuid.write().unwrap();
-        guard.insert(token, auth_info);
+        self.logout(token)?;
 
-        Ok(auth_response)
+        let actor_info = self.authorization_info(&actor_token)?;
+        Ok(AuthenticationResponse::new(actor_token, actor_info))
     }
 
     pub fn authorization_info(
         &self,
         token: &str,
     ) -> Result<Arc<AuthenticatedUser>, actix_web::Error> {
-        let key = Uuid::parse_str(token)
-            .map_err(|_| actix_web::error::ErrorBadRequest("invalid auth token"))?;
-
-        let guard = self.users_by_uuid.read().unwrap();
+        let (realm, key) = self.resolve(token)?;
 
-        let info = guard.get(&key).map(|it| it.clone());
-
-        match info {
+        match realm.sessions.get(&key) {
             Some(info) => {
-                // TODO: check duration; maximal session time must be 12Hours
-                {
-                    let hours = -{
-                        let now = Utc::now();
-                        let authenticated = info.authenticated.read().unwrap();
-                        let duration = authenticated.signed_duration_since(now);
-                        duration.num_hours()
-                    };
-                    if hours > 12 {
-                        // session is outdated
-                        Err(actix_web::error::ErrorUnauthorized("Session expired"))
-                    } else {
-                        Ok(info)
-                    }
+                if info.is_expired() {
+                    // session is outdated
+                    self.revoke_session(realm, &key, RevocationReason::Expired);
+                    Err(actix_web::error::ErrorUnauthorized("Session expired"))
+                } else {
+                    Ok(info)
                 }
             }
             None => Err(actix_web::error::ErrorUnauthorized(
@@ -123,55 +473,10 @@ impl Identity {
     }
 
     pub fn logout(&self, token: &str) -> Result<(), actix_web::Error> {
-        let key = Uuid::parse_str(token)
-            .map_err(|_| actix_web::error::ErrorBadRequest("invalid auth token"))?;
-
-        let mut guard = self.users_by_uuid.write().unwrap();
+        let (realm, key) = self.resolve(token)?;
 
-        let auth_user = guard.remove(&key);
-        if let Some(auth_user) = auth_user {
-            let personnel_nr = auth_user.user.personnel_nr;
-            let mut guard = self.users_by_personnel_nr.lock().unwrap();
-            guard.remove(&personnel_nr).unwrap();
-        }
+        self.revoke_session(realm, &key, RevocationReason::Logout);
 
         Ok(())
     }
-
-    fn verify_password(
-        &self,
-        salt: &str,
-        actual_password: &str,
-        attempted_password: &str,
-    ) -> Result<(), actix_web::Error> {
-        let decoded_salt = decode(salt).unwrap();
-        let decoded_actual_password = decode(actual_password).unwrap();
-
-        pbkdf2::verify(
-            PBKDF2_ALG,
-            self.iterations,
-            decoded_salt.as_slice(),
-            attempted_password.as_bytes(),
-            decoded_actual_password.as_slice(),
-        )
-        .map_err(|_| actix_web::error::ErrorUnauthorized("Parola este incorecta"))
-    }
-
-    pub fn generate_password_hash(&self, password: &str, salt: &str) -> String {
-        let iterations = NonZeroU32::new(1000).unwrap();
-
-        log::info!("Credentials len: {:}", CREDENTIAL_LEN);
-
-        let decoded_salt = decode(salt).unwrap();
-
-        let mut to_store: Credential = [0u8; CREDENTIAL_LEN];
-        pbkdf2::derive(
-            PBKDF2_ALG,
-            iterations,
-            decoded_salt.as_slice(),
-            password.as_bytes(),
-            &mut to_store,
-        );
-        encode(to_store)
-    }
 }
@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::service::AuthenticatedUser;
+
+/// Concurrent index of active sessions.
+///
+/// Both maps are sharded, so authorization lookups never contend on a global lock.
+/// Lock order is always `by_personnel_nr` -> `by_token`: a token shard is never held
+/// while a personnel nr shard is acquired, so the two maps can not deadlock.
+/// `by_token` is the source of truth; `by_personnel_nr` only points to the current token
+/// of a user and is removed only if it still points to the removed token.
+#[derive(Default)]
+pub struct SessionRegistry {
+    by_token: DashMap<Uuid, Arc<AuthenticatedUser>>,
+    by_personnel_nr: DashMap<i32, Uuid>,
+}
+
+impl SessionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the existing session of the user (renewed) or registers a new one
+    /// created by `create`; flag is true if session was created
+    pub fn open<F>(&self, personnel_nr: i32, create: F) -> (Uuid, Arc<AuthenticatedUser>, bool)
+    where
+        F: FnOnce() -> Arc<AuthenticatedUser>,
+    {
+        match self.by_personnel_nr.entry(personnel_nr) {
+            Entry::Occupied(mut entry) => {
+                let token = *entry.get();
+                if let Some(auth_info) = self.by_token.get(&token) {
+                    // if auth record exists, renew auth timestamp
+                    auth_info.touch();
+                    return (token, auth_info.clone(), false);
+                }
+                // token was removed concurrently; replace it
+                let token = Uuid::new_v4();
+                let auth_info = create();
+                self.by_token.insert(token, auth_info.clone());
+                entry.insert(token);
+                (token, auth_info, true)
+            }
+            Entry::Vacant(entry) => {
+                let token = Uuid::new_v4();
+                let auth_info = create();
+                self.by_token.insert(token, auth_info.clone());
+                entry.insert(token);
+                (token, auth_info, true)
+            }
+        }
+    }
+
+    /// Registers a session which is not indexed by personnel nr
+    /// and never replaces the own session of the user
+    pub fn insert(&self, token: Uuid, auth_info: Arc<AuthenticatedUser>) {
+        self.by_token.insert(token, auth_info);
+    }
+
+    pub fn get(&self, token: &Uuid) -> Option<Arc<AuthenticatedUser>> {
+        self.by_token.get(token).map(|it| it.value().clone())
+    }
+
+    /// Removes session by token; returns removed session if it existed
+    pub fn remove(&self, token: &Uuid) -> Option<Arc<AuthenticatedUser>> {
+        let (_, auth_info) = self.by_token.remove(token)?;
+        self.by_personnel_nr
+            .remove_if(&auth_info.personnel_nr(), |_, current| current == token);
+        Some(auth_info)
+    }
+
+    /// Removes all sessions where the user is owner or actor of impersonation
+    pub fn remove_user(&self, personnel_nr: i32) -> Vec<(Uuid, Arc<AuthenticatedUser>)> {
+        self.remove_matching(|_, auth_info| {
+            auth_info.personnel_nr() == personnel_nr
+                || auth_info
+                    .actor()
+                    .is_some_and(|actor| actor.personnel_nr() == personnel_nr)
+        })
+    }
+
+    /// Tokens of all sessions with their current expiry
+    pub fn expiries(&self) -> Vec<(Uuid, DateTime<Utc>)> {
+        self.by_token
+            .iter()
+            .map(|it| (*it.key(), it.value().expires()))
+            .collect()
+    }
+
+    pub fn remove_expired(&self) -> Vec<(Uuid, Arc<AuthenticatedUser>)> {
+        self.remove_matching(|_, auth_info| auth_info.is_expired())
+    }
+
+    pub fn remove_matching<F>(&self, predicate: F) -> Vec<(Uuid, Arc<AuthenticatedUser>)>
+    where
+        F: Fn(&Uuid, &AuthenticatedUser) -> bool,
+    {
+        // collect first: removing while iterating would deadlock on the shard
+        let tokens: Vec<Uuid> = self
+            .by_token
+            .iter()
+            .filter(|it| predicate(it.key(), it.value()))
+            .map(|it| *it.key())
+            .collect();
+
+        tokens
+            .into_iter()
+            .filter_map(|token| self.remove(&token).map(|auth_info| (token, auth_info)))
+            .collect()
+    }
+}
@@ -0,0 +1,11 @@
+pub mod cli;
+pub mod config;
+pub mod database;
+pub mod errors;
+pub mod handlers;
+pub mod identity;
+pub mod openapi;
+pub mod security;
+pub mod session_store;
+pub mod sse;
+pub mod webauthn;
@@ -1,15 +1,11 @@
-mod config;
-mod database;
-mod errors;
-mod handlers;
-mod identity;
-
 use actix_web::{web, App, HttpServer};
+use clap::Parser;
 use dotenv::dotenv;
 
-use crate::config::IdentityServerConfig;
-use ::config::Config;
 use actix_web::middleware::Logger;
+use identity_server_rs::cli::{self, Cli, Command};
+use identity_server_rs::config::{self, IdentityServerConfig};
+use identity_server_rs::{database, handlers, identity, security, session_store, webauthn};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -19,33 +15,88 @@ async fn main() -> std::io::Result<()> {
     env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
     dotenv().ok();
 
-    let config_ = Config::builder()
-        .add_source(::config::Environment::default())
-        .build()
-        .unwrap();
+    let command = Cli::parse().command.unwrap_or(Command::Serve);
+
+    // hashing does not need any configuration
+    if let Command::HashPassword(ref password) = command {
+        exit_on_error(cli::hash_password(password));
+        return Ok(());
+    }
+
+    let config = match config::load_config() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("invalid configuration: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    match command {
+        Command::Serve => serve(config).await,
+        command => {
+            exit_on_error(cli::run(command, config).await);
+            Ok(())
+        }
+    }
+}
 
-    let config: IdentityServerConfig = config_.try_deserialize().unwrap();
+fn exit_on_error(result: Result<(), String>) {
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
 
+async fn serve(config: IdentityServerConfig) -> std::io::Result<()> {
+    let identity_service = match identity::Identity::with_realms(config.realms()) {
+        Ok(identity) => identity,
+        Err(err) => {
+            eprintln!("invalid realm configuration: {}", err);
+            std::process::exit(1);
+        }
+    };
+    if let Err(err) = config.cors.validate() {
+        eprintln!("invalid cors configuration: {}", err);
+        std::process::exit(1);
+    }
     let pool = database::create_db_pool(config.pg);
-    let identity_service = identity::Identity::new();
     let auth_token_middleware_factory = identity::AuthTokenMiddlewareFactory::new();
+    let webauthn = webauthn::WebAuthn::new(config.webauthn.clone());
 
     // configure tls for http server
     let rustls_config = config::load_rustls_config(&config.ssl);
 
     log::info!("Server running at http://{}/", config.server_addr);
 
+    let cors_config = config.cors.clone();
+    let headers_config = config.headers.clone();
+    let impersonation_config = config.impersonation.clone();
+    let admin_config = config.admin.clone();
+    let events_config = config.events.clone();
+
+    // persist sessions, publish expiry of sessions which are not used anymore
+    // and apply revocations made by admin CLI
+    let poll_interval = std::time::Duration::from_secs(config.events.expiry_check_seconds);
+    actix_web::rt::spawn(session_store::mirror_sessions(
+        pool.clone(),
+        identity_service.clone(),
+        poll_interval,
+    ));
+
     let server = HttpServer::new(move || {
         let logger = Logger::default();
         App::new()
             .app_data(web::Data::new(pool.clone()))
             .app_data(web::Data::new(identity_service.clone()))
+            .app_data(web::Data::new(impersonation_config.clone()))
+            .app_data(web::Data::new(admin_config.clone()))
+            .app_data(web::Data::new(events_config.clone()))
+            .app_data(web::Data::new(webauthn.clone()))
             .wrap(logger)
             .wrap(auth_token_middleware_factory.clone())
-            .service(handlers::hello)
-            .service(handlers::login)
-            .service(handlers::logout)
-            .service(handlers::auth_scope())
+            .wrap(security::security_headers(&headers_config))
+            .wrap(security::cors(&cors_config))
+            .configure(handlers::configure)
     })
     .bind_rustls(config.server_addr.clone(), rustls_config)?
     .run();
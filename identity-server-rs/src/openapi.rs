@@ -0,0 +1,75 @@
+use actix_web::{get, web, Responder};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::handlers;
+use crate::identity::AUTH_TOKEN_COOKIE;
+
+/// OpenAPI 3 document of the identity API
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Identity server", description = "Authentication and sessions of users"),
+    paths(
+        handlers::hello,
+        handlers::login,
+        handlers::realm_login,
+        handlers::logout,
+        handlers::auth_info,
+        handlers::realm_auth_info,
+        handlers::verify,
+        handlers::impersonate,
+        handlers::end_impersonation,
+        handlers::change_password,
+        handlers::disable_user,
+        handlers::session_events,
+        handlers::webauthn_register_start,
+        handlers::webauthn_register_finish,
+        handlers::webauthn_login_start,
+        handlers::webauthn_login_finish,
+    ),
+    modifiers(&TokenSecurity)
+)]
+pub struct ApiDoc;
+
+/// Session token is passed as `Authorization: Token <token>` header
+/// or, for forward auth of browser requests, as cookie
+struct TokenSecurity;
+
+impl Modify for TokenSecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "token",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "Authorization",
+                "Token <token>",
+            ))),
+        );
+        components.add_security_scheme(
+            "cookie",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(AUTH_TOKEN_COOKIE))),
+        );
+    }
+}
+
+#[get("/openapi.json")]
+pub async fn openapi_json() -> impl Responder {
+    web::Json(ApiDoc::openapi())
+}
+
+/// Swagger UI at `/docs/`, reads the document from `/openapi.json`.
+/// UI needs its own scripts and styles, so it gets a less strict CSP than the JSON API
+#[cfg(feature = "swagger-ui")]
+pub fn swagger_ui() -> impl actix_web::dev::HttpServiceFactory {
+    use actix_web::http::header;
+    use actix_web::middleware::DefaultHeaders;
+    use utoipa_swagger_ui::{Config, SwaggerUi};
+
+    web::scope("/docs")
+        .wrap(DefaultHeaders::new().add((
+            header::CONTENT_SECURITY_POLICY,
+            "default-src 'self'; img-src 'self' data:; style-src 'self' 'unsafe-inline'; \
+            frame-ancestors 'none'",
+        )))
+        .service(SwaggerUi::new("/{_:.*}").config(Config::from("/openapi.json")))
+}
@@ -0,0 +1,47 @@
+use actix_cors::Cors;
+use actix_web::http::header;
+use actix_web::middleware::DefaultHeaders;
+
+use crate::config::{CorsConfig, SecurityHeadersConfig};
+
+/// CORS policy for browser clients from other origins.
+/// Without configured origins only same-origin requests are allowed.
+pub fn cors(config: &CorsConfig) -> Cors {
+    let mut cors = Cors::default();
+
+    for origin in config.origins() {
+        cors = if origin == "*" {
+            cors.allow_any_origin()
+        } else {
+            cors.allowed_origin(origin)
+        };
+    }
+
+    cors = cors
+        .allowed_methods(config.methods())
+        .allowed_headers(config.headers())
+        .max_age(config.max_age);
+
+    if config.supports_credentials {
+        cors = cors.supports_credentials();
+    }
+
+    cors
+}
+
+/// Security headers appended to every response of the JSON API
+pub fn security_headers(config: &SecurityHeadersConfig) -> DefaultHeaders {
+    let mut hsts = format!("max-age={}", config.hsts_max_age);
+    if config.hsts_include_subdomains {
+        hsts.push_str("; includeSubDomains");
+    }
+
+    DefaultHeaders::new()
+        .add((header::STRICT_TRANSPORT_SECURITY, hsts))
+        .add((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+        .add((header::REFERRER_POLICY, config.referrer_policy.clone()))
+        .add((
+            header::CONTENT_SECURITY_POLICY,
+            config.content_security_policy.clone(),
+        ))
+}
@@ -0,0 +1,117 @@
+use chrono::Utc;
+use deadpool_postgres::Pool;
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::database::domain::StoredSession;
+use crate::database::{
+    find_disabled_session_users, find_revoked_sessions, insert_session, renew_sessions,
+    revoke_session,
+};
+use crate::errors::IdentityServerError;
+use crate::identity::{token_hash, Identity, SessionEvent, SessionEventKind};
+
+/// Mirrors sessions of this server into `security.sessions`
+/// and periodically stores renewed expiry and applies revocations made outside (admin CLI)
+/// and session expiry
+pub async fn mirror_sessions(pool: Pool, identity: Identity, poll_interval: Duration) {
+    let mut receiver = identity.events().subscribe(None).receiver;
+    let mut interval = tokio::time::interval(poll_interval);
+    let overlap = chrono::Duration::from_std(poll_interval).unwrap();
+    let mut last_poll = Utc::now();
+
+    loop {
+        tokio::select! {
+            event = receiver.recv() => match event {
+                Ok(event) => {
+                    if let Err(err) = store_event(&pool, &event).await {
+                        log::error!("can not store session event {}: {}", event.id, err);
+                    }
+                }
+                Err(RecvError::Lagged(count)) => {
+                    log::warn!("session store missed {} session events", count);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            _ = interval.tick() => {
+                let now = Utc::now();
+                // overlap covers clock difference between server and database
+                match apply_revocations(&pool, &identity, last_poll - overlap).await {
+                    Ok(_) => last_poll = now,
+                    Err(err) => log::error!("can not load revoked sessions: {}", err),
+                }
+                if let Err(err) = store_renewals(&pool, &identity).await {
+                    log::error!("can not store renewed sessions: {}", err);
+                }
+                identity.remove_expired_sessions();
+            }
+        }
+    }
+}
+
+async fn store_event(pool: &Pool, event: &SessionEvent) -> Result<(), IdentityServerError> {
+    let client = pool.get().await.map_err(IdentityServerError::PoolError)?;
+
+    match event.kind {
+        SessionEventKind::SessionCreated {
+            personnel_nr,
+            token,
+            expires,
+            actor_personnel_nr,
+        } => {
+            let session = StoredSession {
+                token_hash: token_hash(&token),
+                realm: event.realm.clone(),
+                personnel_nr,
+                actor_personnel_nr,
+                created: event.timestamp,
+                expires,
+                revoked: None,
+                revoke_reason: None,
+            };
+            insert_session(&client, &session).await
+        }
+        SessionEventKind::SessionRevoked { token, reason, .. } => {
+            revoke_session(&client, &token_hash(&token), reason.as_str()).await?;
+            Ok(())
+        }
+        SessionEventKind::UserDisabled { .. } => Ok(()),
+    }
+}
+
+async fn apply_revocations(
+    pool: &Pool,
+    identity: &Identity,
+    since: chrono::DateTime<Utc>,
+) -> Result<(), IdentityServerError> {
+    let client = pool.get().await.map_err(IdentityServerError::PoolError)?;
+    // disabled users first, so they get the same events as disabled by admin here
+    let disabled: HashSet<(String, i32)> = find_disabled_session_users(&client, since)
+        .await?
+        .into_iter()
+        .collect();
+    if !disabled.is_empty() {
+        identity.disable_users(&disabled);
+    }
+    let revoked: HashSet<String> = find_revoked_sessions(&client, since)
+        .await?
+        .into_iter()
+        .collect();
+    if !revoked.is_empty() {
+        identity.revoke_sessions_by_hash(&revoked);
+    }
+    Ok(())
+}
+
+/// Sessions are renewed only in memory; listing of stored sessions relies on their expiry
+async fn store_renewals(pool: &Pool, identity: &Identity) -> Result<(), IdentityServerError> {
+    let (token_hashes, expires): (Vec<String>, Vec<_>) =
+        identity.session_expiries().into_iter().unzip();
+    if token_hashes.is_empty() {
+        return Ok(());
+    }
+    let client = pool.get().await.map_err(IdentityServerError::PoolError)?;
+    renew_sessions(&client, &token_hashes, &expires).await?;
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use actix_web::web::Bytes;
+use futures_util::stream::{self, Stream, StreamExt};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::identity::{SessionEvent, Subscription};
+
+// subscriber missed events and must drop everything it cached
+const RESYNC: &[u8] = b"event: resync\ndata: {}\n\n";
+const KEEP_ALIVE: &[u8] = b": keep-alive\n\n";
+
+/// Server-sent events stream of session events:
+/// replayed events first, then live events with keep-alive comments in between
+pub fn session_event_stream(
+    subscription: Subscription,
+    keep_alive: Duration,
+) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+    let Subscription {
+        replay,
+        resync,
+        receiver,
+    } = subscription;
+
+    let mut head = Vec::with_capacity(replay.len() + 1);
+    if resync {
+        head.push(Bytes::from_static(RESYNC));
+    }
+    head.extend(replay.iter().map(format_event));
+
+    let live = stream::unfold(receiver, move |mut receiver| async move {
+        let chunk = match tokio::time::timeout(keep_alive, receiver.recv()).await {
+            Ok(Ok(event)) => format_event(&event),
+            Ok(Err(RecvError::Lagged(_))) => Bytes::from_static(RESYNC),
+            Ok(Err(RecvError::Closed)) => return None,
+            Err(_) => Bytes::from_static(KEEP_ALIVE),
+        };
+        Some((chunk, receiver))
+    });
+
+    stream::iter(head).chain(live).map(Ok)
+}
+
+fn format_event(event: &SessionEvent) -> Bytes {
+    let data = serde_json::to_string(event).unwrap();
+    Bytes::from(format!(
+        "id: {}\nevent: {}\ndata: {}\n\n",
+        event.id,
+        event.name(),
+        data
+    ))
+}
@@ -0,0 +1,171 @@
+use ciborium::value::Value;
+use ring::signature::{self, RsaPublicKeyComponents, UnparsedPublicKey};
+use serde::Deserialize;
+
+// flags of authenticator data
+const USER_PRESENT: u8 = 0x01;
+const USER_VERIFIED: u8 = 0x04;
+const ATTESTED_CREDENTIAL: u8 = 0x40;
+
+// COSE algorithms accepted for passkeys
+pub const ES256: i64 = -7;
+pub const EDDSA: i64 = -8;
+pub const RS256: i64 = -257;
+
+/// `clientDataJSON` of the browser
+#[derive(Debug, Deserialize)]
+pub struct ClientData {
+    #[serde(rename = "type")]
+    pub ceremony: String,
+    pub challenge: String,
+    pub origin: String,
+}
+
+impl ClientData {
+    pub fn parse(json: &[u8]) -> Result<ClientData, &'static str> {
+        serde_json::from_slice(json).map_err(|_| "invalid client data")
+    }
+}
+
+/// Authenticator data of attestation and assertion
+#[derive(Debug)]
+pub struct AuthenticatorData {
+    pub rp_id_hash: Vec<u8>,
+    flags: u8,
+    pub sign_count: u32,
+    /// credential id and COSE key, only in attestation
+    pub credential: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl AuthenticatorData {
+    pub fn parse(data: &[u8]) -> Result<AuthenticatorData, &'static str> {
+        const INVALID: &str = "invalid authenticator data";
+
+        if data.len() < 37 {
+            return Err(INVALID);
+        }
+        let flags = data[32];
+        let sign_count = u32::from_be_bytes([data[33], data[34], data[35], data[36]]);
+
+        let credential = if flags & ATTESTED_CREDENTIAL != 0 {
+            // aaguid (16), length of credential id (2), credential id, COSE key
+            let rest = data.get(37 + 16..).ok_or(INVALID)?;
+            if rest.len() < 2 {
+                return Err(INVALID);
+            }
+            let id_len = u16::from_be_bytes([rest[0], rest[1]]) as usize;
+            let credential_id = rest.get(2..2 + id_len).ok_or(INVALID)?;
+            let key = &rest[2 + id_len..];
+
+            // COSE key has no length prefix, its length is known only after decoding;
+            // extensions may follow it
+            let mut remaining = key;
+            let _: Value = ciborium::de::from_reader(&mut remaining).map_err(|_| INVALID)?;
+            let key_len = key.len() - remaining.len();
+
+            Some((credential_id.to_vec(), key[..key_len].to_vec()))
+        } else {
+            None
+        };
+
+        Ok(AuthenticatorData {
+            rp_id_hash: data[..32].to_vec(),
+            flags,
+            sign_count,
+            credential,
+        })
+    }
+
+    pub fn user_present(&self) -> bool {
+        self.flags & USER_PRESENT != 0
+    }
+
+    pub fn user_verified(&self) -> bool {
+        self.flags & USER_VERIFIED != 0
+    }
+}
+
+/// `authData` of CBOR encoded attestation object; attestation statement is not verified,
+/// passkeys are trusted as much as the session which registered them
+pub fn attestation_auth_data(attestation_object: &[u8]) -> Result<Vec<u8>, &'static str> {
+    const INVALID: &str = "invalid attestation object";
+
+    let value: Value = ciborium::de::from_reader(attestation_object).map_err(|_| INVALID)?;
+    let entries = value.into_map().map_err(|_| INVALID)?;
+    entries
+        .into_iter()
+        .find(|(key, _)| key.as_text() == Some("authData"))
+        .and_then(|(_, value)| value.into_bytes().ok())
+        .ok_or(INVALID)
+}
+
+/// Public key of a credential, as COSE key
+pub enum PublicKey {
+    Es256 { point: Vec<u8> },
+    EdDsa { x: Vec<u8> },
+    Rs256 { n: Vec<u8>, e: Vec<u8> },
+}
+
+impl PublicKey {
+    pub fn parse(cose_key: &[u8]) -> Result<PublicKey, &'static str> {
+        const INVALID: &str = "invalid public key";
+
+        let value: Value = ciborium::de::from_reader(cose_key).map_err(|_| INVALID)?;
+        let entries = value.into_map().map_err(|_| INVALID)?;
+        let param = |label: i64| {
+            entries
+                .iter()
+                .find(|(key, _)| key.as_integer() == Some(label.into()))
+                .map(|(_, value)| value)
+        };
+        let integer = |label: i64| {
+            param(label)
+                .and_then(|value| value.as_integer())
+                .and_then(|value| i64::try_from(value).ok())
+        };
+        let bytes = |label: i64| {
+            param(label)
+                .and_then(|value| value.as_bytes())
+                .cloned()
+                .ok_or(INVALID)
+        };
+
+        // kty: 1 - OKP, 2 - EC2, 3 - RSA; crv: 1 - P-256, 6 - Ed25519
+        match (integer(3), integer(1)) {
+            (Some(ES256), Some(2)) if integer(-1) == Some(1) => {
+                let (x, y) = (bytes(-2)?, bytes(-3)?);
+                if x.len() != 32 || y.len() != 32 {
+                    return Err(INVALID);
+                }
+                let mut point = vec![0x04];
+                point.extend(x);
+                point.extend(y);
+                Ok(PublicKey::Es256 { point })
+            }
+            (Some(EDDSA), Some(1)) if integer(-1) == Some(6) => {
+                Ok(PublicKey::EdDsa { x: bytes(-2)? })
+            }
+            (Some(RS256), Some(3)) => Ok(PublicKey::Rs256 {
+                n: bytes(-1)?,
+                e: bytes(-2)?,
+            }),
+            _ => Err("unsupported public key algorithm"),
+        }
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        match self {
+            PublicKey::Es256 { point } => {
+                UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_ASN1, point)
+                    .verify(message, signature)
+                    .is_ok()
+            }
+            PublicKey::EdDsa { x } => UnparsedPublicKey::new(&signature::ED25519, x)
+                .verify(message, signature)
+                .is_ok(),
+            PublicKey::Rs256 { n, e } => RsaPublicKeyComponents { n, e }
+                .verify(&signature::RSA_PKCS1_2048_8192_SHA256, message, signature)
+                .is_ok(),
+        }
+    }
+}
@@ -0,0 +1,382 @@
+mod data;
+
+use base64::{decode_config, encode_config, URL_SAFE_NO_PAD};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use ring::digest;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::config::WebAuthnConfig;
+use crate::database::domain::WebAuthnCredential;
+use data::{AuthenticatorData, ClientData, PublicKey};
+
+const CHALLENGE_LEN: usize = 32;
+const PUBLIC_KEY: &str = "public-key";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CeremonyKind {
+    Registration,
+    Login,
+}
+
+/// Started registration or login, waiting for the response of the authenticator
+struct Ceremony {
+    kind: CeremonyKind,
+    realm: String,
+    /// user who registers a passkey or who is expected to log in
+    personnel_nr: Option<i32>,
+    expires: DateTime<Utc>,
+}
+
+/// Relying party of passkey registration and login ceremonies.
+///
+/// Challenges are kept in memory until the ceremony completes or times out,
+/// so a ceremony must finish on the server instance which started it.
+#[derive(Clone)]
+pub struct WebAuthn {
+    config: Arc<WebAuthnConfig>,
+    ceremonies: Arc<DashMap<String, Ceremony>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RelyingParty {
+    id: String,
+    name: String,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserEntity {
+    /// base64url encoded user handle
+    id: String,
+    name: String,
+    display_name: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CredentialParameters {
+    #[serde(rename = "type")]
+    credential_type: &'static str,
+    /// COSE algorithm
+    alg: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CredentialDescriptor {
+    #[serde(rename = "type")]
+    credential_type: &'static str,
+    /// base64url encoded credential id
+    id: String,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelection {
+    resident_key: &'static str,
+    user_verification: &'static str,
+}
+
+/// `publicKey` options of `navigator.credentials.create()`, binary values base64url encoded
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreationOptions {
+    challenge: String,
+    rp: RelyingParty,
+    user: UserEntity,
+    pub_key_cred_params: Vec<CredentialParameters>,
+    /// milliseconds
+    timeout: i64,
+    exclude_credentials: Vec<CredentialDescriptor>,
+    authenticator_selection: AuthenticatorSelection,
+    attestation: &'static str,
+}
+
+/// `publicKey` options of `navigator.credentials.get()`, binary values base64url encoded
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestOptions {
+    challenge: String,
+    rp_id: String,
+    /// milliseconds
+    timeout: i64,
+    /// empty for discoverable credentials
+    allow_credentials: Vec<CredentialDescriptor>,
+    user_verification: &'static str,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct AttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    client_data_json: String,
+    #[serde(rename = "attestationObject")]
+    attestation_object: String,
+}
+
+/// Result of `navigator.credentials.create()`, binary values base64url encoded
+#[derive(Deserialize, ToSchema)]
+pub struct RegistrationResponse {
+    id: String,
+    response: AttestationResponse,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct AssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    client_data_json: String,
+    #[serde(rename = "authenticatorData")]
+    authenticator_data: String,
+    signature: String,
+}
+
+/// Result of `navigator.credentials.get()`, binary values base64url encoded
+#[derive(Deserialize, ToSchema)]
+pub struct LoginResponse {
+    id: String,
+    response: AssertionResponse,
+}
+
+impl LoginResponse {
+    /// base64url encoded credential id
+    pub fn credential_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl WebAuthn {
+    pub fn new(config: WebAuthnConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            ceremonies: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Options to register a passkey for user; `registered` are ids of existing passkeys
+    pub fn start_registration(
+        &self,
+        realm: &str,
+        personnel_nr: i32,
+        username: &str,
+        registered: &[String],
+    ) -> CreationOptions {
+        let challenge = self.start(CeremonyKind::Registration, realm, Some(personnel_nr));
+        CreationOptions {
+            challenge,
+            rp: RelyingParty {
+                id: self.config.rp_id.clone(),
+                name: self.config.rp_name.clone(),
+            },
+            user: UserEntity {
+                // personnel nr is unique only within realm
+                id: encode_config(format!("{}:{}", realm, personnel_nr), URL_SAFE_NO_PAD),
+                name: username.to_owned(),
+                display_name: username.to_owned(),
+            },
+            pub_key_cred_params: [data::ES256, data::EDDSA, data::RS256]
+                .into_iter()
+                .map(|alg| CredentialParameters {
+                    credential_type: PUBLIC_KEY,
+                    alg,
+                })
+                .collect(),
+            timeout: self.timeout_millis(),
+            exclude_credentials: descriptors(registered),
+            authenticator_selection: AuthenticatorSelection {
+                resident_key: "preferred",
+                user_verification: "required",
+            },
+            attestation: "none",
+        }
+    }
+
+    /// Verifies the new passkey of user, who must have started the registration
+    pub fn finish_registration(
+        &self,
+        realm: &str,
+        personnel_nr: i32,
+        response: &RegistrationResponse,
+    ) -> Result<WebAuthnCredential, actix_web::Error> {
+        self.verify_registration(realm, personnel_nr, response)
+            .map_err(actix_web::error::ErrorBadRequest)
+    }
+
+    fn verify_registration(
+        &self,
+        realm: &str,
+        personnel_nr: i32,
+        response: &RegistrationResponse,
+    ) -> Result<WebAuthnCredential, &'static str> {
+        let client_data_json = decode(&response.response.client_data_json)?;
+        let ceremony = self.take_ceremony(&client_data_json, "webauthn.create")?;
+        if ceremony.kind != CeremonyKind::Registration
+            || ceremony.realm != realm
+            || ceremony.personnel_nr != Some(personnel_nr)
+        {
+            return Err("registration was not started by this user");
+        }
+
+        let attestation_object = decode(&response.response.attestation_object)?;
+        let auth_data = data::attestation_auth_data(&attestation_object)?;
+        let auth_data = self.check_authenticator_data(&auth_data)?;
+
+        let (credential_id, public_key) = auth_data
+            .credential
+            .ok_or("attested credential not found")?;
+        let credential_id = encode_config(credential_id, URL_SAFE_NO_PAD);
+        if credential_id != response.id.trim_end_matches('=') {
+            return Err("credential id does not match attested credential");
+        }
+        // reject keys which could not be used for login
+        PublicKey::parse(&public_key)?;
+
+        Ok(WebAuthnCredential {
+            credential_id,
+            realm: realm.to_owned(),
+            personnel_nr,
+            public_key,
+            sign_count: auth_data.sign_count as i64,
+        })
+    }
+
+    /// Options to log in with a passkey; `personnel_nr` and `allowed` are known
+    /// if user has entered username, otherwise a discoverable passkey is expected
+    pub fn start_login(
+        &self,
+        realm: &str,
+        personnel_nr: Option<i32>,
+        allowed: &[String],
+    ) -> RequestOptions {
+        let challenge = self.start(CeremonyKind::Login, realm, personnel_nr);
+        RequestOptions {
+            challenge,
+            rp_id: self.config.rp_id.clone(),
+            timeout: self.timeout_millis(),
+            allow_credentials: descriptors(allowed),
+            user_verification: "required",
+        }
+    }
+
+    /// Verifies assertion signed by stored passkey; returns new signature counter
+    pub fn finish_login(
+        &self,
+        response: &LoginResponse,
+        credential: &WebAuthnCredential,
+    ) -> Result<i64, actix_web::Error> {
+        self.verify_login(response, credential)
+            .map_err(actix_web::error::ErrorUnauthorized)
+    }
+
+    fn verify_login(
+        &self,
+        response: &LoginResponse,
+        credential: &WebAuthnCredential,
+    ) -> Result<i64, &'static str> {
+        let client_data_json = decode(&response.response.client_data_json)?;
+        let ceremony = self.take_ceremony(&client_data_json, "webauthn.get")?;
+        if ceremony.kind != CeremonyKind::Login
+            || ceremony.realm != credential.realm
+            || ceremony
+                .personnel_nr
+                .is_some_and(|nr| nr != credential.personnel_nr)
+        {
+            return Err("login was not started for this passkey");
+        }
+
+        let raw_auth_data = decode(&response.response.authenticator_data)?;
+        let auth_data = self.check_authenticator_data(&raw_auth_data)?;
+
+        // signature covers authenticator data and hash of client data
+        let mut message = raw_auth_data;
+        message.extend_from_slice(digest::digest(&digest::SHA256, &client_data_json).as_ref());
+        let signature = decode(&response.response.signature)?;
+        if !PublicKey::parse(&credential.public_key)?.verify(&message, &signature) {
+            return Err("invalid signature");
+        }
+
+        // authenticators without counter always report 0;
+        // otherwise counter which does not grow means a cloned authenticator
+        let sign_count = auth_data.sign_count as i64;
+        if (sign_count != 0 || credential.sign_count != 0) && sign_count <= credential.sign_count {
+            return Err("signature counter did not increase");
+        }
+        Ok(sign_count)
+    }
+
+    fn start(&self, kind: CeremonyKind, realm: &str, personnel_nr: Option<i32>) -> String {
+        let now = Utc::now();
+        self.ceremonies.retain(|_, ceremony| ceremony.expires > now);
+
+        let mut challenge = [0u8; CHALLENGE_LEN];
+        SystemRandom::new().fill(&mut challenge).unwrap();
+        let challenge = encode_config(challenge, URL_SAFE_NO_PAD);
+
+        self.ceremonies.insert(
+            challenge.clone(),
+            Ceremony {
+                kind,
+                realm: realm.to_owned(),
+                personnel_nr,
+                expires: now + Duration::seconds(self.config.timeout_seconds),
+            },
+        );
+        challenge
+    }
+
+    /// Checks client data and removes its ceremony, so every challenge is used once
+    fn take_ceremony(
+        &self,
+        client_data_json: &[u8],
+        ceremony_type: &str,
+    ) -> Result<Ceremony, &'static str> {
+        let client_data = ClientData::parse(client_data_json)?;
+        if client_data.ceremony != ceremony_type {
+            return Err("unexpected client data type");
+        }
+        if !self.config.origins().contains(&client_data.origin.as_str()) {
+            return Err("origin is not allowed");
+        }
+        let (_, ceremony) = self
+            .ceremonies
+            .remove(&client_data.challenge)
+            .ok_or("unknown challenge")?;
+        if ceremony.expires <= Utc::now() {
+            return Err("challenge expired");
+        }
+        Ok(ceremony)
+    }
+
+    fn check_authenticator_data(&self, data: &[u8]) -> Result<AuthenticatorData, &'static str> {
+        let auth_data = AuthenticatorData::parse(data)?;
+        let rp_id_hash = digest::digest(&digest::SHA256, self.config.rp_id.as_bytes());
+        if auth_data.rp_id_hash != rp_id_hash.as_ref() {
+            return Err("passkey belongs to another relying party");
+        }
+        // passkey replaces password, so it must be unlocked by the user
+        if !auth_data.user_present() || !auth_data.user_verified() {
+            return Err("user was not verified by authenticator");
+        }
+        Ok(auth_data)
+    }
+
+    fn timeout_millis(&self) -> i64 {
+        self.config.timeout_seconds * 1000
+    }
+}
+
+fn descriptors(credential_ids: &[String]) -> Vec<CredentialDescriptor> {
+    credential_ids
+        .iter()
+        .map(|id| CredentialDescriptor {
+            credential_type: PUBLIC_KEY,
+            id: id.clone(),
+        })
+        .collect()
+}
+
+// browsers may pad base64url values
+fn decode(value: &str) -> Result<Vec<u8>, &'static str> {
+    decode_config(value.trim_end_matches('='), URL_SAFE_NO_PAD)
+        .map_err(|_| "invalid base64url value")
+}
@@ -0,0 +1,84 @@
+//! Forward auth answers 401 for every request without a valid session
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use chrono::Utc;
+
+use identity_server_rs::config::DEFAULT_REALM;
+use identity_server_rs::database::domain::User;
+use identity_server_rs::handlers;
+use identity_server_rs::identity::{AuthTokenMiddlewareFactory, Identity};
+
+fn user() -> User {
+    User {
+        personnel_nr: 1,
+        salt: String::new(),
+        password: String::new(),
+        username: "admin".to_owned(),
+        email: None,
+        password_changed: Utc::now(),
+        must_change_password: false,
+        disabled: false,
+    }
+}
+
+fn open_session(identity: &Identity) -> String {
+    let response = identity
+        .open_session(DEFAULT_REALM, user(), vec!["REPORTS".to_owned()])
+        .unwrap();
+    let response = serde_json::to_value(response).unwrap();
+    response["token"].as_str().unwrap().to_owned()
+}
+
+#[actix_web::test]
+async fn verify_answers_unauthorized_for_malformed_header() {
+    let identity = Identity::new();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(identity.clone()))
+            .wrap(AuthTokenMiddlewareFactory::new())
+            .service(handlers::verify),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/auth/verify")
+        .insert_header((
+            "Authorization",
+            format!("Token {}", open_session(&identity)),
+        ))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    for header in ["Bearer abc", "Token", "Basic YWRtaW46YWRtaW4="] {
+        let req = test::TestRequest::get()
+            .uri("/auth/verify")
+            .insert_header(("Authorization", header))
+            .to_request();
+        let err = test::try_call_service(&app, req).await.err().unwrap();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::UNAUTHORIZED,
+            "{}",
+            header
+        );
+    }
+
+    // other endpoints still reject malformed header as bad request
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(identity.clone()))
+            .wrap(AuthTokenMiddlewareFactory::new())
+            .configure(handlers::configure),
+    )
+    .await;
+    let req = test::TestRequest::get()
+        .uri("/auth/info")
+        .insert_header(("Authorization", "Bearer abc"))
+        .to_request();
+    let err = test::try_call_service(&app, req).await.err().unwrap();
+    assert_eq!(
+        err.as_response_error().status_code(),
+        StatusCode::BAD_REQUEST
+    );
+}
@@ -0,0 +1,208 @@
+//! Keeps OpenAPI document in sync with the routes registered by the server:
+//! every documented operation must reach a handler (no 404 / 405)
+//! and every route of the route table must be routed and documented.
+use actix_web::http::{Method, StatusCode};
+use actix_web::{test, web, App};
+use chrono::Utc;
+use std::collections::BTreeSet;
+use utoipa::OpenApi;
+
+use identity_server_rs::config::{
+    AdminConfig, EventsConfig, ImpersonationConfig, WebAuthnConfig, DEFAULT_REALM,
+};
+use identity_server_rs::database::domain::User;
+use identity_server_rs::handlers;
+use identity_server_rs::identity::{AuthTokenMiddlewareFactory, Identity};
+use identity_server_rs::openapi::ApiDoc;
+use identity_server_rs::webauthn::WebAuthn;
+
+fn user() -> User {
+    User {
+        personnel_nr: 1,
+        salt: String::new(),
+        password: String::new(),
+        username: "admin".to_owned(),
+        email: None,
+        password_changed: Utc::now(),
+        must_change_password: false,
+        disabled: false,
+    }
+}
+
+// scopes require a valid session; without it they answer 401 even for unknown paths
+fn open_session(identity: &Identity) -> String {
+    let response = identity
+        .open_session(DEFAULT_REALM, user(), vec!["IDENTITY_ADMIN".to_owned()])
+        .unwrap();
+    let response = serde_json::to_value(response).unwrap();
+    response["token"].as_str().unwrap().to_owned()
+}
+
+// path parameters of documented paths and of the route table
+fn uri(path: &str) -> String {
+    path.replace("{personnel_nr}", "2")
+        .replace("{realm}", DEFAULT_REALM)
+}
+
+#[actix_web::test]
+async fn documented_operations_are_routed() {
+    let identity = Identity::new();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(identity.clone()))
+            .app_data(web::Data::new(ImpersonationConfig::default()))
+            .app_data(web::Data::new(AdminConfig::default()))
+            .app_data(web::Data::new(EventsConfig::default()))
+            .app_data(web::Data::new(WebAuthn::new(WebAuthnConfig::default())))
+            .wrap(AuthTokenMiddlewareFactory::new())
+            .configure(handlers::configure),
+    )
+    .await;
+
+    let doc = ApiDoc::openapi();
+    let mut operations = 0;
+    for (path, item) in &doc.paths.paths {
+        let methods = [
+            (Method::GET, &item.get),
+            (Method::POST, &item.post),
+            (Method::PUT, &item.put),
+            (Method::DELETE, &item.delete),
+        ];
+        for (method, operation) in methods {
+            if operation.is_none() {
+                continue;
+            }
+            operations += 1;
+
+            let req = test::TestRequest::default()
+                .method(method.clone())
+                .uri(&uri(path))
+                .insert_header((
+                    "Authorization",
+                    format!("Token {}", open_session(&identity)),
+                ))
+                .to_request();
+            let status = match test::try_call_service(&app, req).await {
+                Ok(res) => res.status(),
+                Err(err) => err.as_response_error().status_code(),
+            };
+
+            assert_ne!(
+                status,
+                StatusCode::NOT_FOUND,
+                "{} {} is not routed",
+                method,
+                path
+            );
+            assert_ne!(
+                status,
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{} {} is not routed",
+                method,
+                path
+            );
+        }
+    }
+    assert!(operations > 0);
+
+    // sanity check of the approach: unknown operations are really reported
+    let req = test::TestRequest::get()
+        .uri("/auth/unknown")
+        .insert_header((
+            "Authorization",
+            format!("Token {}", open_session(&identity)),
+        ))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::get().uri("/openapi.json").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+// routes which are not operations of the API itself
+const UNDOCUMENTED: [(&str, &str); 1] = [("GET", "/openapi.json")];
+
+#[actix_web::test]
+async fn route_table_is_routed_and_documented() {
+    let identity = Identity::new();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(identity.clone()))
+            .app_data(web::Data::new(ImpersonationConfig::default()))
+            .app_data(web::Data::new(AdminConfig::default()))
+            .app_data(web::Data::new(EventsConfig::default()))
+            .app_data(web::Data::new(WebAuthn::new(WebAuthnConfig::default())))
+            .wrap(AuthTokenMiddlewareFactory::new())
+            .configure(handlers::configure),
+    )
+    .await;
+
+    for (method, path) in handlers::ROUTES {
+        let req = test::TestRequest::default()
+            .method(Method::from_bytes(method.as_bytes()).unwrap())
+            .uri(&uri(path))
+            .insert_header((
+                "Authorization",
+                format!("Token {}", open_session(&identity)),
+            ))
+            .to_request();
+        let status = match test::try_call_service(&app, req).await {
+            Ok(res) => res.status(),
+            Err(err) => err.as_response_error().status_code(),
+        };
+        assert!(
+            status != StatusCode::NOT_FOUND && status != StatusCode::METHOD_NOT_ALLOWED,
+            "{} {} is in route table but not routed",
+            method,
+            path
+        );
+    }
+
+    let doc = ApiDoc::openapi();
+    let mut documented = BTreeSet::new();
+    for (path, item) in &doc.paths.paths {
+        let methods = [
+            ("GET", &item.get),
+            ("POST", &item.post),
+            ("PUT", &item.put),
+            ("DELETE", &item.delete),
+        ];
+        for (method, operation) in methods {
+            if operation.is_some() {
+                documented.insert((method, path.as_str()));
+            }
+        }
+    }
+
+    let routes: BTreeSet<(&str, &str)> = handlers::ROUTES
+        .into_iter()
+        .filter(|route| !UNDOCUMENTED.contains(route))
+        .collect();
+    let undocumented: Vec<_> = routes.difference(&documented).collect();
+    assert!(
+        undocumented.is_empty(),
+        "registered but not documented: {:?}",
+        undocumented
+    );
+    let unlisted: Vec<_> = documented.difference(&routes).collect();
+    assert!(
+        unlisted.is_empty(),
+        "documented but not in route table: {:?}",
+        unlisted
+    );
+}
+
+#[cfg(feature = "swagger-ui")]
+#[actix_web::test]
+async fn swagger_ui_is_served() {
+    let app = test::init_service(App::new().configure(handlers::configure)).await;
+
+    for uri in ["/docs/", "/docs/swagger-initializer.js"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK, "{}", uri);
+        assert!(res.headers().contains_key("content-security-policy"));
+    }
+}
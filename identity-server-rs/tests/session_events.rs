@@ -0,0 +1,49 @@
+//! Replay of session events for `Last-Event-ID`
+use identity_server_rs::identity::{SessionEventKind, SessionEvents};
+
+fn publish_disabled(events: &SessionEvents, count: i32) {
+    for personnel_nr in 1..=count {
+        events.publish("default", SessionEventKind::UserDisabled { personnel_nr });
+    }
+}
+
+#[test]
+fn replays_events_after_last_id() {
+    let events = SessionEvents::new();
+    publish_disabled(&events, 3);
+
+    let subscription = events.subscribe(Some(1));
+    assert!(!subscription.resync);
+    let ids: Vec<u64> = subscription.replay.iter().map(|it| it.id).collect();
+    assert_eq!(ids, vec![2, 3]);
+
+    let subscription = events.subscribe(Some(3));
+    assert!(!subscription.resync);
+    assert!(subscription.replay.is_empty());
+}
+
+#[test]
+fn unknown_last_id_requires_resync() {
+    let events = SessionEvents::new();
+    publish_disabled(&events, 3);
+
+    // ids of server before restart
+    let subscription = events.subscribe(Some(7));
+    assert!(subscription.resync);
+    assert!(subscription.replay.is_empty());
+
+    for last_id in [u64::MAX - 1, u64::MAX] {
+        let subscription = events.subscribe(Some(last_id));
+        assert!(subscription.resync);
+        assert!(subscription.replay.is_empty());
+    }
+}
+
+#[test]
+fn maximal_last_id_without_events_requires_resync() {
+    let events = SessionEvents::new();
+
+    let subscription = events.subscribe(Some(u64::MAX));
+    assert!(subscription.resync);
+    assert!(subscription.replay.is_empty());
+}
@@ -0,0 +1,285 @@
+//! Passkey ceremonies against a software authenticator with P-256 key
+use actix_web::http::StatusCode;
+use base64::{encode_config, URL_SAFE_NO_PAD};
+use ciborium::value::Value;
+use ring::digest;
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+use serde_json::json;
+
+use identity_server_rs::config::{WebAuthnConfig, DEFAULT_REALM};
+use identity_server_rs::database::domain::WebAuthnCredential;
+use identity_server_rs::webauthn::{
+    CreationOptions, LoginResponse, RegistrationResponse, RequestOptions, WebAuthn,
+};
+
+const ORIGIN: &str = "https://localhost:8443";
+
+// flags of authenticator data
+const USER_PRESENT: u8 = 0x01;
+const USER_VERIFIED: u8 = 0x04;
+const ATTESTED_CREDENTIAL: u8 = 0x40;
+
+struct SoftwareAuthenticator {
+    key_pair: EcdsaKeyPair,
+    credential_id: Vec<u8>,
+    sign_count: u32,
+    origin: String,
+    flags: u8,
+}
+
+impl SoftwareAuthenticator {
+    fn new() -> Self {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+        let key_pair =
+            EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref()).unwrap();
+        let mut credential_id = vec![0u8; 16];
+        rng.fill(&mut credential_id).unwrap();
+
+        Self {
+            key_pair,
+            credential_id,
+            sign_count: 0,
+            origin: ORIGIN.to_owned(),
+            flags: USER_PRESENT | USER_VERIFIED,
+        }
+    }
+
+    fn credential_id(&self) -> String {
+        encode_config(&self.credential_id, URL_SAFE_NO_PAD)
+    }
+
+    fn register(&mut self, options: &CreationOptions) -> RegistrationResponse {
+        let options = serde_json::to_value(options).unwrap();
+        let rp_id = options["rp"]["id"].as_str().unwrap();
+        let client_data = self.client_data("webauthn.create", &options["challenge"]);
+
+        let attestation_object = Value::Map(vec![
+            (Value::from("fmt"), Value::from("none")),
+            (Value::from("attStmt"), Value::Map(vec![])),
+            (
+                Value::from("authData"),
+                Value::Bytes(self.authenticator_data(rp_id, true)),
+            ),
+        ]);
+        let mut attestation_object_bytes = Vec::new();
+        ciborium::ser::into_writer(&attestation_object, &mut attestation_object_bytes).unwrap();
+
+        serde_json::from_value(json!({
+            "id": self.credential_id(),
+            "rawId": self.credential_id(),
+            "type": "public-key",
+            "response": {
+                "clientDataJSON": encode_config(&client_data, URL_SAFE_NO_PAD),
+                "attestationObject": encode_config(&attestation_object_bytes, URL_SAFE_NO_PAD),
+            }
+        }))
+        .unwrap()
+    }
+
+    fn login(&mut self, options: &RequestOptions) -> LoginResponse {
+        let options = serde_json::to_value(options).unwrap();
+        let rp_id = options["rpId"].as_str().unwrap();
+        let client_data = self.client_data("webauthn.get", &options["challenge"]);
+
+        self.sign_count += 1;
+        let authenticator_data = self.authenticator_data(rp_id, false);
+        let mut message = authenticator_data.clone();
+        message.extend_from_slice(digest::digest(&digest::SHA256, &client_data).as_ref());
+        let signature = self.key_pair.sign(&SystemRandom::new(), &message).unwrap();
+
+        serde_json::from_value(json!({
+            "id": self.credential_id(),
+            "rawId": self.credential_id(),
+            "type": "public-key",
+            "response": {
+                "clientDataJSON": encode_config(&client_data, URL_SAFE_NO_PAD),
+                "authenticatorData": encode_config(&authenticator_data, URL_SAFE_NO_PAD),
+                "signature": encode_config(signature.as_ref(), URL_SAFE_NO_PAD),
+                "userHandle": null,
+            }
+        }))
+        .unwrap()
+    }
+
+    fn client_data(&self, ceremony: &str, challenge: &serde_json::Value) -> Vec<u8> {
+        serde_json::to_vec(&json!({
+            "type": ceremony,
+            "challenge": challenge,
+            "origin": self.origin,
+            "crossOrigin": false,
+        }))
+        .unwrap()
+    }
+
+    fn authenticator_data(&self, rp_id: &str, attested: bool) -> Vec<u8> {
+        let mut data = digest::digest(&digest::SHA256, rp_id.as_bytes())
+            .as_ref()
+            .to_vec();
+        let flags = if attested {
+            self.flags | ATTESTED_CREDENTIAL
+        } else {
+            self.flags
+        };
+        data.push(flags);
+        data.extend_from_slice(&self.sign_count.to_be_bytes());
+        if attested {
+            // aaguid of software authenticator is all zeros
+            data.extend_from_slice(&[0u8; 16]);
+            data.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+            data.extend_from_slice(&self.credential_id);
+            data.extend_from_slice(&self.cose_key());
+        }
+        data
+    }
+
+    fn cose_key(&self) -> Vec<u8> {
+        // uncompressed point: 0x04, x, y
+        let point = self.key_pair.public_key().as_ref();
+        let key = Value::Map(vec![
+            (Value::from(1), Value::from(2)),
+            (Value::from(3), Value::from(-7)),
+            (Value::from(-1), Value::from(1)),
+            (Value::from(-2), Value::Bytes(point[1..33].to_vec())),
+            (Value::from(-3), Value::Bytes(point[33..].to_vec())),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::ser::into_writer(&key, &mut bytes).unwrap();
+        bytes
+    }
+}
+
+fn webauthn() -> WebAuthn {
+    WebAuthn::new(WebAuthnConfig::default())
+}
+
+fn register(webauthn: &WebAuthn, authenticator: &mut SoftwareAuthenticator) -> WebAuthnCredential {
+    let options = webauthn.start_registration(DEFAULT_REALM, 7, "ion", &[]);
+    let response = authenticator.register(&options);
+    webauthn
+        .finish_registration(DEFAULT_REALM, 7, &response)
+        .unwrap()
+}
+
+fn status(err: actix_web::Error) -> StatusCode {
+    err.as_response_error().status_code()
+}
+
+#[test]
+fn passkey_is_registered_and_used_for_login() {
+    let webauthn = webauthn();
+    let mut authenticator = SoftwareAuthenticator::new();
+
+    let mut credential = register(&webauthn, &mut authenticator);
+    assert_eq!(credential.credential_id, authenticator.credential_id());
+    assert_eq!(credential.realm, DEFAULT_REALM);
+    assert_eq!(credential.personnel_nr, 7);
+
+    let options = webauthn.start_login(DEFAULT_REALM, Some(7), &[credential.credential_id.clone()]);
+    let allowed = serde_json::to_value(&options).unwrap()["allowCredentials"].clone();
+    assert_eq!(allowed[0]["id"], json!(authenticator.credential_id()));
+
+    let response = authenticator.login(&options);
+    assert_eq!(response.credential_id(), credential.credential_id);
+    credential.sign_count = webauthn.finish_login(&response, &credential).unwrap();
+    assert_eq!(credential.sign_count, 1);
+
+    // discoverable passkey, user is not known before assertion
+    let options = webauthn.start_login(DEFAULT_REALM, None, &[]);
+    let response = authenticator.login(&options);
+    assert_eq!(webauthn.finish_login(&response, &credential).unwrap(), 2);
+}
+
+#[test]
+fn challenge_is_used_once() {
+    let webauthn = webauthn();
+    let mut authenticator = SoftwareAuthenticator::new();
+    let credential = register(&webauthn, &mut authenticator);
+
+    let options = webauthn.start_login(DEFAULT_REALM, Some(7), &[]);
+    let response = authenticator.login(&options);
+    assert!(webauthn.finish_login(&response, &credential).is_ok());
+
+    let err = webauthn.finish_login(&response, &credential).unwrap_err();
+    assert_eq!(status(err), StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+fn registration_is_bound_to_user_and_origin() {
+    let webauthn = webauthn();
+    let mut authenticator = SoftwareAuthenticator::new();
+
+    let options = webauthn.start_registration(DEFAULT_REALM, 7, "ion", &[]);
+    let response = authenticator.register(&options);
+    let err = webauthn
+        .finish_registration(DEFAULT_REALM, 8, &response)
+        .unwrap_err();
+    assert_eq!(status(err), StatusCode::BAD_REQUEST);
+
+    authenticator.origin = "https://phishing.example".to_owned();
+    let options = webauthn.start_registration(DEFAULT_REALM, 7, "ion", &[]);
+    let response = authenticator.register(&options);
+    assert!(webauthn
+        .finish_registration(DEFAULT_REALM, 7, &response)
+        .is_err());
+}
+
+#[test]
+fn login_requires_signature_of_registered_key() {
+    let webauthn = webauthn();
+    let mut authenticator = SoftwareAuthenticator::new();
+    let credential = register(&webauthn, &mut authenticator);
+
+    // same credential id, other key
+    let mut impostor = SoftwareAuthenticator::new();
+    impostor.credential_id = authenticator.credential_id.clone();
+    let options = webauthn.start_login(DEFAULT_REALM, None, &[]);
+    let response = impostor.login(&options);
+    assert!(webauthn.finish_login(&response, &credential).is_err());
+}
+
+#[test]
+fn login_is_bound_to_expected_user() {
+    let webauthn = webauthn();
+    let mut authenticator = SoftwareAuthenticator::new();
+    let credential = register(&webauthn, &mut authenticator);
+
+    let options = webauthn.start_login(DEFAULT_REALM, Some(8), &[]);
+    let response = authenticator.login(&options);
+    assert!(webauthn.finish_login(&response, &credential).is_err());
+
+    let options = webauthn.start_login("contractors", None, &[]);
+    let response = authenticator.login(&options);
+    assert!(webauthn.finish_login(&response, &credential).is_err());
+}
+
+#[test]
+fn cloned_authenticator_is_rejected() {
+    let webauthn = webauthn();
+    let mut authenticator = SoftwareAuthenticator::new();
+    let mut credential = register(&webauthn, &mut authenticator);
+    credential.sign_count = 5;
+
+    let options = webauthn.start_login(DEFAULT_REALM, Some(7), &[]);
+    let response = authenticator.login(&options);
+    assert!(webauthn.finish_login(&response, &credential).is_err());
+}
+
+#[test]
+fn user_verification_is_required() {
+    let webauthn = webauthn();
+    let mut authenticator = SoftwareAuthenticator::new();
+    let credential = register(&webauthn, &mut authenticator);
+
+    authenticator.flags = USER_PRESENT;
+    let options = webauthn.start_login(DEFAULT_REALM, Some(7), &[]);
+    let response = authenticator.login(&options);
+    assert!(webauthn.finish_login(&response, &credential).is_err());
+
+    let options = webauthn.start_registration(DEFAULT_REALM, 7, "ion", &[]);
+    let response = authenticator.register(&options);
+    assert!(webauthn
+        .finish_registration(DEFAULT_REALM, 7, &response)
+        .is_err());
+}
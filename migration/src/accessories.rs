@@ -6,6 +6,8 @@ use itertools::Itertools;
 use oracle::*;
 
 use grants;
+use ddl::{ Ddl, Section };
+use rules::Selection;
 use logger::*;
 
 struct DbObject {
@@ -56,13 +58,14 @@ impl MetaQuery for DependenciesStruct {
 }
 
 pub fn load(source: &Connection,
-            destination: &Connection,
+            destination: &Ddl,
             schemas: &Vec<String>,
             existing_schemas: &Vec<String>,
+            selection: &Selection,
             grants_service: &mut grants::GrantsService,
             err_logger: &ErrLogger) -> Result<(), String> {
 
-    let db_objects_map = load_objects(source, schemas)?;
+    let db_objects_map = load_objects(source, schemas, selection)?;
     let db_existing_sources = load_existing_sources(destination, existing_schemas)?;
 
     let mut processed_objects: HashSet<&Key> = HashSet::new();
@@ -102,7 +105,7 @@ pub fn load(source: &Connection,
     Ok(())
 }
 
-fn process_object(destination: &Connection,
+fn process_object(destination: &Ddl,
                   object: &DbObject,
                   grants_service: &mut grants::GrantsService,
                   db_existing_sources: &HashSet<String>,
@@ -117,7 +120,7 @@ fn process_object(destination: &Connection,
         return Ok(())
     }
 
-    if let Err(err) = destination.execute(object.sql.clone()) {
+    if let Err(err) = destination.execute(Section::Code, owner, object.sql.clone()) {
         let err_text = match err.code {
             904 => "invalid identifier".to_string(),
             942 => "table or view does not exists".to_string(),
@@ -137,11 +140,14 @@ fn process_object(destination: &Connection,
     Ok(())
 }
 
-fn load_existing_sources(destination: &Connection, schemas: &Vec<String>) -> Result<HashSet<String>, String> {    
+fn load_existing_sources(destination: &Ddl, schemas: &Vec<String>) -> Result<HashSet<String>, String> {    
     let mut db_objects_set: HashSet<String> = HashSet::new();
-    if schemas.len() == 0 {
-        return Ok(db_objects_set);
-    }
+
+    // script does not know destination, its objects are created or replaced
+    let destination = match destination.connection() {
+        Some(conn) if schemas.len() != 0 => conn,
+        _ => return Ok(db_objects_set)
+    };
 
     let schemas = schemas.iter().map(|s| format!("'{}'", s)).join(",");
     let sql = format!("select owner, name from sys.all_source where owner in ({})", schemas);
@@ -177,23 +183,23 @@ impl MetaQuery for SourceNameStruct {
     }
 }
 
-fn load_objects(source: &Connection, schemas: &Vec<String>) -> Result<HashMap<Key,DbObject>, String> {
+fn load_objects(source: &Connection, schemas: &Vec<String>, selection: &Selection) -> Result<HashMap<Key,DbObject>, String> {
     let schemas = schemas.iter().map(|s| format!("'{}'", s)).join(",");
     let mut db_objects_map: HashMap<Key,DbObject> = HashMap::new();
 
     let mut dependencies_loader = DependenciesLoader::new(source, &schemas)?;
 
-    for db_object in load_views(source, &schemas, &mut dependencies_loader)? {
+    for db_object in load_views(source, &schemas, selection, &mut dependencies_loader)? {
         let key = Key::new(&db_object);
         db_objects_map.insert(key, db_object);
     }
 
-    for db_object in load_synonyms(source, &schemas, &mut dependencies_loader)? {
+    for db_object in load_synonyms(source, &schemas, selection, &mut dependencies_loader)? {
         let key = Key::new(&db_object);
         db_objects_map.insert(key, db_object);
     }
     
-    for db_object in load_sources(source, &schemas, &mut dependencies_loader)? {
+    for db_object in load_sources(source, &schemas, selection, &mut dependencies_loader)? {
         let key = Key::new(&db_object);
         db_objects_map.insert(key, db_object);
     }
@@ -215,7 +221,7 @@ impl MetaQuery for ViewStruct {
     }
 }
 
-fn load_views(source: &Connection, schemas: &str, dependencies_loader: &mut DependenciesLoader) -> Result<Vec<DbObject>, String> {
+fn load_views(source: &Connection, schemas: &str, selection: &Selection, dependencies_loader: &mut DependenciesLoader) -> Result<Vec<DbObject>, String> {
     let sql = format!("select owner, view_name, text from sys.all_views where owner in ({})", schemas);
 
     let mut query = source.query(sql)
@@ -232,6 +238,10 @@ fn load_views(source: &Connection, schemas: &str, dependencies_loader: &mut Depe
         let name = ti.1;
         let text = ti.2;
 
+        if !selection.selects(&owner, &name) {
+            continue;
+        }
+
         let references = dependencies_loader.load(&owner, &name, "VIEW")?;
 
         let sql = format!("create or replace view {}.{} as {}", &owner, &name, text);
@@ -262,7 +272,7 @@ impl MetaQuery for SynonymStruct {
     }
 }
 
-fn load_synonyms(source: &Connection, schemas: &str, dependencies_loader: &mut DependenciesLoader) -> Result<Vec<DbObject>, String> {
+fn load_synonyms(source: &Connection, schemas: &str, selection: &Selection, dependencies_loader: &mut DependenciesLoader) -> Result<Vec<DbObject>, String> {
     let sql = format!("select owner, synonym_name, table_owner, table_name from sys.all_synonyms where owner in ({})", schemas);
 
     let mut query = source.query(sql)
@@ -280,6 +290,10 @@ fn load_synonyms(source: &Connection, schemas: &str, dependencies_loader: &mut D
         let table_owner = ti.2;
         let table_name = ti.3;
 
+        if !selection.selects(&owner, &name) {
+            continue;
+        }
+
         let references = dependencies_loader.load(&owner, &name, "SYNONYM")?;
 
         let sql = format!("create synonym {}.{} for {}.{}", &owner, &name, &table_owner, &table_name);
@@ -311,7 +325,7 @@ impl MetaQuery for SourceStruct {
     }
 }
 
-fn load_sources(source: &Connection, schemas: &str, dependencies_loader: &mut DependenciesLoader) -> Result<Vec<DbObject>, String> {
+fn load_sources(source: &Connection, schemas: &str, selection: &Selection, dependencies_loader: &mut DependenciesLoader) -> Result<Vec<DbObject>, String> {
     let sql = format!("select owner, name, type, line, text from sys.all_source where owner in ({}) order by owner, name, type, line", schemas);
 
     let mut query = source.query(sql)
@@ -325,6 +339,8 @@ fn load_sources(source: &Connection, schemas: &str, dependencies_loader: &mut De
 
     let mut cur_object: Option<(Rc<String>, Rc<String>, DbObjectType, Vec<Key>)> = None;
     let mut sql = String::new();
+    // lines of object which is not selected
+    let mut skip = false;
     for ti in iterator { 
         let owner = ti.0;
         let name = ti.1;
@@ -346,6 +362,12 @@ fn load_sources(source: &Connection, schemas: &str, dependencies_loader: &mut De
                 }
             }
 
+            skip = !selection.selects(&owner, &name);
+            if skip {
+                cur_object = None;
+                continue;
+            }
+
             sql = text.trim_left().to_uppercase().replacen(&name, &(format!("{}.{}", owner,name)), 1);
 
             let references = dependencies_loader.load(&owner, &name, obj_type)?;
@@ -353,7 +375,7 @@ fn load_sources(source: &Connection, schemas: &str, dependencies_loader: &mut De
             let object_type = get_object_type(obj_type);
 
             cur_object = Some((Rc::new(owner), Rc::new(name), object_type, references));
-        } else {
+        } else if !skip {
             sql.push_str(&text);
         }
     }
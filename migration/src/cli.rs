@@ -0,0 +1,225 @@
+use std::env;
+use std::path::Path;
+
+pub const USAGE: &'static str = "usage: migration <command> [options]
+
+commands:
+  tables          drop and create schemas, copy tables, indexes and sequences
+  accessories     foreign keys, views, code objects, snapshots, triggers, compile
+  all             tables followed by accessories
+  compile         compile schemas in destination
+  drop-schemas    drop schemas from destination (schemas marked assumexists are kept)
+  verify          compare rows of tables in source and destination
+  list            print objects of source selected by include and exclude rules
+  sync            merge rows changed since previous tables or sync into destination
+
+options:
+  -c, --config <file>      connections and buffer size, default: config.json
+  -s, --schemas <file>     list of schemas to migrate, default: config-content.json
+  -l, --log <file>         log file, default: migrate.log next to config
+  -e, --error-log <file>   error log file, default: errors.log next to config
+  -j, --journal <file>     checkpoint journal, default: journal.log next to config
+  -w, --watermarks <file>  high-water marks of tables for sync, recorded by tables and sync,
+                           default: watermarks.log next to config
+  -r, --resume             continue interrupted migration: skip steps finished according
+                           to journal, reload the table which was in progress
+      --script <dir>       dry run: read source and write the migration as SQL scripts
+                           to dir (one directory per schema, install.sql runs them all)
+                           instead of executing it; rows are not copied
+      --verify             verify tables after they are copied by tables or all
+      --checksums          verify also checksums of columns, not only rows count
+  -h, --help               print this help";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Tables, Accessories, All, Compile, DropSchemas, Verify, Sync, List
+}
+
+#[derive(Debug)]
+pub struct Args {
+    pub command:   Command,
+    pub config:    String,
+    pub schemas:   String,
+    pub log:       String,
+    pub error_log: String,
+    pub journal:   String,
+    pub watermarks: String,
+    pub resume:    bool,
+    pub script:    Option<String>,
+    pub verify:    bool,
+    pub checksums: bool
+}
+
+/// result of parsing when user asked for help instead of a command
+pub enum Parsed {
+    Help, Run(Args)
+}
+
+impl Command {
+    fn parse(name: &str) -> Result<Command, String> {
+        match name {
+            "tables" => Ok(Command::Tables),
+            "accessories" => Ok(Command::Accessories),
+            "all" => Ok(Command::All),
+            "compile" => Ok(Command::Compile),
+            "drop-schemas" => Ok(Command::DropSchemas),
+            "verify" => Ok(Command::Verify),
+            "sync" => Ok(Command::Sync),
+            "list" => Ok(Command::List),
+            _ => Err(format!("unknown command: {}", name))
+        }
+    }
+}
+
+pub fn parse_args() -> Result<Parsed, String> {
+    parse(env::args().skip(1))
+}
+
+pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Parsed, String> {
+    let mut command = None;
+    let mut config = None;
+    let mut schemas = None;
+    let mut log = None;
+    let mut error_log = None;
+    let mut journal = None;
+    let mut watermarks = None;
+    let mut resume = false;
+    let mut script = None;
+    let mut verify = false;
+    let mut checksums = false;
+
+    while let Some(arg) = args.next() {
+        let arg: &str = &arg;
+        match arg {
+            "-h" | "--help" => return Ok(Parsed::Help),
+            "-c" | "--config" => config = Some(value(arg, args.next())?),
+            "-s" | "--schemas" => schemas = Some(value(arg, args.next())?),
+            "-l" | "--log" => log = Some(value(arg, args.next())?),
+            "-e" | "--error-log" => error_log = Some(value(arg, args.next())?),
+            "-j" | "--journal" => journal = Some(value(arg, args.next())?),
+            "-w" | "--watermarks" => watermarks = Some(value(arg, args.next())?),
+            "-r" | "--resume" => resume = true,
+            "--script" => script = Some(value(arg, args.next())?),
+            "--verify" => verify = true,
+            "--checksums" => checksums = true,
+            _ if arg.starts_with('-') => return Err(format!("unknown option: {}", arg)),
+            _ => {
+                if command.is_some() {
+                    return Err(format!("unexpected argument: {}", arg));
+                }
+                command = Some(Command::parse(arg)?);
+            }
+        }
+    }
+
+    let command = command.ok_or("command is missing".to_string())?;
+    let config = config.unwrap_or("config.json".to_string());
+    let schemas = schemas.unwrap_or("config-content.json".to_string());
+
+    // logs are kept next to config, as every database has own config directory
+    let log = log.unwrap_or_else(|| sibling(&config, "migrate.log"));
+    let error_log = error_log.unwrap_or_else(|| sibling(&config, "errors.log"));
+    let journal = journal.unwrap_or_else(|| sibling(&config, "journal.log"));
+    let watermarks = watermarks.unwrap_or_else(|| sibling(&config, "watermarks.log"));
+
+    if resume && script.is_some() {
+        return Err("script can not be resumed".to_string());
+    }
+    if script.is_some() && (verify || command == Command::Verify) {
+        return Err("script can not be verified".to_string());
+    }
+    if script.is_some() && command == Command::Sync {
+        return Err("sync can not be written to script".to_string());
+    }
+    if script.is_some() && command == Command::List {
+        return Err("list can not be written to script".to_string());
+    }
+
+    Ok(Parsed::Run(Args { command, config, schemas, log, error_log, journal, watermarks, resume, script, verify, checksums }))
+}
+
+fn value(option: &str, value: Option<String>) -> Result<String, String> {
+    match value {
+        Some(ref v) if !v.starts_with('-') => Ok(v.clone()),
+        _ => Err(format!("option {} requires a value", option))
+    }
+}
+
+fn sibling(file_name: &str, name: &str) -> String {
+    match Path::new(file_name).parent() {
+        Some(dir) => dir.join(name).to_string_lossy().into_owned(),
+        None => name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &[&str]) -> Result<Args, String> {
+        match parse(args.iter().map(|a| a.to_string()))? {
+            Parsed::Run(args) => Ok(args),
+            Parsed::Help => Err("help".to_string())
+        }
+    }
+
+    #[test]
+    fn defaults_are_next_to_config() {
+        let args = run(&["tables", "-c", "db/prod/config.json"]).unwrap();
+        assert_eq!(args.command, Command::Tables);
+        assert_eq!(args.config, "db/prod/config.json");
+        assert_eq!(args.schemas, "config-content.json");
+        assert_eq!(args.log, Path::new("db/prod").join("migrate.log").to_string_lossy());
+        assert_eq!(args.error_log, Path::new("db/prod").join("errors.log").to_string_lossy());
+        assert_eq!(args.journal, Path::new("db/prod").join("journal.log").to_string_lossy());
+        assert_eq!(args.watermarks, Path::new("db/prod").join("watermarks.log").to_string_lossy());
+        assert!(!args.resume && !args.verify && !args.checksums && args.script.is_none());
+    }
+
+    #[test]
+    fn defaults_without_config() {
+        let args = run(&["all"]).unwrap();
+        assert_eq!(args.config, "config.json");
+        assert_eq!(args.log, "migrate.log");
+        assert_eq!(args.journal, "journal.log");
+    }
+
+    #[test]
+    fn explicit_logs_override_defaults() {
+        let args = run(&["sync", "--config", "db/config.json", "-l", "sync.log", "-w", "marks.log"]).unwrap();
+        assert_eq!(args.log, "sync.log");
+        assert_eq!(args.watermarks, "marks.log");
+    }
+
+    #[test]
+    fn help() {
+        assert!(match parse(vec!["tables".to_string(), "-h".to_string()].into_iter()) {
+            Ok(Parsed::Help) => true,
+            _ => false
+        });
+    }
+
+    #[test]
+    fn unknown_option_and_command() {
+        assert_eq!(run(&["tables", "--force"]).unwrap_err(), "unknown option: --force");
+        assert_eq!(run(&["copy"]).unwrap_err(), "unknown command: copy");
+        assert_eq!(run(&["tables", "all"]).unwrap_err(), "unexpected argument: all");
+        assert_eq!(run(&["-r"]).unwrap_err(), "command is missing");
+    }
+
+    #[test]
+    fn missing_value() {
+        assert_eq!(run(&["tables", "-c"]).unwrap_err(), "option -c requires a value");
+        assert_eq!(run(&["tables", "--script", "--verify"]).unwrap_err(), "option --script requires a value");
+    }
+
+    #[test]
+    fn script_conflicts() {
+        assert!(run(&["tables", "--script", "out"]).is_ok());
+        assert_eq!(run(&["verify", "--script", "out"]).unwrap_err(), "script can not be verified");
+        assert_eq!(run(&["all", "--script", "out", "--verify"]).unwrap_err(), "script can not be verified");
+        assert_eq!(run(&["sync", "--script", "out"]).unwrap_err(), "sync can not be written to script");
+        assert_eq!(run(&["list", "--script", "out"]).unwrap_err(), "list can not be written to script");
+        assert_eq!(run(&["tables", "--script", "out", "-r"]).unwrap_err(), "script can not be resumed");
+    }
+}
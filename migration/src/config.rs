@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::io::prelude::*;
 use std::fs::File;
 use serde_json;
@@ -7,7 +8,18 @@ pub struct Config {
     pub destination: Addr, 
     pub source:      Addr,
     pub buffer_size: u32,
-    pub luna_calc:   u32
+    pub luna_calc:   u32,
+    /// number of tables loaded concurrently, each by own connections; one when missing
+    pub workers:     Option<u32>,
+    /// include and exclude patterns of objects applied to every schema, see `rules`
+    pub include:     Option<Vec<String>>,
+    pub exclude:     Option<Vec<String>>,
+    /// tablespaces of destination applied to every schema
+    pub tablespaces: Option<Tablespaces>,
+    /// tables bigger than this many MB are copied in ROWID range chunks by all workers
+    pub chunk_above_mb: Option<u64>,
+    /// number of chunks of a big table; number of workers when missing
+    pub chunks:      Option<u32>
 }
 
 #[derive(Debug, PartialEq, Deserialize, Clone)]
@@ -17,29 +29,77 @@ pub struct Addr {
     pub pw:   String
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, PartialEq, Deserialize, Clone)]
 pub struct Schema {
     pub name:        String,
     pub pw:          Option<String>,
     pub assumexists: Option<bool>,
-    pub exclusions:  Option<Vec<String>>
+    pub exclusions:  Option<Vec<String>>,
+    /// glob or `re:` regex patterns of object names to migrate or to leave out
+    pub include:     Option<Vec<String>>,
+    pub exclude:     Option<Vec<String>>,
+    /// change column of table for sync, by table name: a number, a date or ORA_ROWSCN;
+    /// LUNA_CALC when table has it, ORA_ROWSCN otherwise
+    pub change_columns: Option<HashMap<String, String>>,
+    /// settings of tables by table name
+    pub tables:      Option<HashMap<String, TableConfig>>,
+    /// tablespaces of schema, over those of config
+    pub tablespaces: Option<Tablespaces>,
+    /// logging of tables and indexes: nologging when false; tables log
+    /// and indexes do not when missing
+    pub logging:     Option<bool>,
+    /// compression clause of tables, as `compress basic` or `row store compress advanced`
+    pub compress:    Option<String>
+}
+
+/// Tablespaces in destination: `remap` by tablespace in source, and defaults of tables,
+/// indexes and LOBs for tablespaces not in remap. Without defaults tables go to DATA,
+/// indexes to GRAND_INDEX and LOBs stay with their table; users get TEMP as temporary.
+#[derive(Debug, PartialEq, Deserialize, Clone, Default)]
+pub struct Tablespaces {
+    pub remap:     Option<HashMap<String, String>>,
+    pub tables:    Option<String>,
+    pub indexes:   Option<String>,
+    pub lobs:      Option<String>,
+    pub temporary: Option<String>
+}
+
+/// What of table is copied: rows matching `where`, columns not in `exclude`,
+/// and values of columns in `transforms` replaced while they are read
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+pub struct TableConfig {
+    #[serde(rename = "where")]
+    pub filter:     Option<String>,
+    pub exclude:    Option<Vec<String>>,
+    pub transforms: Option<HashMap<String, Transform>>
+}
+
+/// Masking of column: `{"constant": "value"}`, `"null"`, `"hash"` or `"fake-name"`
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum Transform {
+    Constant(String), Null, Hash, FakeName
 }
 
 impl Config {
-    pub fn load_config(file_name: &str) -> Config {
-        let s = Self::load_file(file_name);
-        serde_json::from_str(&s).unwrap()
+    pub fn load_config(file_name: &str) -> Result<Config, String> {
+        let s = Self::load_file(file_name)?;
+        serde_json::from_str(&s)
+            .map_err(|err| format!("can not parse config: {} with error: {}", file_name, err))
     }
 
-    pub fn load_content(file_name: &str) -> Vec<Schema> {
-        let s = Self::load_file(file_name);
-        serde_json::from_str(&s).unwrap()        
+    pub fn load_content(file_name: &str) -> Result<Vec<Schema>, String> {
+        let s = Self::load_file(file_name)?;
+        serde_json::from_str(&s)
+            .map_err(|err| format!("can not parse schemas list: {} with error: {}", file_name, err))
     }
 
-    fn load_file(file_name: &str) -> String {
-        let mut f = File::open(file_name).unwrap();
+    fn load_file(file_name: &str) -> Result<String, String> {
+        let mut f = File::open(file_name)
+            .map_err(|err| format!("can not open file: {} with error: {}", file_name, err))?;
         let mut s = String::new();
-        f.read_to_string(&mut s).unwrap();
-        s
+        f.read_to_string(&mut s)
+            .map_err(|err| format!("can not read file: {} with error: {}", file_name, err))?;
+        Ok(s)
     }
 }
\ No newline at end of file
@@ -0,0 +1,56 @@
+use oracle::*;
+use script::Script;
+
+/// Part of the migration which a DDL statement belongs to, in order of execution
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Section {
+    Drop, Users, Tables, Sequences, Indexes, Constraints, Code, Grants, Compile
+}
+
+impl Section {
+    pub const ALL: [Section; 9] = [
+        Section::Drop, Section::Users, Section::Tables, Section::Sequences, Section::Indexes,
+        Section::Constraints, Section::Code, Section::Grants, Section::Compile
+    ];
+
+    pub fn file_name(&self) -> &'static str {
+        match *self {
+            Section::Drop        => "00_drop.sql",
+            Section::Users       => "01_users.sql",
+            Section::Tables      => "02_tables.sql",
+            Section::Sequences   => "03_sequences.sql",
+            Section::Indexes     => "04_indexes.sql",
+            Section::Constraints => "05_constraints.sql",
+            Section::Code        => "06_code.sql",
+            Section::Grants      => "07_grants.sql",
+            Section::Compile     => "08_compile.sql"
+        }
+    }
+}
+
+/// Target of DDL statements: executed in destination or written to script for review
+#[derive(Clone, Copy)]
+pub enum Ddl<'a> {
+    Execute(&'a Connection),
+    Script(&'a Script)
+}
+
+impl <'a> Ddl<'a> {
+    /// executes statement in destination or appends it to the script of schema;
+    /// in script mode only writing of script fails, with error which has no Oracle code
+    pub fn execute(&self, section: Section, schema: &str, sql: String) -> Result<(), OracleError> {
+        match *self {
+            Ddl::Execute(conn) => conn.execute(sql),
+            Ddl::Script(script) => script.write(section, schema, &sql)
+                .map_err(|err| OracleError::new(err, "Ddl::execute"))
+        }
+    }
+
+    /// destination connection, none in script mode
+    pub fn connection(&self) -> Option<&'a Connection> {
+        match *self {
+            Ddl::Execute(conn) => Some(conn),
+            Ddl::Script(_) => None
+        }
+    }
+}
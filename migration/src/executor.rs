@@ -16,60 +16,196 @@ use accessories;
 use snapshots;
 use triggers;
 use foreign_keys;
+use ddl::Ddl;
+use journal::{ Journal, Step };
+use workers;
+use verify;
+use sync;
+use list;
+use rules::{ Rules, Selection };
+use sync::{ Tracking, Watermarks };
 
 pub struct Executor<'g, 'a: 'g> {
     source:         &'a oracle::Connection,
-    destination:    &'a oracle::Connection,
+    destination:    Ddl<'a>,
     schemas:        Vec<config::Schema>,
     grants_service: grants::GrantsService<'g>,
-    luna_calc:   u32
+    luna_calc:   u32,
+    journal:        Journal
 }
 
+/// How rows are copied: one table after another over connections of executor,
+/// or concurrently by workers with own connections
+pub enum Loader {
+    Single(table_loader::LoadBuffer),
+    Workers(workers::Pool)
+}
+
+/// journal object of steps done once for all schemas
+const ALL_SCHEMAS: &'static str = "*";
+
 pub fn connect(conf: config::Config) -> Result<(oracle::Connection, oracle::Connection), String> {
-    let source_uri = conf.source.uri.clone();
-    let source = 
-        oracle::Connection::new(conf.source.user, conf.source.pw, conf.source.uri, oracle::OCICharset::WE8ISO8859P1)
-            .map_err(|err| format!("can not connect to source {:?} with error: {}", source_uri, err) )?;
+    let source = connect_source(conf.source)?;
 
-    let destination_uri = conf.destination.uri.clone(); 
-    let destination = 
-        oracle::Connection::new(conf.destination.user, conf.destination.pw, conf.destination.uri, oracle::OCICharset::EE8ISO8859P2)
-            .map_err(|err| format!("can not connect to destination {:?} with error: {}", destination_uri, err) )?;
+    let destination = connect_destination(conf.destination)?;
 
     Ok((source, destination))
 }
 
+pub fn connect_destination(destination: config::Addr) -> Result<oracle::Connection, String> {
+    let destination_uri = destination.uri.clone(); 
+    oracle::Connection::new(destination.user, destination.pw, destination.uri, oracle::OCICharset::EE8ISO8859P2)
+        .map_err(|err| format!("can not connect to destination {:?} with error: {}", destination_uri, err) )
+}
+
+/// only source is needed when migration is written to script
+pub fn connect_source(source: config::Addr) -> Result<oracle::Connection, String> {
+    let source_uri = source.uri.clone();
+    oracle::Connection::new(source.user, source.pw, source.uri, oracle::OCICharset::WE8ISO8859P1)
+        .map_err(|err| format!("can not connect to source {:?} with error: {}", source_uri, err) )
+}
+
 impl <'g, 'a: 'g> Executor <'g, 'a> {
-    pub fn new(source: &'a oracle::Connection, destination: &'a oracle::Connection, schemas: Vec<config::Schema>, luna_calc: u32) -> Result<Executor<'a, 'g>, String> {
+    pub fn new(source: &'a oracle::Connection, destination: Ddl<'a>, schemas: Vec<config::Schema>, luna_calc: u32, journal: Journal) -> Result<Executor<'a, 'g>, String> {
         let grants_service = grants::GrantsService::new(source)?;                
-        Ok( Executor { source, destination, schemas, grants_service, luna_calc } )
+        Ok( Executor { source, destination, schemas, grants_service, luna_calc, journal } )
     }
 
-    pub fn load(&mut self, load_buffer: table_loader::LoadBuffer,
+    pub fn load(&mut self, loader: Loader, watermarks: &mut Watermarks,
                 logger: &Logger, err_logger: &ErrLogger,
                 tas: &IndexesService) -> Result<(), String> {
+        let destination = self.destination.connection()
+            .ok_or("rows can not be written to script".to_string())?;
         let schemas = self.schemas.iter().filter(|&s| s.name != "SYS" && s.name != "SYSTEM").collect::<Vec<&config::Schema>>();
-        let schema_names = schemas.iter().map(|s| s.name.clone() ).collect::<Vec<String>>();
         
+        self.drop_schemas(logger)?;
+        self.create_schemas(logger)?;
+
+        let mut table_info_service = table::TableInfoService::new(self.source)?;
+
+        let mut sequences_service = sequences::SequencesService::new(self.source)?;
+
+        // marks are taken before rows are copied, so rows changed while copying are merged by sync;
+        // marks kept from interrupted run stay, as tables copied by it are not copied again
         for ref schema in &schemas {
-            let assumexists = if let Some(ae) = schema.assumexists { ae } else { false };  
-            if !assumexists {
-                logger.println(format!("drop {}", &(schema.name)));
-                schemas::drop(&self.destination, &(schema.name))?;
+            for table in table_info_service.load(schema)? {
+                let full_name = format!("{}.{}", schema.name, table.name);
+                if table.temporary || table.unsupported || watermarks.contains(&full_name) {
+                    continue;
+                }
+                match Tracking::of(schema, &table) {
+                    Ok(tracking) => {
+                        if let Some(mark) = tracking.mark(self.source, &(schema.name), &(table.name))? {
+                            watermarks.set(&full_name, tracking.column(), mark);
+                        }
+                    }
+                    Err(err) => err_logger.error(err)
+                }
             }
         }
+        watermarks.save()?;
+
+        if let Loader::Workers(ref pool) = loader {
+            let mut jobs = Vec::new();
+            for ref schema in &schemas {
+                let sizes = table::load_sizes(self.source, &(schema.name))?;
+                for table in table_info_service.load(schema)? {
+                    let bytes = sizes.get(&table.name).cloned().unwrap_or(0);
+                    jobs.extend(pool.jobs(self.source, destination, &mut self.grants_service,
+                                          schema, table, bytes, tas, &self.journal)?);
+                }
+            }
+
+            logger.newline();
+            logger.println(format!("TABLES: {} jobs by {} workers", jobs.len(), pool.workers));
+            pool.load(jobs, logger, err_logger, tas, &self.journal)?;
+        }
+
+        let lob_copy_buffer: [u8; 1024 * 1024] = [0; 1024 * 1024];
+
+        let ref mut grants_service = self.grants_service;
 
         for ref schema in &schemas {
-            let assumexists = if let Some(ae) = schema.assumexists { ae } else { false };
-            if !assumexists {
-                logger.println(format!("create {}", schema.name));
-                schemas::create(self.destination, schema)?;
+            logger.newline();
+            logger.println(format!("schema [ {} ]", schema.name));
+
+            if let Loader::Single(ref load_buffer) = loader {
+                table_executor::load(&self.source, destination, &mut table_info_service,
+                                     grants_service,
+                                     schema, self.luna_calc, 
+                                     load_buffer, &lob_copy_buffer, logger, err_logger, tas, &self.journal)?;
+                logger.newline();
+            }
+            if !self.journal.is_done(Step::Sequences, &(schema.name)) {
+                logger.println("  SEQUENCES...");
+                sequences_service.load(&(schema.name), &Rules::of(schema)?, &self.destination, grants_service)?;
+                self.journal.done(Step::Sequences, &(schema.name))?;
             }
         }
 
+        logger.newline();
+        
+        Ok(())
+    }
+
+    /// merges rows changed in source since high-water marks into existing tables of destination
+    pub fn sync(&mut self, watermarks: &mut Watermarks, load_buffer: &table_loader::LoadBuffer,
+                logger: &Logger, err_logger: &ErrLogger) -> Result<(), String> {
+        let destination = self.destination.connection()
+            .ok_or("rows can not be written to script".to_string())?;
+        let schemas = self.schemas.iter().filter(|&s| s.name != "SYS" && s.name != "SYSTEM").collect::<Vec<&config::Schema>>();
+
         let mut table_info_service = table::TableInfoService::new(self.source)?;
-        let lob_copy_buffer: [u8; 1024 * 1024] = [0; 1024 * 1024];
+        let lob_copy_buffer = vec![0u8; 1024 * 1024];
+
+        let mut failed = 0;
+
+        for ref schema in &schemas {
+            logger.newline();
+            logger.println(format!("schema [ {} ]", schema.name));
+
+            for table in table_info_service.load(schema)? {
+                if table.temporary || table.unsupported {
+                    continue;
+                }
+
+                let tn: &str = &table.name;
+                logger.print(format!("  {}", rpad(tn, 40)));
+                logger.flush();
+
+                // rows can be merged only by primary key, such tables are left as loaded
+                if table.primary_key.is_none() {
+                    logger.println(" skipped, no primary key");
+                    continue;
+                }
+
+                match sync::sync_table(self.source, destination, schema, &table, self.luna_calc,
+                                       watermarks, load_buffer, &lob_copy_buffer) {
+                    Ok(text) => logger.println(text),
+                    Err(err) => {
+                        logger.println(" failed");
+                        err_logger.error(err);
+                        failed += 1;
+                    }
+                }
+            }
+        }
+
+        logger.newline();
+
+        if failed > 0 {
+            return Err(format!("sync failed for {} tables", failed));
+        }
+        Ok(())
+    }
+
+    /// writes users, tables, indexes and sequences to script, without rows
+    pub fn script(&mut self, logger: &Logger) -> Result<(), String> {
+        let schemas = self.schemas.iter().filter(|&s| s.name != "SYS" && s.name != "SYSTEM").collect::<Vec<&config::Schema>>();
+
+        self.create_schemas(logger)?;
 
+        let mut table_info_service = table::TableInfoService::new(self.source)?;
         let mut sequences_service = sequences::SequencesService::new(self.source)?;
 
         let ref mut grants_service = self.grants_service;
@@ -78,17 +214,28 @@ impl <'g, 'a: 'g> Executor <'g, 'a> {
             logger.newline();
             logger.println(format!("schema [ {} ]", schema.name));
 
-            table_executor::load(&self.source, &self.destination, &mut table_info_service,
-                                 grants_service,
-                                 schema, self.luna_calc, 
-                                 &load_buffer, &lob_copy_buffer, logger, err_logger, tas)?;
-            logger.newline();
+            table_executor::script(&self.destination, &mut table_info_service, grants_service, schema, logger)?;
             logger.println("  SEQUENCES...");
-            sequences_service.load(&(schema.name), &self.destination, grants_service)?;
+            sequences_service.load(&(schema.name), &Rules::of(schema)?, &self.destination, grants_service)?;
         }
 
         logger.newline();
-        
+
+        Ok(())
+    }
+
+    fn create_schemas(&self, logger: &Logger) -> Result<(), String> {
+        let schemas = self.schemas.iter().filter(|&s| s.name != "SYS" && s.name != "SYSTEM").collect::<Vec<&config::Schema>>();
+
+        for ref schema in &schemas {
+            let assumexists = if let Some(ae) = schema.assumexists { ae } else { false };
+            if !assumexists && !self.journal.is_done(Step::Create, &(schema.name)) {
+                logger.println(format!("create {}", schema.name));
+                schemas::create(&self.destination, schema)?;
+                self.journal.done(Step::Create, &(schema.name))?;
+            }
+        }
+
         Ok(())
     }
 
@@ -96,13 +243,17 @@ impl <'g, 'a: 'g> Executor <'g, 'a> {
         let schemas = self.schemas.iter().filter(|&s| s.name != "SYS" && s.name != "SYSTEM").collect::<Vec<&config::Schema>>();
         let schema_names = schemas.iter().map(|s| s.name.clone() ).collect::<Vec<String>>();
         
-        logger.println("FOREIGN KEYS...");
+        let journal = self.journal.clone();
+        let selection = Selection::new(&schemas)?;
 
-        if let Err(err) = foreign_keys::load(self.source, self.destination, &schema_names) {
-            err_logger.error(err);
-        }
+        if !journal.is_done(Step::ForeignKeys, ALL_SCHEMAS) {
+            logger.println("FOREIGN KEYS...");
 
-        logger.println("ACCESSORIES...");
+            if let Err(err) = foreign_keys::load(self.source, &self.destination, &schema_names) {
+                err_logger.error(err);
+            }
+            journal.done(Step::ForeignKeys, ALL_SCHEMAS)?;
+        }
 
         let existing_schemas = schemas.iter().filter(|&s| {
             let assumexists = if let Some(ae) = s.assumexists { ae } else { false };
@@ -111,27 +262,81 @@ impl <'g, 'a: 'g> Executor <'g, 'a> {
 
         let ref mut grants_service = self.grants_service;
 
-        accessories::load(&self.source, &self.destination, 
-                &schema_names, 
-                &existing_schemas,
-                grants_service, err_logger)?;
+        if !journal.is_done(Step::Accessories, ALL_SCHEMAS) {
+            logger.println("ACCESSORIES...");
 
-        logger.println("SNAPSHOTS...");            
+            accessories::load(&self.source, &self.destination, 
+                    &schema_names, 
+                    &existing_schemas,
+                    &selection,
+                    grants_service, err_logger)?;
+            journal.done(Step::Accessories, ALL_SCHEMAS)?;
+        }
 
-        snapshots::load(self.source, self.destination, 
-            &schema_names, grants_service, err_logger)?;
+        if !journal.is_done(Step::Snapshots, ALL_SCHEMAS) {
+            logger.println("SNAPSHOTS...");            
 
-        logger.println("TRIGGERS...");            
+            snapshots::load(self.source, &self.destination, 
+                &schema_names, &selection, grants_service, err_logger)?;
+            journal.done(Step::Snapshots, ALL_SCHEMAS)?;
+        }
 
-        triggers::load(self.source, self.destination, &schema_names, err_logger)?;
+        if !journal.is_done(Step::Triggers, ALL_SCHEMAS) {
+            logger.println("TRIGGERS...");            
+
+            triggers::load(self.source, &self.destination, &schema_names, &selection, err_logger)?;
+            journal.done(Step::Triggers, ALL_SCHEMAS)?;
+        }
 
         logger.newline();
 
-        for ref schema in &schema_names {
-            logger.println(format!("compile {}", schema));
-            schemas::compile(self.destination, schema)?;
+        self.compile(logger)
+    }
+
+    /// drops schemas from destination, except those which are assumed to exist
+    pub fn drop_schemas(&self, logger: &Logger) -> Result<(), String> {
+        let schemas = self.schemas.iter().filter(|&s| s.name != "SYS" && s.name != "SYSTEM").collect::<Vec<&config::Schema>>();
+
+        for ref schema in &schemas {
+            let assumexists = if let Some(ae) = schema.assumexists { ae } else { false };  
+            if !assumexists && !self.journal.is_done(Step::Drop, &(schema.name)) {
+                logger.println(format!("drop {}", &(schema.name)));
+                schemas::drop(&self.destination, &(schema.name))?;
+                self.journal.done(Step::Drop, &(schema.name))?;
+            }
         }
-        
+
+        Ok(())
+    }
+
+    /// prints objects of source which are migrated according to include and exclude rules
+    pub fn list(&self, logger: &Logger) -> Result<(), String> {
+        let schemas = self.schemas.iter().filter(|&s| s.name != "SYS" && s.name != "SYSTEM").collect::<Vec<&config::Schema>>();
+        list::list(self.source, &schemas, logger)
+    }
+
+    /// compares tables of source and destination
+    pub fn verify(&self, checksums: bool, logger: &Logger, err_logger: &ErrLogger) -> Result<(), String> {
+        let destination = self.destination.connection()
+            .ok_or("script can not be verified".to_string())?;
+        let schemas = self.schemas.iter().filter(|&s| s.name != "SYS" && s.name != "SYSTEM").collect::<Vec<&config::Schema>>();
+
+        let mut table_info_service = table::TableInfoService::new(self.source)?;
+
+        verify::verify(self.source, destination, &mut table_info_service, &schemas, checksums, logger, err_logger)
+    }
+
+    pub fn compile(&self, logger: &Logger) -> Result<(), String> {
+        let schemas = self.schemas.iter().filter(|&s| s.name != "SYS" && s.name != "SYSTEM").collect::<Vec<&config::Schema>>();
+
+        for ref schema in &schemas {
+            if !self.journal.is_done(Step::Compile, &(schema.name)) {
+                logger.println(format!("compile {}", schema.name));
+                schemas::compile(&self.destination, &(schema.name))?;
+                self.journal.done(Step::Compile, &(schema.name))?;
+            }
+        }
+
         Ok(())
     }
 
@@ -2,9 +2,10 @@ use std::result::Result;
 use itertools::Itertools;
 
 use oracle::*;
+use ddl::{ Ddl, Section };
 
 pub fn load(source: &Connection,
-            destination: &Connection,
+            destination: &Ddl,
             schemas: &Vec<String>) -> Result<(), String> {
 
     let schemas = schemas.iter().map(|s| format!("'{}'", s)).join(",");
@@ -72,7 +73,7 @@ pub fn load(source: &Connection,
             let ref_columns = ref_columns.iter().join(",");
             let sql = format!("alter table {}.{} add constraint {} foreign key ({}) references  {}.{} ({})", 
                               &owner, &table_name, &constraint_name, &columns, &r_owner, &ref_table_name, &ref_columns);
-            if let Err(err) = destination.execute(sql.clone()) {
+            if let Err(err) = destination.execute(Section::Constraints, &owner, sql.clone()) {
                 if err.code != 2264 && err.code != 2275 {
                     println!("can not create foreign key: {}, sql: {}", err, sql);
                 }
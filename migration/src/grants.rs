@@ -1,6 +1,7 @@
 use std::result::Result;
 
 use oracle::*;
+use ddl::{ Ddl, Section };
 
 pub struct GrantsService<'a> {
     schema_bind: Binding<String>,
@@ -38,7 +39,7 @@ impl <'a> GrantsService<'a> {
         Ok( GrantsService { schema_bind, object_bind, query } )            
     }
 
-    pub fn grant(&mut self, destination: &Connection, schema: &str, object: &str) -> Result<(), String> {
+    pub fn grant(&mut self, destination: &Ddl, schema: &str, object: &str) -> Result<(), String> {
         self.schema_bind.set(schema);
         self.object_bind.set(object);
         let iterator = self.query.iterator().map_err(|err| format!("can not load grants struct with error: {}", err))?;
@@ -50,7 +51,7 @@ impl <'a> GrantsService<'a> {
 
             let sql = format!("grant {} on {}.{} to {}{}", privilege, schema, object, &grantee, grantable ); 
 
-            if let Err(err) = destination.execute(sql.clone()) {
+            if let Err(err) = destination.execute(Section::Grants, schema, sql.clone()) {
                 println!("can not grant on {}.{} to {}", schema, &object, &grantee);
             }
         }
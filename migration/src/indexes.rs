@@ -4,12 +4,15 @@ use config;
 use logger;
 use oracle::*;
 use table;
+use ddl::{ Ddl, Section };
+use journal::{ Journal, Step };
 
 use std::sync::mpsc::{ sync_channel, SyncSender };
 use std::thread;
 
 use itertools::Itertools;
 
+#[derive(Clone)]
 pub struct IndexesService {
     channel: SyncSender<Command>
 }
@@ -20,7 +23,7 @@ enum Command {
 
 impl IndexesService {
 
-    pub fn new(destination: config::Addr, logger: logger::ErrLogger) -> (IndexesService,thread::JoinHandle<()>) {
+    pub fn new(destination: config::Addr, logger: logger::ErrLogger, journal: Journal) -> (IndexesService,thread::JoinHandle<()>) {
         let (tx, rx) = sync_channel(600);
 
         let handle = thread::spawn(move|| {
@@ -40,11 +43,20 @@ impl IndexesService {
                     }
                     Command::Table(schema, table) => {
                         if let Some(ref conn) = connection {
-                            if let Err(err) = create_pk(conn, &schema, &table) {
+                            let ddl = Ddl::Execute(conn);
+                            let mut created = true;
+                            if let Err(err) = create_pk(&ddl, &schema, &table) {
                                 logger.error(err);
+                                created = false;
                             }                            
-                            if let Err(err) = create_indexes(conn, &schema, &table) {
+                            if let Err(err) = create_indexes(&ddl, &schema, &table) {
                                 logger.error(err);
+                                created = false;
+                            }
+                            if created {
+                                if let Err(err) = journal.done(Step::Indexes, &format!("{}.{}", schema, table.name)) {
+                                    logger.error(err);
+                                }
                             }
                         }
                     }
@@ -65,13 +77,13 @@ impl IndexesService {
 
 }
 
-fn create_pk(conn: &Connection, schema: &str, table: &table::TableInfo) -> Result<(), String> {
+pub fn create_pk(destination: &Ddl, schema: &str, table: &table::TableInfo) -> Result<(), String> {
     if let Some(ref pk) = table.primary_key {
         let columns: String = pk.columns.iter().join(",");
         
         let sql = "alter table ".to_string() + schema + "." + &(table.name) + 
-                  " add constraint " + &(pk.name) + " primary key (" + &columns + ") using index tablespace GRAND_INDEX";
-        conn.execute(sql.clone())
+                  " add constraint " + &(pk.name) + " primary key (" + &columns + ") using index tablespace \"" + &(pk.tablespace) + "\"";
+        destination.execute(Section::Constraints, schema, sql.clone())
             .map_err(|err| {
                 if err.code == 2264 {
                     format!("can not create primary key: {}.{}: name already used by an existing constraint", schema, pk.name)
@@ -83,11 +95,11 @@ fn create_pk(conn: &Connection, schema: &str, table: &table::TableInfo) -> Resul
     Ok(())
 }
 
-fn create_indexes(conn: &Connection, schema: &str, table: &table::TableInfo) -> Result<(), String> {
+pub fn create_indexes(destination: &Ddl, schema: &str, table: &table::TableInfo) -> Result<(), String> {
     for ref index in &table.indexes {
-        let sql = create_index_sql(schema, &(table.name), index, table.temporary);
+        let sql = create_index_sql(schema, table, index);
 
-        if let Err(err) = conn.execute(sql.clone()) {
+        if let Err(err) = destination.execute(Section::Indexes, schema, sql.clone()) {
             if err.code != 1408 && err.code != 2264 && err.code != 955 {
                 return Err(format!("can not create index: {}.{} with error: {}\n   sql: {}", schema, index.name, err, sql));
             }
@@ -96,7 +108,7 @@ fn create_indexes(conn: &Connection, schema: &str, table: &table::TableInfo) ->
     Ok(())
 }
 
-fn create_index_sql(schema: &str, table: &str, index: &table::Index, temporary: bool) -> String {
+fn create_index_sql(schema: &str, table: &table::TableInfo, index: &table::Index) -> String {
     let mut sql = "create ".to_string();
 
     if index.unique {
@@ -113,10 +125,12 @@ fn create_index_sql(schema: &str, table: &str, index: &table::Index, temporary:
         } )
         .join(",");
 
-    sql = sql + "index " + schema + "." + &(index.name) + " on " + schema + "." + table + "(" + &columns + ")";
+    sql = sql + "index " + schema + "." + &(index.name) + " on " + schema + "." + &(table.name) + "(" + &columns + ")";
 
-    if !temporary {
-        sql = sql + " tablespace GRAND_INDEX NOLOGGING"
+    if !table.temporary {
+        sql = sql + " tablespace \"" + &(index.tablespace) + "\"";
+        // indexes are rebuilt easily, so they do not log unless schema wants so
+        sql += if table.logging == Some(true) { " LOGGING" } else { " NOLOGGING" };
     }
 
     sql
@@ -0,0 +1,141 @@
+use chrono::*;
+
+use std::collections::{ HashMap, HashSet };
+use std::fs::{ File, OpenOptions };
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::sync::{ Arc, Mutex };
+
+/// Step of migration recorded in journal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Step {
+    Drop, Create, Table, Chunk, Indexes, Sequences, ForeignKeys, Accessories, Snapshots, Triggers, Compile
+}
+
+impl Step {
+    fn name(&self) -> &'static str {
+        match *self {
+            Step::Drop        => "drop",
+            Step::Create      => "create",
+            Step::Table       => "table",
+            Step::Chunk       => "chunk",
+            Step::Indexes     => "indexes",
+            Step::Sequences   => "sequences",
+            Step::ForeignKeys => "foreign-keys",
+            Step::Accessories => "accessories",
+            Step::Snapshots   => "snapshots",
+            Step::Triggers    => "triggers",
+            Step::Compile     => "compile"
+        }
+    }
+}
+
+/// Checkpoint journal of migration, one line per started and finished step:
+/// `time <tab> started|done <tab> step <tab> object [<tab> rows]`.
+/// `reset` forgets finished steps whose object starts with given prefix.
+///
+/// With resume steps finished by previous run are skipped, and a table
+/// which was started but not finished is truncated and loaded again.
+#[derive(Clone)]
+pub struct Journal {
+    file:    Arc<Mutex<Option<File>>>,
+    done:    Arc<HashMap<String, Option<u32>>>,
+    started: Arc<HashSet<String>>
+}
+
+impl Journal {
+
+    pub fn open(file_name: &str, resume: bool) -> Result<Journal, String> {
+        let mut done = HashMap::new();
+        let mut started = HashSet::new();
+
+        if resume {
+            if let Ok(f) = File::open(file_name) {
+                for line in BufReader::new(f).lines() {
+                    let line = line.map_err(|err| format!("can not read journal: {} with error: {}", file_name, err))?;
+                    let fields = line.split('\t').collect::<Vec<&str>>();
+                    if fields.len() < 4 {
+                        continue;
+                    }
+                    let key = format!("{}\t{}", fields[2], fields[3]);
+                    if fields[1] == "reset" {
+                        done.retain(|k: &String, _| !k.starts_with(&key));
+                    } else if fields[1] == "done" {
+                        started.remove(&key);
+                        done.insert(key, fields.get(4).and_then(|rows| rows.parse::<u32>().ok()));
+                    } else {
+                        started.insert(key);
+                    }
+                }
+            }
+        }
+
+        let f = OpenOptions::new().create(true).write(true).append(resume).truncate(!resume)
+            .open(file_name)
+            .map_err(|err| format!("can not open journal: {} with error: {}", file_name, err))?;
+
+        Ok( Journal { file: Arc::new(Mutex::new(Some(f))), done: Arc::new(done), started: Arc::new(started) } )
+    }
+
+    /// journal which records nothing, for scripts
+    pub fn none() -> Journal {
+        Journal { file: Arc::new(Mutex::new(None)), done: Arc::new(HashMap::new()), started: Arc::new(HashSet::new()) }
+    }
+
+    /// step was finished by previous run
+    pub fn is_done(&self, step: Step, object: &str) -> bool {
+        self.done.contains_key(&key(step, object))
+    }
+
+    /// rows of table loaded by previous run
+    pub fn rows(&self, step: Step, object: &str) -> Option<u32> {
+        self.done.get(&key(step, object)).and_then(|rows| *rows)
+    }
+
+    /// step was started by previous run but did not finish
+    pub fn is_interrupted(&self, step: Step, object: &str) -> bool {
+        self.started.contains(&key(step, object))
+    }
+
+    /// finished steps of previous run whose object starts with prefix
+    pub fn count_done(&self, step: Step, prefix: &str) -> usize {
+        let prefix = key(step, prefix);
+        self.done.keys().filter(|k| k.starts_with(&prefix)).count()
+    }
+
+    /// steps whose object starts with prefix must be done again by next run
+    pub fn reset(&self, step: Step, prefix: &str) -> Result<(), String> {
+        self.write(format!("reset\t{}", key(step, prefix)))
+    }
+
+    pub fn started(&self, step: Step, object: &str) -> Result<(), String> {
+        self.write(format!("started\t{}", key(step, object)))
+    }
+
+    pub fn done(&self, step: Step, object: &str) -> Result<(), String> {
+        self.write(format!("done\t{}", key(step, object)))
+    }
+
+    pub fn done_rows(&self, step: Step, object: &str, rows: u32) -> Result<(), String> {
+        self.write(format!("done\t{}\t{}", key(step, object), rows))
+    }
+
+    /// step not recorded would be repeated or, worse, skipped by resume, so errors are returned
+    fn write(&self, text: String) -> Result<(), String> {
+        let mut guard = self.file.lock().unwrap();
+        if let Some(ref mut f) = *guard {
+            let now: DateTime<Local> = Local::now();
+            let line = format!("{}\t{}\n", now.format("%Y-%m-%d %H:%M:%S"), text);
+            f.write_all(line.as_bytes())
+                .map_err(|err| format!("can not write journal with error: {}", err))?;
+            // journal must survive a crash of migration
+            f.sync_data()
+                .map_err(|err| format!("can not sync journal with error: {}", err))?;
+        }
+        Ok(())
+    }
+}
+
+fn key(step: Step, object: &str) -> String {
+    format!("{}\t{}", step.name(), object)
+}
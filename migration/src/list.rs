@@ -0,0 +1,62 @@
+use std::result::Result;
+
+use oracle::*;
+use config;
+use logger::*;
+use rules::Rules;
+
+struct ObjectStruct (String, String);
+impl MetaQuery for ObjectStruct {
+    fn create(values: &ResultSet) -> ObjectStruct {
+        let s0 = &(values[0]);
+        let s1 = &(values[1]);
+        ObjectStruct(s0.into(), s1.into())
+    }
+
+    fn meta() -> Vec<MetaType> {
+        vec![ string_meta(30), string_meta(128) ]
+    }
+}
+
+/// prints objects of schemas which are selected by rules, with number of those left out
+pub fn list(source: &Connection, schemas: &[&config::Schema], logger: &Logger) -> Result<(), String> {
+    for schema in schemas {
+        let rules = Rules::of(schema)?;
+
+        let sql = format!("select object_type, object_name from sys.all_objects
+                           where owner = '{}' and object_name not like 'BIN$%'
+                           and object_type in ('TABLE', 'VIEW', 'SEQUENCE', 'TRIGGER', 'SYNONYM', 'MATERIALIZED VIEW',
+                                               'PACKAGE', 'PACKAGE BODY', 'PROCEDURE', 'FUNCTION', 'TYPE')
+                           order by object_type, object_name", schema.name);
+
+        let mut query = source.query(sql)
+            .prepare::<ObjectStruct>()
+            .map_err(|err| format!("can not prepare query for objects info: {}", err))?;
+
+        let iterator = query.iterator()
+            .map_err(|err| format!("can not execute query for objects info: {}", err))?;
+
+        logger.newline();
+        logger.println(format!("schema [ {} ]", schema.name));
+
+        let mut selected = 0;
+        let mut excluded = 0;
+
+        for ti in iterator {
+            let object_type = ti.0;
+            let name = ti.1;
+
+            if rules.selects(&name) {
+                let ot: &str = &object_type;
+                logger.println(format!("  {} {}", rpad(ot, 20), name));
+                selected += 1;
+            } else {
+                excluded += 1;
+            }
+        }
+
+        logger.println(format!("  {} objects selected, {} excluded", selected, excluded));
+    }
+
+    Ok(())
+}
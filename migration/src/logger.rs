@@ -9,6 +9,7 @@ use std::thread;
 use std::io::prelude::*;
 use std::fs::File;
 
+#[derive(Clone)]
 pub struct Logger {    
     channel: Sender<LogCommand>
 }
@@ -1,22 +1,181 @@
+extern crate chrono;
+extern crate itertools;
+extern crate libc;
+extern crate regex;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+#[macro_use]
 mod oracle;
 
-const ORA_DB: &'static str = "oracle-linux.apa-canal.md:1521/ACC";
-const USER: &'static str = "CLIENT";
-const PASSWD: &'static str = "CLIENT";
+mod cli;
+mod config;
+mod logger;
+mod schemas;
+mod table;
+mod table_executor;
+mod table_loader;
+mod transform;
+mod tablespaces;
+mod indexes;
+mod sequences;
+mod grants;
+mod accessories;
+mod snapshots;
+mod triggers;
+mod foreign_keys;
+mod ddl;
+mod script;
+mod journal;
+mod executor;
+mod workers;
+mod verify;
+mod sync;
+mod rules;
+mod list;
+
+use std::process;
+
+use cli::{ Args, Command, Parsed };
+use logger::*;
+use indexes::IndexesService;
+use ddl::Ddl;
+use script::Script;
+use journal::Journal;
+use sync::Watermarks;
 
 fn main() {
-    println!("Hello, world!");
+    let args = match cli::parse_args() {
+        Ok(Parsed::Run(args)) => args,
+        Ok(Parsed::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(err) = run(args) {
+        eprintln!("migration failed: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let conf = config::Config::load_config(&args.config)?;
+    let mut schemas = config::Config::load_content(&args.schemas)?;
+    for schema in schemas.iter_mut() {
+        rules::inherit(schema, &conf);
+        tablespaces::inherit(schema, &conf);
+        // wrong pattern is reported before anything is done
+        rules::Rules::of(schema)?;
+    }
+
+    let (logger, logger_handle) = Logger::new(&args.log);
+    let (err_logger, err_logger_handle) = ErrLogger::new(&args.error_log);
+
+    let result = match args.script {
+        Some(ref dir) => write_script(args.command, dir, conf, schemas, &logger, &err_logger),
+        // verification, sync and list keep journal of migration as it is
+        None if args.command == Command::Verify || args.command == Command::Sync || args.command == Command::List => execute(&args, conf, schemas, Journal::none(), &logger, &err_logger),
+        None => Journal::open(&args.journal, args.resume)
+            .and_then(|journal| execute(&args, conf, schemas, journal, &logger, &err_logger))
+    };
+
+    if let Err(ref err) = result {
+        err_logger.error(err.clone());
+    }
+
+    logger.quit();
+    err_logger.quit();
+    logger_handle.join().map_err(|_| "logger thread panicked".to_string())?;
+    err_logger_handle.join().map_err(|_| "error logger thread panicked".to_string())?;
+
+    result
+}
+
+fn execute(args: &Args, conf: config::Config, schemas: Vec<config::Schema>, journal: Journal,
+           logger: &Logger, err_logger: &ErrLogger) -> Result<(), String> {
+    let source_addr = conf.source.clone();
+    let destination_addr = conf.destination.clone();
+    // buffer size in config is in KB
+    let buffer_size = conf.buffer_size * 1024;
+    let luna_calc = conf.luna_calc;
+    let workers = conf.workers.unwrap_or(1);
+    let chunk_above_mb = conf.chunk_above_mb;
+    let chunks = conf.chunks;
+
+    let command = args.command;
+
+    let (source, destination) = executor::connect(conf)?;
+    let mut executor = executor::Executor::new(&source, Ddl::Execute(&destination), schemas, luna_calc, journal.clone())?;
+
+    match command {
+        Command::DropSchemas => executor.drop_schemas(logger),
+        Command::Compile => executor.compile(logger),
+        Command::Accessories => executor.accessories(logger, err_logger),
+        Command::Verify => executor.verify(args.checksums, logger, err_logger),
+        Command::List => executor.list(logger),
+        Command::Sync => {
+            let mut watermarks = Watermarks::open(&args.watermarks, true)?;
+            executor.sync(&mut watermarks, &table_loader::LoadBuffer::new(buffer_size), logger, err_logger)
+        }
+        Command::Tables | Command::All => {
+            let loader = if workers > 1 {
+                let pool = workers::Pool::new(workers, source_addr, destination_addr.clone(), buffer_size, luna_calc,
+                                              chunk_above_mb, chunks);
+                executor::Loader::Workers(pool)
+            } else {
+                executor::Loader::Single(table_loader::LoadBuffer::new(buffer_size))
+            };
+            let mut watermarks = Watermarks::open(&args.watermarks, args.resume)?;
+            let (tas, tas_handle) = IndexesService::new(destination_addr, err_logger.clone(), journal);
+
+            let result = executor.load(loader, &mut watermarks, logger, err_logger, &tas);
+
+            // indexes are created in background, wait for them before accessories reference them
+            tas.quit();
+            tas_handle.join().map_err(|_| "indexes thread panicked".to_string())?;
+
+            result?;
+
+            if command == Command::All {
+                executor.accessories(logger, err_logger)?;
+            }
+            if args.verify {
+                executor.verify(args.checksums, logger, err_logger)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn write_script(command: Command, dir: &str, conf: config::Config, schemas: Vec<config::Schema>,
+                logger: &Logger, err_logger: &ErrLogger) -> Result<(), String> {
+    let script = Script::new(dir)?;
+    let luna_calc = conf.luna_calc;
 
-    let mut env = oracle::Environment::new().unwrap();
-    println!("Environment created");
+    let source = executor::connect_source(conf.source)?;
+    let mut executor = executor::Executor::new(&source, Ddl::Script(&script), schemas, luna_calc, Journal::none())?;
 
-    let mut conn1 = env.connect(ORA_DB, USER, PASSWD).unwrap();
-    println!("Connected 1");
-    conn1.commit().unwrap();
+    match command {
+        Command::DropSchemas => executor.drop_schemas(logger)?,
+        Command::Compile => executor.compile(logger)?,
+        Command::Accessories => executor.accessories(logger, err_logger)?,
+        Command::Verify => return Err("script can not be verified".to_string()),
+        Command::Sync => return Err("sync can not be written to script".to_string()),
+        Command::List => return Err("list can not be written to script".to_string()),
+        Command::Tables => executor.script(logger)?,
+        Command::All => {
+            executor.script(logger)?;
+            executor.accessories(logger, err_logger)?;
+        }
+    }
 
-    let mut _stmt = conn1.prepare("SELECT * FROM ANEXA_CONTRACTE");
-    
-    let mut conn2 = env.connect(ORA_DB, USER, PASSWD).unwrap();
-    println!("Connected 2");
-    conn2.rollback().unwrap();
+    script.finish()?;
+    logger.println(format!("script written to {}", dir));
+    Ok(())
 }
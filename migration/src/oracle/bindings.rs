@@ -32,8 +32,8 @@ macro_rules! bindmap {
 
 #[macro_export]
 macro_rules! bind {
-    ( $value:expr ) => ( Binding::from($value); );
-    ( $value:expr; $size:expr ) => ( Binding::new($value,$size); )
+    ( $value:expr ) => ( Binding::from($value) );
+    ( $value:expr; $size:expr ) => ( Binding::new($value,$size) )
 }
 
 pub type Bindmap<'a> = HashMap<&'a str, Rc<RefCell<RowBinding>>>;
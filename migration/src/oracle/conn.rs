@@ -110,6 +110,10 @@ impl Connection {
         oci::oci_trans_commit(self.service_handle, self.env.error_handle, mode)
     }
 
+    pub fn rollback(&self) -> Result<(), oci::OracleError> {
+        oci::oci_trans_rollback(self.service_handle, self.env.error_handle)
+    }
+
     pub fn prepare_statement<S: Into<String>>(&self, stmt_text: S, bindmap: Option<bindings::Bindmap>) -> Result<stmt::Statement, oci::OracleError> {
         stmt::Statement::new(self, stmt_text.into(), bindmap)
     }
@@ -85,6 +85,21 @@ impl <'a> LobDescriptor <'a> {
         }
     }
 
+    /// appends bytes of buffer to lob written from the start as one piece or as stream of pieces
+    pub fn write_bytes(&mut self, piece: oci::OCILobPiece, buffer: &[u8]) -> Result<(), oci::OracleError> {
+        match oci::oci_lob_write2(self.conn.service_handle,
+                                  self.conn.env.error_handle,
+                                  *self.locator,
+                                  1,
+                                  buffer.as_ptr(),
+                                  buffer.len() as u64,
+                                  piece) {
+            Ok(_) => Ok(()),
+            Err(ref error) if error.code == 99 && (piece == oci::OCILobPiece::FirstPiece || piece == oci::OCILobPiece::NextPiece) => Ok(()),
+            Err(error) => Err(error)
+        }
+    }
+
     pub fn trim(&mut self, total_lob_len: u32) -> Result<(), oci::OracleError> {
         oci::oci_lob_trim(self.conn.service_handle, self.conn.env.error_handle, *self.locator, total_lob_len)
     }
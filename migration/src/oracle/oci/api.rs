@@ -264,6 +264,18 @@ pub fn oci_trans_commit(service_handle: *mut OCISvcCtx, error_handle: *mut OCIEr
     }        
 }
 
+pub fn oci_trans_rollback(service_handle: *mut OCISvcCtx, error_handle: *mut OCIError) -> Result<(), OracleError> {
+    let res = unsafe {
+        OCITransRollback(service_handle, // svchp
+                         error_handle, // errhp
+                         OCIMode::Default as c_uint /* mode */)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_trans_rollback") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }        
+}
+
 pub fn oci_stmt_define_by_pos(stmt_handle: *mut OCIStmt,
                               error_handle: *mut OCIError,
                               position: usize,
@@ -272,7 +284,7 @@ pub fn oci_stmt_define_by_pos(stmt_handle: *mut OCIStmt,
                               size: isize,
                               rlenp: *const c_ushort,
                               data_type: OCIDataType)
-                              -> Result<(), OracleError> {
+                              -> Result<*mut OCIDefine, OracleError> {
 
     let mut handle = ptr::null_mut();
 
@@ -290,6 +302,87 @@ pub fn oci_stmt_define_by_pos(stmt_handle: *mut OCIStmt,
                        OCIMode::Default as c_uint /* mode */)
     };
     match check_error(res, Some(error_handle), "ffi::oci_stmt_define") {
+        None => Ok(handle),
+        Some(err) => Err(err),
+    }
+}
+
+/// defines column fetched piece by piece, as LONG of any length; pieces are supplied
+/// by `oci_stmt_set_piece_info` whenever fetch needs data
+pub fn oci_stmt_define_dynamic(stmt_handle: *mut OCIStmt,
+                               error_handle: *mut OCIError,
+                               position: usize,
+                               indp: *const c_short,
+                               data_type: OCIDataType)
+                               -> Result<*mut OCIDefine, OracleError> {
+
+    let mut handle = ptr::null_mut();
+
+    let res = unsafe {
+        OCIDefineByPos(stmt_handle, // stmtp
+                       &mut handle, // defnpp
+                       error_handle, // errhp
+                       position as c_uint, // position
+                       ptr::null(), // valuep
+                       std::i32::MAX as c_int, // value_sz
+                       data_type as c_ushort, // dty
+                       indp, // indp
+                       ptr::null(), // rlenp
+                       ptr::null(), // rcodep
+                       OCIDefineMode::DynamicFetch as c_uint /* mode */)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_stmt_define_dynamic") {
+        None => Ok(handle),
+        Some(err) => Err(err),
+    }
+}
+
+/// handle of bind or define which needs data and the piece it needs
+pub fn oci_stmt_get_piece_info(stmt_handle: *mut OCIStmt,
+                               error_handle: *mut OCIError)
+                               -> Result<(*mut c_void, u8), OracleError> {
+    let mut handle = ptr::null_mut();
+    let mut htype: c_uint = 0;
+    let mut in_out: u8 = 0;
+    let mut iter: c_uint = 0;
+    let mut idx: c_uint = 0;
+    let mut piece: u8 = 0;
+
+    let res = unsafe {
+        OCIStmtGetPieceInfo(stmt_handle, // stmtp
+                            error_handle, // errhp
+                            &mut handle, // hndlpp
+                            &mut htype, // typep
+                            &mut in_out, // in_outp
+                            &mut iter, // iterp
+                            &mut idx, // idxp
+                            &mut piece /* piecep */)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_stmt_get_piece_info") {
+        None => Ok((handle, piece)),
+        Some(err) => Err(err),
+    }
+}
+
+/// supplies buffer for next piece of define; its length is written to alenp by next fetch
+pub fn oci_stmt_set_piece_info(define_handle: *mut c_void,
+                               error_handle: *mut OCIError,
+                               buffer: *const u8,
+                               alenp: *mut c_uint,
+                               piece: u8,
+                               indp: *const c_short)
+                               -> Result<(), OracleError> {
+    let res = unsafe {
+        OCIStmtSetPieceInfo(define_handle, // hndlp
+                            OCIHandleType::Define as c_uint, // type
+                            error_handle, // errhp
+                            buffer, // bufp
+                            alenp, // alenp
+                            piece, // piece
+                            indp, // indp
+                            ptr::null_mut() /* rcodep */)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_stmt_set_piece_info") {
         None => Ok(()),
         Some(err) => Err(err),
     }
@@ -559,6 +652,40 @@ pub fn oci_lob_write(service_handle: *mut OCISvcCtx,
     }
 }
 
+/// writes buffer measured in bytes, also to CLOB whose length in characters is not known;
+/// pieces of stream are written with zero amount and may split characters
+pub fn oci_lob_write2(service_handle: *mut OCISvcCtx,
+                      error_handle: *mut OCIError,
+                      locator: *mut OCILobLocator,
+                      offset: u64,
+                      buffer: *const u8, // buffer
+                      buffer_len: u64,   // buffer len
+                      piece: OCILobPiece
+                      ) -> Result<(), OracleError> {
+    let mut byte_amt: u64 = if piece == OCILobPiece::OnePiece { buffer_len } else { 0 };
+    let mut char_amt: u64 = 0;
+
+    let res = unsafe {
+        OCILobWrite2(service_handle, // svchp
+                     error_handle, // errhp
+                     locator, // locp
+                     &mut byte_amt, // byte_amtp
+                     &mut char_amt, // char_amtp
+                     offset, // offset
+                     buffer, // bufp
+                     buffer_len, // buflen
+                     piece as u8, // piece
+                     ptr::null(), // ctxp
+                     ptr::null(), // cbfp
+                     0, // csid
+                     0, /* csfrm */)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_lob_write2") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
 pub fn oci_lob_trim(service_handle: *mut OCISvcCtx,
                    error_handle: *mut OCIError,
                    locator: *mut OCILobLocator,
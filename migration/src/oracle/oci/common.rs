@@ -201,6 +201,9 @@ pub enum OCIAttribute {
     /// `OCI_ATTR_CHARSET_ID`
     CharsetId = 31,
 
+    /// `OCI_ATTR_CHARSET_FORM`
+    CharsetForm = 32,
+
 
     /// `OCI_ATTR_ROWS_FETCHED`
     /// Mode: READ
@@ -324,6 +327,12 @@ pub enum OCIDataType {
     /// `SQLT_FLT`: (ORANET TYPE) Floating point number
     Float = 4,
 
+    /// `SQLT_BFLOAT`: native binary float
+    BinaryFloat = 21,
+
+    /// `SQLT_BDOUBLE`: native binary double
+    BinaryDouble = 22,
+
     /// `SQLT_PDN`: (ORANET TYPE) Packed Decimal Numeric
     PackedDecimalNumber = 7,
 
@@ -333,6 +342,9 @@ pub enum OCIDataType {
     /// `SQLT_BIN`: binary data (DTYBIN)
     Binary = 23,
 
+    /// `SQLT_LBI`: long binary
+    LongBinary = 24,
+
     /// `SQLT_NUM`: (ORANET TYPE) oracle numeric
     Numeric = 2,
 
@@ -349,6 +361,16 @@ pub enum OCIDataType {
     NumericWithLength = 6,
 }
 
+/// Mode of define
+#[allow(dead_code)]
+pub enum OCIDefineMode {
+    /// `OCI_DEFAULT`
+    Default = 0x00000000,
+
+    /// `OCI_DYNAMIC_FETCH`: value is supplied piece by piece while fetching
+    DynamicFetch = 0x00000002,
+}
+
 /// Orientation
 #[allow(dead_code)]
 pub enum OCIOrientation {
@@ -411,5 +433,17 @@ pub enum OCILobPiece {
 pub enum OCICharset {
     Default = 0,
     WE8ISO8859P1 = 31,
-    EE8ISO8859P2 = 32
+    EE8ISO8859P2 = 32,
+    AL32UTF8 = 873
+}
+
+/// Character set form of bind or define, national for NCHAR columns
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OCICharsetForm {
+    /// `SQLCS_IMPLICIT`
+    Implicit = 1,
+
+    /// `SQLCS_NCHAR`
+    Nchar = 2
 }
\ No newline at end of file
@@ -19,6 +19,13 @@ pub struct OracleError {
     location: String,
 }
 
+impl OracleError {
+    /// Error which did not come from Oracle, e.g. writing of script; its code is no Oracle code
+    pub fn new(message: String, location: &str) -> OracleError {
+        OracleError {code: -1, message, location: location.to_string()}
+    }
+}
+
 impl fmt::Display for OracleError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!{f, "\n\n  Error code: {}\n  Error message: {}\n  Where: {}\n\n",
@@ -101,6 +101,8 @@ extern "C" {
 
     pub fn OCITransCommit(svchp: *mut OCISvcCtx, errhp: *mut OCIError, flags: c_uint) -> c_int;                          
 
+    pub fn OCITransRollback(svchp: *mut OCISvcCtx, errhp: *mut OCIError, flags: c_uint) -> c_int;
+
     pub fn OCIParamGet(hndlp: *const c_void,
                        htype: c_uint,
                        errhp: *mut OCIError,
@@ -152,6 +154,26 @@ extern "C" {
                         mode: c_uint)
                         -> c_int;
 
+    pub fn OCIStmtGetPieceInfo(stmtp: *const OCIStmt,
+                               errhp: *mut OCIError,
+                               hndlpp: *mut *mut c_void,
+                               typep: *mut c_uint,
+                               in_outp: *mut u8,
+                               iterp: *mut c_uint,
+                               idxp: *mut c_uint,
+                               piecep: *mut u8)
+                               -> c_int;
+
+    pub fn OCIStmtSetPieceInfo(hndlp: *mut c_void,
+                               _type: c_uint,
+                               errhp: *mut OCIError,
+                               bufp: *const u8,
+                               alenp: *mut c_uint,
+                               piece: u8,
+                               indp: *const c_short,
+                               rcodep: *mut c_ushort)
+                               -> c_int;
+
     pub fn OCIStmtFetch2(stmtp: *mut OCIStmt,
                          errhp: *mut OCIError,
                          nrows: c_uint,
@@ -229,6 +251,21 @@ extern "C" {
                        csfrm: u8
                        ) -> c_int;                     
 
+    pub fn OCILobWrite2(svchp: *mut OCISvcCtx,
+                        errhp: *mut OCIError,
+                        locp: *mut OCILobLocator,
+                        byte_amtp: *mut u64,
+                        char_amtp: *mut u64,
+                        offset: u64,
+                        bufp: *const u8,
+                        buflen: u64,
+                        piece: u8,
+                        ctxp: *const c_void,
+                        cbfp: *const c_void,
+                        csid: c_ushort,
+                        csfrm: u8
+                        ) -> c_int;
+
     pub fn OCILobWrite(svchp: *mut OCISvcCtx,
                        errhp: *mut OCIError,
                        locp: *mut OCILobLocator,
@@ -18,9 +18,9 @@ pub fn timestamp_meta() -> MetaType {
 }
 */
 
-type SqlDate = naive::date::NaiveDate;
-type SqlDateTime = naive::datetime::NaiveDateTime;
-type SqlTImestamp = naive::time::NaiveTime;
+type SqlDate = NaiveDate;
+type SqlDateTime = NaiveDateTime;
+type SqlTImestamp = NaiveTime;
 
 impl<'a> From<&'a ResultItem> for SqlDate {
     fn from(result: &'a ResultItem) -> SqlDate {
@@ -28,7 +28,7 @@ impl<'a> From<&'a ResultItem> for SqlDate {
         let y = (vec[0] as i32 - 100)*100 + vec[1] as i32 - 100;
         let m = vec[2] as u32;
         let d = vec[3] as u32;
-        naive::date::NaiveDate::from_ymd(y, m, d)        
+        NaiveDate::from_ymd(y, m, d)        
     }
 } 
 
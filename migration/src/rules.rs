@@ -0,0 +1,198 @@
+use std::collections::{ HashMap, HashSet };
+
+use regex::Regex;
+
+use config;
+
+/// Which objects of schema are migrated. Patterns are globs (`TMP_*`, `*_OLD`, `BAK_??`)
+/// matched regardless of case, or regular expressions when prefixed by `re:`.
+/// Object is selected when it matches some include pattern, or there are none,
+/// and it matches no exclude pattern nor is listed in exclusions.
+pub struct Rules {
+    include:    Vec<Regex>,
+    exclude:    Vec<Regex>,
+    exclusions: HashSet<String>
+}
+
+impl Rules {
+    pub fn of(schema: &config::Schema) -> Result<Rules, String> {
+        let include = compile_all(&(schema.name), &schema.include)?;
+        let exclude = compile_all(&(schema.name), &schema.exclude)?;
+        let exclusions = match schema.exclusions {
+            None => HashSet::new(),
+            Some(ref exclusions) => exclusions.iter().cloned().collect()
+        };
+
+        Ok( Rules { include, exclude, exclusions } )
+    }
+
+    pub fn selects(&self, name: &str) -> bool {
+        if self.exclusions.contains(name) {
+            return false;
+        }
+        (self.include.is_empty() || self.include.iter().any(|re| re.is_match(name)))
+            && !self.exclude.iter().any(|re| re.is_match(name))
+    }
+}
+
+/// Rules of several schemas, for objects loaded for all schemas at once
+pub struct Selection {
+    rules: HashMap<String, Rules>
+}
+
+impl Selection {
+    pub fn new(schemas: &[&config::Schema]) -> Result<Selection, String> {
+        let mut rules = HashMap::new();
+        for schema in schemas {
+            rules.insert(schema.name.clone(), Rules::of(schema)?);
+        }
+        Ok( Selection { rules } )
+    }
+
+    /// objects of schemas without rules are selected
+    pub fn selects(&self, owner: &str, name: &str) -> bool {
+        match self.rules.get(owner) {
+            Some(rules) => rules.selects(name),
+            None => true
+        }
+    }
+}
+
+/// global rules apply to every schema: schema includes replace global ones, excludes add up
+pub fn inherit(schema: &mut config::Schema, conf: &config::Config) {
+    if schema.include.is_none() {
+        schema.include = conf.include.clone();
+    }
+    if let Some(ref global) = conf.exclude {
+        let mut exclude = schema.exclude.take().unwrap_or(Vec::new());
+        exclude.extend(global.iter().cloned());
+        schema.exclude = Some(exclude);
+    }
+}
+
+fn compile_all(schema: &str, patterns: &Option<Vec<String>>) -> Result<Vec<Regex>, String> {
+    match *patterns {
+        None => Ok(Vec::new()),
+        Some(ref patterns) => patterns.iter()
+            .map(|p| compile(p).map_err(|err| format!("can not parse pattern: {} of schema: {} with error: {}", p, schema, err)))
+            .collect()
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex, ::regex::Error> {
+    if pattern.starts_with("re:") {
+        return Regex::new(&pattern[3..]);
+    }
+
+    let mut re = "(?i)^".to_string();
+    for c in pattern.chars() {
+        match c {
+            '*' => re += ".*",
+            '?' => re.push('.'),
+            _ => re += &::regex::escape(&c.to_string())
+        }
+    }
+    re.push('$');
+    Regex::new(&re)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn schema(json: &str) -> config::Schema {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn config(json: &str) -> config::Config {
+        let addr = r#"{"uri": "db", "user": "u", "pw": "p"}"#;
+        let json = format!(r#"{{"destination": {}, "source": {}, "buffer_size": 1, "luna_calc": 1{}}}"#, addr, addr, json);
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn compile_escapes_glob() {
+        assert_eq!(compile("BAK_??").unwrap().as_str(), "(?i)^BAK_..$");
+        assert_eq!(compile("A.B*").unwrap().as_str(), "(?i)^A\\.B.*$");
+        assert_eq!(compile("re:^A.B").unwrap().as_str(), "^A.B");
+    }
+
+    #[test]
+    fn globs() {
+        let rules = Rules::of(&schema(r#"{"name": "S", "exclude": ["TMP_*", "*_OLD", "BAK_??"]}"#)).unwrap();
+        assert!(!rules.selects("TMP_ORDERS"));
+        assert!(!rules.selects("ORDERS_OLD"));
+        assert!(!rules.selects("BAK_01"));
+        assert!(rules.selects("BAK_001"));
+        assert!(rules.selects("ORDERS"));
+        assert!(rules.selects("MY_TMP_ORDERS"));
+        // only `*` and `?` are wildcards
+        assert!(rules.selects("ORDERSXOLD"));
+    }
+
+    #[test]
+    fn globs_ignore_case() {
+        let rules = Rules::of(&schema(r#"{"name": "S", "include": ["orders*"]}"#)).unwrap();
+        assert!(rules.selects("ORDERS"));
+        assert!(rules.selects("Orders_2020"));
+        assert!(!rules.selects("CUSTOMERS"));
+    }
+
+    #[test]
+    fn regex() {
+        let rules = Rules::of(&schema(r#"{"name": "S", "include": ["re:^T_[0-9]+$"]}"#)).unwrap();
+        assert!(rules.selects("T_2020"));
+        assert!(!rules.selects("T_X"));
+        assert!(!rules.selects("t_2020"));
+
+        let rules = Rules::of(&schema(r#"{"name": "S", "exclude": ["re:(?i)_log$"]}"#)).unwrap();
+        assert!(!rules.selects("AUDIT_LOG"));
+        assert!(rules.selects("LOGS"));
+    }
+
+    #[test]
+    fn invalid_regex() {
+        let err = Rules::of(&schema(r#"{"name": "S", "include": ["re:T_("]}"#)).err().unwrap();
+        assert!(err.starts_with("can not parse pattern: re:T_( of schema: S with error:"));
+    }
+
+    #[test]
+    fn exclusions_and_excludes_win_over_includes() {
+        let rules = Rules::of(&schema(r#"{"name": "S", "include": ["ORD*"], "exclude": ["*_OLD"], "exclusions": ["ORDER_ITEMS"]}"#)).unwrap();
+        assert!(rules.selects("ORDERS"));
+        assert!(!rules.selects("ORDERS_OLD"));
+        assert!(!rules.selects("ORDER_ITEMS"));
+        // exclusions are exact names
+        assert!(rules.selects("ORDER_ITEMS_2"));
+    }
+
+    #[test]
+    fn inherit_replaces_includes_and_adds_excludes() {
+        let conf = config(r#", "include": ["A*"], "exclude": ["TMP_*"]"#);
+
+        let mut own = schema(r#"{"name": "S", "include": ["B*"], "exclude": ["*_OLD"]}"#);
+        inherit(&mut own, &conf);
+        assert_eq!(own.include, Some(vec!["B*".to_string()]));
+        assert_eq!(own.exclude, Some(vec!["*_OLD".to_string(), "TMP_*".to_string()]));
+        let rules = Rules::of(&own).unwrap();
+        assert!(rules.selects("BOOKS"));
+        assert!(!rules.selects("AUTHORS"));
+        assert!(!rules.selects("BOOKS_OLD"));
+
+        let mut plain = schema(r#"{"name": "S"}"#);
+        inherit(&mut plain, &conf);
+        let rules = Rules::of(&plain).unwrap();
+        assert!(rules.selects("AUTHORS"));
+        assert!(!rules.selects("BOOKS"));
+        assert!(!rules.selects("TMP_AUTHORS"));
+    }
+
+    #[test]
+    fn selection_of_schemas_without_rules() {
+        let s = schema(r#"{"name": "S", "exclude": ["TMP_*"]}"#);
+        let selection = Selection::new(&[&s]).unwrap();
+        assert!(!selection.selects("S", "TMP_X"));
+        assert!(selection.selects("OTHER", "TMP_X"));
+    }
+}
@@ -1,10 +1,11 @@
-use oracle;
 use config;
+use tablespaces::Remap;
+use ddl::{ Ddl, Section };
 
-pub fn drop(destination: &oracle::Connection, schema: &str) -> Result<(), String> {
+pub fn drop(destination: &Ddl, schema: &str) -> Result<(), String> {
     let sql = format!("DROP USER {} CASCADE", schema);
 
-    if let Err(err) = destination.execute(sql) {
+    if let Err(err) = destination.execute(Section::Drop, schema, sql) {
         if err.code == 1918 {
             println!("  user does not exists");
             Ok(())
@@ -18,13 +19,15 @@ pub fn drop(destination: &oracle::Connection, schema: &str) -> Result<(), String
     }        
 }
 
-pub fn create(destination: &oracle::Connection, schema: &config::Schema) -> Result<(), String> {
+pub fn create(destination: &Ddl, schema: &config::Schema) -> Result<(), String> {
     let schema_name = &(schema.name);
     let pw = if let Some(ref pw) = schema.pw { pw } else { schema_name };
 
-    let sql = format!("CREATE USER {} IDENTIFIED BY {} DEFAULT TABLESPACE \"DATA\" TEMPORARY TABLESPACE \"TEMP\"", schema_name, pw);
+    let remap = Remap::of(schema);
+    let sql = format!("CREATE USER {} IDENTIFIED BY {} DEFAULT TABLESPACE \"{}\" TEMPORARY TABLESPACE \"{}\"",
+                      schema_name, pw, remap.user(), remap.temporary());
 
-    destination.execute(sql)
+    destination.execute(Section::Users, schema_name, sql)
         .map_err(|err| format!("can not create user: {} with error: {}", schema_name, err))?;
 
     let grants = vec![
@@ -35,14 +38,14 @@ pub fn create(destination: &oracle::Connection, schema: &config::Schema) -> Resu
     ];
     for grant in grants {
         let sql = format!("GRANT {} TO {}", grant, schema_name);
-        destination.execute(sql)
+        destination.execute(Section::Users, schema_name, sql)
             .map_err(|err| format!("can not grant {} to user: {} with error: {}", grant, schema_name, err))?;        
     }
     Ok(())
 }
 
-pub fn compile(destination: &oracle::Connection, schema: &str) -> Result<(), String> {
+pub fn compile(destination: &Ddl, schema: &str) -> Result<(), String> {
     let sql = format!("BEGIN DBMS_UTILITY.COMPILE_SCHEMA('{}'); END;", schema);    
-    destination.execute(sql)
+    destination.execute(Section::Compile, schema, sql)
         .map_err(|err| format!("can not compile schema: {} with error: {}", schema, err))
 }
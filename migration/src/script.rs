@@ -0,0 +1,80 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::PathBuf;
+
+use chrono::*;
+
+use ddl::Section;
+
+/// Migration written as SQL*Plus scripts instead of being executed:
+/// one directory per schema with a file per section, and `install.sql`
+/// which runs all sections of all schemas in order
+pub struct Script {
+    dir:     PathBuf,
+    files:   RefCell<HashMap<(String, Section), File>>,
+    // schemas in order of first statement
+    schemas: RefCell<Vec<String>>
+}
+
+impl Script {
+    pub fn new(dir: &str) -> Result<Script, String> {
+        fs::create_dir_all(dir)
+            .map_err(|err| format!("can not create script directory: {} with error: {}", dir, err))?;
+
+        Ok( Script { dir: PathBuf::from(dir), files: RefCell::new(HashMap::new()), schemas: RefCell::new(Vec::new()) } )
+    }
+
+    pub fn write(&self, section: Section, schema: &str, sql: &str) -> Result<(), String> {
+        let mut files = self.files.borrow_mut();
+        let key = (schema.to_string(), section);
+        let file_name = self.dir.join(schema).join(section.file_name());
+
+        if !files.contains_key(&key) {
+            let dir = self.dir.join(schema);
+            fs::create_dir_all(&dir)
+                .map_err(|err| format!("can not create script directory: {:?} with error: {}", dir, err))?;
+
+            let mut f = File::create(&file_name)
+                .map_err(|err| format!("can not create script: {:?} with error: {}", file_name, err))?;
+            let text = format!("-- {} {:?} of schema {}, generated at {:?}\n\n", env!("CARGO_PKG_NAME"), section, schema, Local::now());
+            f.write_all(text.as_bytes())
+                .map_err(|err| format!("can not write script: {:?} with error: {}", file_name, err))?;
+            files.insert(key.clone(), f);
+
+            let mut schemas = self.schemas.borrow_mut();
+            if !schemas.iter().any(|s| s == schema) {
+                schemas.push(schema.to_string());
+            }
+        }
+
+        // every statement is terminated by slash, what works for SQL and PL/SQL alike
+        let f = files.get_mut(&key).unwrap();
+        f.write_all(format!("{}\n/\n\n", sql.trim_right()).as_bytes())
+            .map_err(|err| format!("can not write script: {:?} with error: {}", file_name, err))
+    }
+
+    /// writes `install.sql`, section by section for all schemas
+    pub fn finish(&self) -> Result<(), String> {
+        let files = self.files.borrow();
+        let schemas = self.schemas.borrow();
+
+        let file_name = self.dir.join("install.sql");
+        let mut f = File::create(&file_name)
+            .map_err(|err| format!("can not create script: {:?} with error: {}", file_name, err))?;
+
+        let mut text = "whenever sqlerror continue\nset define off\nset echo on\n".to_string();
+        for section in Section::ALL.iter() {
+            for schema in schemas.iter() {
+                if files.contains_key(&(schema.clone(), *section)) {
+                    text += &format!("@@{}/{}\n", schema, section.file_name());
+                }
+            }
+        }
+
+        f.write_all(text.as_bytes())
+            .map_err(|err| format!("can not write script: {:?} with error: {}", file_name, err))
+    }
+}
@@ -2,6 +2,8 @@ use std::result::Result;
 
 use oracle::*;
 use grants;
+use rules::Rules;
+use ddl::{ Ddl, Section };
 
 pub struct SequencesService<'a> {
     schema_bind: Binding<String>,
@@ -40,12 +42,15 @@ impl <'a> SequencesService<'a> {
         Ok( SequencesService { schema_bind, query } )            
     }
 
-    pub fn load(&mut self, schema: &str, destination: &Connection, grants_service: &mut grants::GrantsService) -> Result<(), String> {
+    pub fn load(&mut self, schema: &str, rules: &Rules, destination: &Ddl, grants_service: &mut grants::GrantsService) -> Result<(), String> {
         self.schema_bind.set(schema);
         let iterator = self.query.iterator().map_err(|err| format!("can not load sequences struct with error: {}", err))?;
 
         for ti in iterator { 
             let name = ti.0;
+            if !rules.selects(&name) {
+                continue;
+            }
             let min_value = ti.1;
             let max_value = ti.2;
             let increment_by = ti.3;
@@ -57,7 +62,7 @@ impl <'a> SequencesService<'a> {
 
             // println!("sequence sql: {}", sql);                    
 
-            if let Err(err) = destination.execute(sql) {
+            if let Err(err) = destination.execute(Section::Sequences, schema, sql) {
                 if err.code == 955 {
                     continue;
                 } else {
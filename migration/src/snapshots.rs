@@ -4,11 +4,14 @@ use itertools::Itertools;
 use oracle::*;
 
 use grants;
+use ddl::{ Ddl, Section };
+use rules::Selection;
 use logger::*;
 
 pub fn load(source: &Connection,
-            destination: &Connection,
+            destination: &Ddl,
             schemas: &Vec<String>,
+            selection: &Selection,
             grants_service: &mut grants::GrantsService,
             err_logger: &ErrLogger) -> Result<(), String> {
 
@@ -27,9 +30,13 @@ pub fn load(source: &Connection,
         let name = ti.1;
         let text = ti.2;
 
+        if !selection.selects(&owner, &name) {
+            continue;
+        }
+
         let sql = format!("create materialized view {}.{} build immediate as {}", &owner, &name, text);
 
-        if let Err(err) = destination.execute(sql.clone()) {
+        if let Err(err) = destination.execute(Section::Code, &owner, sql.clone()) {
             err_logger.error(format!("can not create materialized view: {}.{} with error: {}, sql: {}", &owner, &name, err, sql));
         } else {
             grants_service.grant(destination, &owner, &name)?;        
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufReader;
+
+use oracle::*;
+use config;
+use table;
+use table_executor;
+use table_loader::LoadBuffer;
+
+/// How changed rows of table are found in source
+#[derive(Clone, Debug, PartialEq)]
+pub enum Tracking {
+    /// numeric column, as LUNA_CALC
+    Number(String),
+    /// DATE or TIMESTAMP column, mark is kept to seconds
+    Date(String),
+    /// system change number of block of row; blocks are not tracked row by row,
+    /// so unchanged rows of changed blocks are merged too
+    RowScn
+}
+
+impl Tracking {
+    /// change column configured for table in schema, LUNA_CALC when table has it, ORA_ROWSCN otherwise
+    pub fn of(schema: &config::Schema, table: &table::TableInfo) -> Result<Tracking, String> {
+        let configured = schema.change_columns.as_ref().and_then(|cc| cc.get(&table.name));
+        let name = match configured {
+            Some(name) => name.to_uppercase(),
+            None if table.luna_calc => "LUNA_CALC".to_string(),
+            None => return Ok(Tracking::RowScn)
+        };
+
+        if name == "ORA_ROWSCN" {
+            return Ok(Tracking::RowScn);
+        }
+
+        let column = table.columns.iter().find(|c| c.name == name)
+            .ok_or(format!("change column: {} not found in table: {}.{}", name, schema.name, table.name))?;
+
+        match column.col_type {
+            table::ColumnType::Int32 | table::ColumnType::Int64 | table::ColumnType::Float64 => Ok(Tracking::Number(name)),
+            table::ColumnType::DateTime => Ok(Tracking::Date(name)),
+            // marks of timestamps with time zone would be compared in time zone of session
+            table::ColumnType::Timestamp if !column.col_type_name.ends_with("TIME ZONE") => Ok(Tracking::Date(name)),
+            _ => Err(format!("change column: {} of table: {}.{} is not a number nor a date", name, schema.name, table.name))
+        }
+    }
+
+    pub fn column(&self) -> &str {
+        match *self {
+            Tracking::Number(ref name) | Tracking::Date(ref name) => name,
+            Tracking::RowScn => "ORA_ROWSCN"
+        }
+    }
+
+    /// current high-water mark of table in source, none for empty table
+    pub fn mark(&self, source: &Connection, schema: &str, table: &str) -> Result<Option<String>, String> {
+        let sql = match *self {
+            Tracking::Number(ref name) => format!("select to_char(max(\"{}\")) from {}.{}", name, schema, table),
+            Tracking::Date(ref name) => format!("select to_char(max(\"{}\"), 'YYYY-MM-DD HH24:MI:SS') from {}.{}", name, schema, table),
+            Tracking::RowScn => "select to_char(current_scn) from v$database".to_string()
+        };
+
+        let mark = source.query(sql)
+            .prepare::<String>()
+            .and_then(|mut query| query.fetch())
+            .map_err(|err| format!("can not load high-water mark of table: {}.{} with error: {}", schema, table, err))?;
+
+        Ok(mark.and_then(|m| if m.is_empty() { None } else { Some(m) }))
+    }
+
+    /// rows at mark are taken again, what does no harm as they are merged
+    pub fn filter(&self, mark: &str) -> String {
+        match *self {
+            Tracking::Number(ref name) => format!("\"{}\" >= {}", name, mark),
+            Tracking::Date(ref name) => format!("\"{}\" >= to_date('{}', 'YYYY-MM-DD HH24:MI:SS')", name, mark),
+            Tracking::RowScn => format!("ora_rowscn >= {}", mark)
+        }
+    }
+}
+
+/// High-water marks of tables, one line per table: `SCHEMA.TABLE <tab> column <tab> mark`.
+/// File is rewritten as a whole on save.
+pub struct Watermarks {
+    file_name: String,
+    marks:     HashMap<String, (String, String)>
+}
+
+impl Watermarks {
+    /// with keep marks of previous run are read, otherwise they are forgotten
+    pub fn open(file_name: &str, keep: bool) -> Result<Watermarks, String> {
+        let mut marks = HashMap::new();
+
+        if keep {
+            if let Ok(f) = File::open(file_name) {
+                for line in BufReader::new(f).lines() {
+                    let line = line.map_err(|err| format!("can not read high-water marks: {} with error: {}", file_name, err))?;
+                    let fields = line.split('\t').collect::<Vec<&str>>();
+                    if fields.len() == 3 {
+                        marks.insert(fields[0].to_string(), (fields[1].to_string(), fields[2].to_string()));
+                    }
+                }
+            }
+        }
+
+        Ok( Watermarks { file_name: file_name.to_string(), marks } )
+    }
+
+    /// mark of table, if it was recorded for the same column
+    pub fn get(&self, table: &str, column: &str) -> Option<&str> {
+        match self.marks.get(table) {
+            Some(&(ref c, ref mark)) if c == column => Some(mark),
+            _ => None
+        }
+    }
+
+    pub fn contains(&self, table: &str) -> bool {
+        self.marks.contains_key(table)
+    }
+
+    pub fn set(&mut self, table: &str, column: &str, mark: String) {
+        self.marks.insert(table.to_string(), (column.to_string(), mark));
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let mut text = String::new();
+        let mut tables = self.marks.keys().collect::<Vec<&String>>();
+        tables.sort();
+        for table in tables {
+            let &(ref column, ref mark) = &self.marks[table];
+            text += &format!("{}\t{}\t{}\n", table, column, mark);
+        }
+
+        // written aside and renamed, so a crash does not leave marks half written
+        let tmp_name = format!("{}.tmp", self.file_name);
+        let mut f = File::create(&tmp_name)
+            .map_err(|err| format!("can not create high-water marks: {} with error: {}", tmp_name, err))?;
+        f.write_all(text.as_bytes())
+            .and_then(|_| f.sync_data())
+            .map_err(|err| format!("can not write high-water marks: {} with error: {}", tmp_name, err))?;
+        fs::rename(&tmp_name, &self.file_name)
+            .map_err(|err| format!("can not write high-water marks: {} with error: {}", self.file_name, err))
+    }
+}
+
+/// merges rows of table with primary key changed since its high-water mark and moves the mark;
+/// without recorded mark LUNA_CALC starts at luna_calc of config, other columns can not start
+pub fn sync_table(source: &Connection, destination: &Connection,
+                  schema: &config::Schema, table: &table::TableInfo, luna_calc: u32,
+                  watermarks: &mut Watermarks,
+                  load_buffer: &LoadBuffer, lob_copy_buffer: &[u8]) -> Result<String, String> {
+    let full_name = format!("{}.{}", schema.name, table.name);
+    let tracking = Tracking::of(schema, table)?;
+
+    let last = match watermarks.get(&full_name, tracking.column()) {
+        Some(mark) => mark.to_string(),
+        None => match tracking {
+            Tracking::Number(ref name) if name == "LUNA_CALC" => luna_calc.to_string(),
+            _ => return Err(format!("no high-water mark of table: {} for column: {}, table must be loaded by tables first",
+                                    full_name, tracking.column()))
+        }
+    };
+
+    // mark is taken before rows are read, so rows changed meanwhile are merged by next sync
+    let mark = tracking.mark(source, &(schema.name), &(table.name))?;
+
+    let rows = table_executor::merge_rows(source, destination, schema, table, luna_calc, &tracking.filter(&last),
+                                          load_buffer, lob_copy_buffer)?;
+
+    if let Some(mark) = mark {
+        watermarks.set(&full_name, tracking.column(), mark);
+        watermarks.save()?;
+    }
+
+    Ok(format!(" {} >= {} {} rows merged", tracking.column(), last, rows))
+}
@@ -1,8 +1,11 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::result::Result;
 
 use config;
 use oracle::*;
+use transform;
+use rules::Rules;
+use tablespaces::Remap;
 
 #[derive(Clone, Debug)]
 pub struct TableInfo {
@@ -16,12 +19,31 @@ pub struct TableInfo {
     pub no_iot:      bool,
     pub columns:     Vec<ColumnInfo>,
     pub primary_key: Option<PrimaryKey>,
-    pub indexes:     Vec<Index>
+    pub indexes:     Vec<Index>,
+    /// condition of rows to copy, from table config
+    pub filter:      Option<String>,
+    /// tablespace in destination, none for temporary table
+    pub tablespace:  Option<String>,
+    /// logging and compression of schema
+    pub logging:     Option<bool>,
+    pub compress:    Option<String>
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ColumnType {
-    Int32, Int64, Float64, Varchar, DateTime, Blob, Clob, Long, Unsupported
+    Int32, Int64, Float64, Varchar, NVarchar, DateTime, Timestamp, Interval, Raw, Rowid, Blob, Clob, Xml, Long, LongRaw, Unsupported
+}
+
+impl ColumnType {
+    /// copied by LOB locators, XMLTYPE as CLOB
+    pub fn is_lob(&self) -> bool {
+        *self == ColumnType::Blob || *self == ColumnType::Clob || *self == ColumnType::Xml
+    }
+
+    /// fetched piece by piece into LOB, LONG as CLOB and LONG RAW as BLOB
+    pub fn is_long(&self) -> bool {
+        *self == ColumnType::Long || *self == ColumnType::LongRaw
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -30,24 +52,66 @@ pub struct ColumnInfo {
     pub col_type:       ColumnType,
     pub col_type_name:  String,
     pub oci_data_type:  OCIDataType,    
+    /// in characters for NCHAR and NVARCHAR2
     pub col_len:        u32,
     pub nullable:       bool,
     pub data_precision: u32,
     pub data_scale:     u32,
-    pub buffer_len:     u32
+    pub buffer_len:     u32,
+    /// read instead of column when value is masked
+    pub expression:     Option<String>,
+    /// tablespace of LOB in destination, when it is not that of table
+    pub lob_tablespace: Option<String>
+}
+
+/// Timestamps are copied as text with nine fractional digits and time zone region,
+/// intervals as text too, so neither precision nor time zone is lost on the way
+const TIMESTAMP_FORMAT:    &'static str = "SYYYY-MM-DD HH24:MI:SS.FF9";
+const TIMESTAMP_TZ_FORMAT: &'static str = "SYYYY-MM-DD HH24:MI:SS.FF9 TZR";
+
+impl ColumnInfo {
+    /// read from source for column
+    pub fn select(&self) -> String {
+        let name = format!("\"{}\"", self.name);
+        match self.col_type {
+            ColumnType::Timestamp if self.col_type_name.ends_with("LOCAL TIME ZONE") =>
+                format!("to_char(cast({} as timestamp with time zone), '{}')", name, TIMESTAMP_TZ_FORMAT),
+            ColumnType::Timestamp if self.col_type_name.ends_with("TIME ZONE") =>
+                format!("to_char({}, '{}')", name, TIMESTAMP_TZ_FORMAT),
+            ColumnType::Timestamp => format!("to_char({}, '{}')", name, TIMESTAMP_FORMAT),
+            ColumnType::Interval => format!("to_char({})", name),
+            ColumnType::Xml => format!("xmlserialize(content {} as clob)", name),
+            _ => name
+        }
+    }
+
+    /// written to destination for placeholder of column
+    pub fn value(&self, placeholder: &str) -> String {
+        match self.col_type {
+            ColumnType::Timestamp if self.col_type_name.ends_with("TIME ZONE") =>
+                format!("to_timestamp_tz({}, '{}')", placeholder, TIMESTAMP_TZ_FORMAT),
+            ColumnType::Timestamp => format!("to_timestamp({}, '{}')", placeholder, TIMESTAMP_FORMAT),
+            ColumnType::Interval if self.col_type_name.starts_with("INTERVAL YEAR") => format!("to_yminterval({})", placeholder),
+            ColumnType::Interval => format!("to_dsinterval({})", placeholder),
+            ColumnType::Xml => format!("xmlparse(content {})", placeholder),
+            _ => placeholder.to_string()
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct PrimaryKey {
-    pub name:    String,
-    pub columns: Vec<String>
+    pub name:       String,
+    pub columns:    Vec<String>,
+    pub tablespace: String
 }
 
 #[derive(Clone, Debug)]
 pub struct Index {
-    pub name:    String,
-    pub unique:  bool,
-    pub columns: Vec<IndexColumn>
+    pub name:       String,
+    pub unique:     bool,
+    pub columns:    Vec<IndexColumn>,
+    pub tablespace: String
 }
 
 #[derive(Clone, Debug)]
@@ -64,28 +128,29 @@ pub struct TableInfoService<'a> {
 
     tables_query:  TypedQuery<'a, TableStruct>,
     columns_query: TypedQuery<'a, ColumnStruct>,
-    pk_query:      TypedQuery<'a, String>,
+    pk_query:      TypedQuery<'a, SegmentNameStruct>,
     pk_col_query:  TypedQuery<'a, String>,
-    idx_query:     TypedQuery<'a, IndexStruct>,
+    idx_query:     TypedQuery<'a, IndexInfoStruct>,
     idx_col_query: TypedQuery<'a, IndexStruct>
 }
 
-struct TableStruct (String, String, String, String);
+struct TableStruct (String, String, String, String, Option<String>);
 impl MetaQuery for TableStruct {
     fn create(values: &ResultSet) -> TableStruct {
         let s0 = &(values[0]);
         let s1 = &(values[1]);
         let s2 = &(values[2]);
         let s3 = &(values[3]);
-        TableStruct(s0.into(), s1.into(), s2.into(), s3.into() )
+        let s4 = &(values[4]);
+        TableStruct(s0.into(), s1.into(), s2.into(), s3.into(), s4.into() )
     }
 
     fn meta() -> Vec<MetaType> {
-        vec![ string_meta(100), string_meta(1), string_meta(3), string_meta(1) ]
+        vec![ string_meta(100), string_meta(1), string_meta(3), string_meta(1), string_meta(30) ]
     }
 }        
 
-struct ColumnStruct(String, String, u32, String, Option<u32>, Option<u32>);
+struct ColumnStruct(String, String, u32, String, Option<u32>, Option<u32>, Option<String>, u32);
 impl MetaQuery for ColumnStruct {
     fn create(values: &ResultSet) -> ColumnStruct {
         let s0 = &(values[0]);
@@ -94,11 +159,41 @@ impl MetaQuery for ColumnStruct {
         let s3 = &(values[3]);
         let s4 = &(values[4]);
         let s5 = &(values[5]);
-        ColumnStruct(s0.into(), s1.into(),s2.into(), s3.into(), s4.into(), s5.into() )
+        let s6 = &(values[6]);
+        let s7 = &(values[7]);
+        ColumnStruct(s0.into(), s1.into(),s2.into(), s3.into(), s4.into(), s5.into(), s6.into(), s7.into() )
+    }
+
+    fn meta() -> Vec<MetaType> {
+        vec![ string_meta(40), string_meta(40), u32_meta, string_meta(1), u32_meta, u32_meta, string_meta(30), u32_meta ]
+    }
+}
+
+/// name of constraint or index with tablespace of its segment
+struct SegmentNameStruct(String, Option<String>);
+impl MetaQuery for SegmentNameStruct {
+    fn create(values: &ResultSet) -> SegmentNameStruct {
+        let s0 = &(values[0]);
+        let s1 = &(values[1]);
+        SegmentNameStruct(s0.into(), s1.into() )
     }
 
     fn meta() -> Vec<MetaType> {
-        vec![ string_meta(40), string_meta(20), u32_meta, string_meta(1), u32_meta, u32_meta ]
+        vec![ string_meta(40), string_meta(30) ]
+    }
+}
+
+struct IndexInfoStruct(String, String, Option<String>);
+impl MetaQuery for IndexInfoStruct {
+    fn create(values: &ResultSet) -> IndexInfoStruct {
+        let s0 = &(values[0]);
+        let s1 = &(values[1]);
+        let s2 = &(values[2]);
+        IndexInfoStruct(s0.into(), s1.into(), s2.into() )
+    }
+
+    fn meta() -> Vec<MetaType> {
+        vec![ string_meta(40), string_meta(6), string_meta(30) ]
     }
 }
 
@@ -122,7 +217,7 @@ impl <'a> TableInfoService<'a> {
         let pk_bind     = bind! { ""; 200 };
         let idx_bind    = bind! { ""; 200 };
 
-        let sql = "select table_name, temporary, iot_type, backed_up
+        let sql = "select table_name, temporary, iot_type, backed_up, tablespace_name
                    from sys.all_tables
                    where owner = :owner
                     and table_name in (
@@ -137,8 +232,11 @@ impl <'a> TableInfoService<'a> {
             .prepare::<TableStruct>()
             .map_err(|err| format!("can not prepare query for table info: {}", err))?;
         
-        let sql = "select column_name, data_type, data_length, nullable, data_scale, data_precision 
-                   from sys.all_tab_columns where owner = :owner and table_name = :table_name order by column_id";
+        let sql = "select c.column_name, c.data_type, c.data_length, c.nullable, c.data_scale, c.data_precision, l.tablespace_name, c.char_length
+                   from sys.all_tab_columns c, sys.all_lobs l
+                   where c.owner = :owner and c.table_name = :table_name
+                    and l.owner(+) = c.owner and l.table_name(+) = c.table_name and l.column_name(+) = c.column_name
+                   order by c.column_id";
         let binding = bindmap! { "owner" => schema_bind, "table_name" => table_bind };  
 
         let columns_query = conn.query(sql)
@@ -146,12 +244,15 @@ impl <'a> TableInfoService<'a> {
             .prepare::<ColumnStruct>()
             .map_err(|err| format!("can not prepare query for columns info: {}", err))?;
 
-        let sql = "select constraint_name from sys.all_constraints where owner = :owner and constraint_type = 'P' and status = 'ENABLED' and table_name = :table_name";
+        let sql = "select c.constraint_name, i.tablespace_name
+                   from sys.all_constraints c, sys.all_indexes i
+                   where c.owner = :owner and c.constraint_type = 'P' and c.status = 'ENABLED' and c.table_name = :table_name
+                    and i.owner(+) = c.index_owner and i.index_name(+) = c.index_name";
         let binding = bindmap! { "owner" => schema_bind, "table_name" => table_bind };          
 
         let pk_query = conn.query(sql)
             .bind(binding)
-            .prepare::<String>()
+            .prepare::<SegmentNameStruct>()
             .map_err(|err| format!("can not prepare query for primary key info: {}", err))?;
 
         let sql = "select column_name from sys.all_cons_columns where owner = :owner and table_name = :table_name and constraint_name = :constraint order by position";
@@ -162,12 +263,12 @@ impl <'a> TableInfoService<'a> {
             .prepare::<String>()
             .map_err(|err| format!("can not prepare query for primary key columns info: {}", err))?;
 
-        let sql = "select index_name, uniqueness from sys.all_indexes where table_owner = :owner and table_name = :table_name";
+        let sql = "select index_name, uniqueness, tablespace_name from sys.all_indexes where table_owner = :owner and table_name = :table_name";
         let binding = bindmap! { "owner" => schema_bind, "table_name" => table_bind };          
 
         let idx_query = conn.query(sql)
             .bind(binding)
-            .prepare::<IndexStruct>()
+            .prepare::<IndexInfoStruct>()
             .map_err(|err| format!("can not prepare query for indexes info: {}", err))?;
 
         let sql = "select column_name, descend from sys.all_ind_columns where index_owner = :owner and index_name = :index_name";
@@ -202,42 +303,52 @@ impl <'a> TableInfoService<'a> {
         schema_bind.set(schema_name);
         let iterator = query.iterator().map_err(|err| format!("can not load table struct with error: {}", err))?;
 
-        let ref exclusions = match schema.exclusions {
-            None => HashSet::new(),
-            Some(ref exclusions) => exclusions.into_iter().collect() 
-        };
+        let rules = Rules::of(schema)?;
+        let remap = Remap::of(schema);
 
         for ti in iterator { 
             let name = ti.0;
 
-            if exclusions.contains(&name) {
+            if !rules.selects(&name) {
                 continue;
             }
 
             let temporary = ti.1 == "Y"; 
             let iot = ti.2 == "IOT";
             let backed_up = ti.3 == "Y";
+            let tablespace = if temporary { None } else { Some(remap.table(ti.4.as_ref().map(|ts| ts.as_str()))) };
 
             table_bind.set(&name);
 
-            let columns = Self::load_column_info(columns_query).map_err(|err| format!("can not load columns info for table: {} with error: {}", name, err))?;
+            let mut columns = Self::load_column_info(columns_query, &remap).map_err(|err| format!("can not load columns info for table: {} with error: {}", name, err))?;
+
+            let primary_key = Self::load_primary_key(pk_query, pk_col_query, pk_bind, &remap).map_err(|err| format!("can not load primary key info for table: {} with error: {}", name, err))?;
+
+            let mut indexes = Self::load_indexes(idx_query, idx_col_query, idx_bind, &primary_key, &remap).map_err(|err| format!("can not load indexes info for table: {} with error: {}", name, err))?;
+
+            let table_config = schema.tables.as_ref().and_then(|tables| tables.get(&name));
+            let excluded = table_config.and_then(|tc| tc.exclude.clone()).unwrap_or(Vec::new());
+            if let Some(tc) = table_config {
+                apply_config(schema_name, &name, tc, &primary_key, &indexes, &mut columns)?;
+            }
+            // indexes of excluded columns are not created
+            indexes.retain(|idx| !idx.columns.iter().any(|c| excluded.contains(&c.name)));
 
             let unsupported = columns.iter().any(|ref x| x.col_type == ColumnType::Unsupported);
 
             let no_iot = columns.iter().any(|ref x| x.buffer_len > 1000);
 
-            let primary_key = Self::load_primary_key(pk_query, pk_col_query, pk_bind).map_err(|err| format!("can not load primary key info for table: {} with error: {}", name, err))?;
-
-            let indexes = Self::load_indexes(idx_query, idx_col_query, idx_bind, &primary_key).map_err(|err| format!("can not load indexes info for table: {} with error: {}", name, err))?;
+            let filter = table_config.and_then(|tc| tc.filter.clone());
                     
             let luna_calc = if temporary || iot {
                 false
             } else {
                 columns.iter().any(|ref x| x.name == "LUNA_CALC")    
             };
-            let has_blob = columns.iter().any(|ref x| x.col_type == ColumnType::Blob || x.col_type == ColumnType::Clob);
+            let has_blob = columns.iter().any(|ref x| x.col_type.is_lob() || x.col_type.is_long());
 
-            let table_info = TableInfo { name, temporary, backed_up, iot, luna_calc, has_blob, unsupported, no_iot, columns, primary_key, indexes };
+            let table_info = TableInfo { name, temporary, backed_up, iot, luna_calc, has_blob, unsupported, no_iot, columns, primary_key, indexes, filter,
+                                         tablespace, logging: schema.logging, compress: schema.compress.clone() };
 
             vector.push(table_info);
         }
@@ -245,7 +356,7 @@ impl <'a> TableInfoService<'a> {
         Ok(vector)
     }
 
-    fn load_column_info(columns_query: &mut TypedQuery<'a, ColumnStruct>) -> Result<Vec<ColumnInfo>, OracleError> {
+    fn load_column_info(columns_query: &mut TypedQuery<'a, ColumnStruct>, remap: &Remap) -> Result<Vec<ColumnInfo>, OracleError> {
         let mut vector = Vec::new();
 
         let iterator = columns_query.iterator()?;
@@ -257,6 +368,13 @@ impl <'a> TableInfoService<'a> {
             let nullable = ti.3 == "Y";
             let data_scale = if let Some(v) = ti.4 { v } else { 0 };
             let data_precision = if let Some(v) = ti.5 { v } else { 0 };
+            let lob_tablespace = match ti.6 {
+                Some(ref ts) => remap.lob(Some(ts)),
+                // LONG becomes LOB in destination
+                None if col_type_name == "LONG" || col_type_name == "LONG RAW" => remap.lob(None),
+                None => None
+            };
+            let char_length = ti.7;
 
             let (col_type, oci_data_type, buffer_len) = {
                 let ctn: &str = &col_type_name.clone();
@@ -272,7 +390,13 @@ impl <'a> TableInfoService<'a> {
                         // let l = if col_len < 4000 { col_len + 1 } else { col_len };                        
                         (ColumnType::Varchar, OCIDataType::Char, col_len)
                     }
-                    "LONG" => (ColumnType::Long, OCIDataType::Char, 4000),
+                    "NCHAR" | "NVARCHAR2" => {
+                        // fetched in AL32UTF8, at most 3 bytes for each UTF-16 character of national character set
+                        col_len = char_length;
+                        (ColumnType::NVarchar, OCIDataType::Char, char_length * 3)
+                    }
+                    "LONG" => (ColumnType::Long, OCIDataType::Long, 0),
+                    "LONG RAW" => (ColumnType::LongRaw, OCIDataType::LongBinary, 0),
                     "DATE" => (ColumnType::DateTime, OCIDataType::Timestamp, 11),
                     "NUMBER" => {
                         if data_scale == 0 {
@@ -288,6 +412,16 @@ impl <'a> TableInfoService<'a> {
                             (ColumnType::Float64, OCIDataType::Numeric, 22)
                         }
                     }
+                    "FLOAT" => (ColumnType::Float64, OCIDataType::Numeric, 22),
+                    "BINARY_FLOAT" => (ColumnType::Float64, OCIDataType::BinaryFloat, 4),
+                    "BINARY_DOUBLE" => (ColumnType::Float64, OCIDataType::BinaryDouble, 8),
+                    "RAW" => (ColumnType::Raw, OCIDataType::Binary, col_len),
+                    "ROWID" => (ColumnType::Rowid, OCIDataType::Char, 18),
+                    // logical rowids are written as base 64 of their bytes
+                    "UROWID" => (ColumnType::Rowid, OCIDataType::Char, col_len * 2),
+                    _ if ctn.starts_with("TIMESTAMP") => (ColumnType::Timestamp, OCIDataType::Char, 80),
+                    _ if ctn.starts_with("INTERVAL") => (ColumnType::Interval, OCIDataType::Char, 40),
+                    "XMLTYPE" => (ColumnType::Xml, OCIDataType::Clob, 0),
                     "BLOB" => (ColumnType::Blob, OCIDataType::Blob, 0),
                     "CLOB" => (ColumnType::Clob, OCIDataType::Clob, 0),
                     _ => {
@@ -296,21 +430,22 @@ impl <'a> TableInfoService<'a> {
                     }   
                 }
             };
-            vector.push(ColumnInfo {name, col_type, col_type_name, oci_data_type, col_len, nullable, data_precision, data_scale, buffer_len});
+            vector.push(ColumnInfo {name, col_type, col_type_name, oci_data_type, col_len, nullable, data_precision, data_scale, buffer_len, expression: None, lob_tablespace});
         }
         
         Ok(vector)
     }
 
-    fn load_primary_key(pk_query: &mut TypedQuery<'a, String>, pk_col_query:  &mut TypedQuery<'a, String>,
-                        pk_bind: & Binding<String>) -> Result<Option<PrimaryKey>, OracleError> {
+    fn load_primary_key(pk_query: &mut TypedQuery<'a, SegmentNameStruct>, pk_col_query:  &mut TypedQuery<'a, String>,
+                        pk_bind: & Binding<String>, remap: &Remap) -> Result<Option<PrimaryKey>, OracleError> {
         let pk = pk_query.fetch()?;
 
         let result = match pk {
-            Some(name) => {
+            Some(SegmentNameStruct(name, tablespace)) => {
                 pk_bind.set(&name);
                 let columns = pk_col_query.fetch_vec()?;
-                Some( PrimaryKey { name, columns } )
+                let tablespace = remap.index(tablespace.as_ref().map(|ts| ts.as_str()));
+                Some( PrimaryKey { name, columns, tablespace } )
             }
             None => None
         };
@@ -318,8 +453,8 @@ impl <'a> TableInfoService<'a> {
         Ok(result)
     }
 
-    fn load_indexes(idx_query: &mut TypedQuery<'a, IndexStruct>, idx_col_query: &mut TypedQuery<'a, IndexStruct>,
-                    idx_bind: & Binding<String>, primary_key: &Option<PrimaryKey>) -> Result<Vec<Index>, OracleError> {
+    fn load_indexes(idx_query: &mut TypedQuery<'a, IndexInfoStruct>, idx_col_query: &mut TypedQuery<'a, IndexStruct>,
+                    idx_bind: & Binding<String>, primary_key: &Option<PrimaryKey>, remap: &Remap) -> Result<Vec<Index>, OracleError> {
         let mut vector = Vec::new();
 
         let iterator = idx_query.iterator()?;
@@ -327,6 +462,7 @@ impl <'a> TableInfoService<'a> {
         for ti in iterator {        
             let name = ti.0;
             let unique = ti.1 == "UNIQUE";
+            let tablespace = remap.index(ti.2.as_ref().map(|ts| ts.as_str()));
 
             if let Some(ref pk) = *primary_key {
                 if name == pk.name {
@@ -339,7 +475,7 @@ impl <'a> TableInfoService<'a> {
             idx_bind.set(&name);
             idx_col_query.for_each(|cc| columns.push( IndexColumn { name: cc.0, desc: cc.1 != "ASC" } )).unwrap();
 
-            let index = Index { name, unique, columns };
+            let index = Index { name, unique, columns, tablespace };
 
             vector.push(index);
         }
@@ -347,4 +483,128 @@ impl <'a> TableInfoService<'a> {
         Ok(vector)
     }
     
-}
\ No newline at end of file
+}
+
+struct SegmentStruct(String, u64);
+impl MetaQuery for SegmentStruct {
+    fn create(values: &ResultSet) -> SegmentStruct {
+        let s0 = &(values[0]);
+        let s1 = &(values[1]);
+        SegmentStruct(s0.into(), s1.into())
+    }
+
+    fn meta() -> Vec<MetaType> {
+        vec![ string_meta(100), u64_meta ]
+    }
+}
+
+/// bytes allocated by tables of schema, including their LOB segments
+pub fn load_sizes(conn: &Connection, schema: &str) -> Result<HashMap<String, u64>, String> {
+    let sql = format!("select table_name, sum(bytes) from (
+                         select segment_name table_name, bytes from sys.dba_segments
+                         where owner = '{0}' and segment_type like 'TABLE%'
+                         union all
+                         select l.table_name, s.bytes from sys.dba_lobs l, sys.dba_segments s
+                         where l.owner = '{0}' and s.owner = l.owner and s.segment_name = l.segment_name
+                       ) group by table_name", schema);
+
+    let mut query = conn.query(sql)
+        .prepare::<SegmentStruct>()
+        .map_err(|err| format!("can not prepare query for tables size: {}", err))?;
+
+    query.fold(HashMap::new(), |mut sizes, ss| {
+        sizes.insert(ss.0, ss.1);
+        sizes
+    }).map_err(|err| format!("can not load tables size of schema: {} with error: {}", schema, err))
+}
+
+/// removes excluded columns and sets expressions of transformed columns;
+/// primary key can not lose columns and keys can not get values which may repeat
+fn apply_config(schema: &str, table: &str, tc: &config::TableConfig,
+                primary_key: &Option<PrimaryKey>, indexes: &[Index], columns: &mut Vec<ColumnInfo>) -> Result<(), String> {
+    let in_primary_key = |name: &String| primary_key.as_ref().map_or(false, |pk| pk.columns.contains(name));
+    let in_unique_index = |name: &String| indexes.iter()
+        .any(|idx| idx.unique && idx.columns.iter().any(|c| &c.name == name));
+
+    if let Some(ref exclude) = tc.exclude {
+        for name in exclude {
+            if !columns.iter().any(|c| &c.name == name) {
+                return Err(format!("excluded column: {} not found in table: {}.{}", name, schema, table));
+            }
+            if in_primary_key(name) {
+                return Err(format!("column: {} of primary key of table: {}.{} can not be excluded", name, schema, table));
+            }
+        }
+        columns.retain(|c| !exclude.contains(&c.name));
+        if columns.is_empty() {
+            return Err(format!("all columns of table: {}.{} are excluded", schema, table));
+        }
+    }
+
+    if let Some(ref transforms) = tc.transforms {
+        for (name, t) in transforms {
+            // hashes may collide, fake names repeat and a constant is the same for every row
+            let repeats = match *t {
+                config::Transform::Hash | config::Transform::FakeName | config::Transform::Constant(_) => true,
+                config::Transform::Null => false
+            };
+            if repeats && in_primary_key(name) {
+                return Err(format!("column: {} of primary key of table: {}.{} can not be transformed by {:?}", name, schema, table, t));
+            }
+            if repeats && in_unique_index(name) {
+                return Err(format!("column: {} of unique index of table: {}.{} can not be transformed by {:?}", name, schema, table, t));
+            }
+            let column = columns.iter_mut().find(|c| &c.name == name)
+                .ok_or(format!("transformed column: {} not found in table: {}.{}", name, schema, table))?;
+            let expression = transform::expression(column, t)
+                .map_err(|err| format!("can not transform table: {}.{} with error: {}", schema, table, err))?;
+            column.expression = Some(expression);
+        }
+    }
+
+    Ok(())
+}
+
+struct RowidRangeStruct(String, String);
+impl MetaQuery for RowidRangeStruct {
+    fn create(values: &ResultSet) -> RowidRangeStruct {
+        let s0 = &(values[0]);
+        let s1 = &(values[1]);
+        RowidRangeStruct(s0.into(), s1.into())
+    }
+
+    fn meta() -> Vec<MetaType> {
+        vec![ string_meta(18), string_meta(18) ]
+    }
+}
+
+/// splits extents of table into ranges of about the same number of blocks;
+/// partitioned tables have no segment of type TABLE and are not split
+pub fn load_rowid_ranges(conn: &Connection, schema: &str, table: &str, chunks: u32) -> Result<Vec<(String, String)>, String> {
+    let sql = format!("select dbms_rowid.rowid_create(1, o.data_object_id, e.lo_fno, e.lo_block, 0),
+                              dbms_rowid.rowid_create(1, o.data_object_id, e.hi_fno, e.hi_block, 32767)
+                       from (
+                         select grp,
+                                min(relative_fno) keep (dense_rank first order by relative_fno, block_id) lo_fno,
+                                min(block_id) keep (dense_rank first order by relative_fno, block_id) lo_block,
+                                max(relative_fno) keep (dense_rank last order by relative_fno, block_id) hi_fno,
+                                max(block_id + blocks - 1) keep (dense_rank last order by relative_fno, block_id) hi_block
+                         from (
+                           select relative_fno, block_id, blocks,
+                                  trunc((sum(blocks) over (order by relative_fno, block_id) - 0.01) / (sum(blocks) over () / {2})) grp
+                           from sys.dba_extents
+                           where owner = '{0}' and segment_name = '{1}' and segment_type = 'TABLE'
+                         ) group by grp
+                       ) e, sys.dba_objects o
+                       where o.owner = '{0}' and o.object_name = '{1}' and o.object_type = 'TABLE'
+                       order by e.grp", schema, table, chunks);
+
+    let mut query = conn.query(sql)
+        .prepare::<RowidRangeStruct>()
+        .map_err(|err| format!("can not prepare query for rowid ranges: {}", err))?;
+
+    query.fold(Vec::new(), |mut ranges, rr| {
+        ranges.push((rr.0, rr.1));
+        ranges
+    }).map_err(|err| format!("can not load rowid ranges of table: {}.{} with error: {}", schema, table, err))
+}
@@ -1,12 +1,16 @@
 use itertools::Itertools;
 
 use oracle;
+use oracle::{ Binding, Bindmap };
 use config;
 use logger::*;
 use table;
 use table_loader;
+use indexes;
 use indexes::IndexesService;
 use grants;
+use ddl::{ Ddl, Section };
+use journal::{ Journal, Step };
 
 use chrono::*;
 
@@ -19,180 +23,417 @@ pub fn load(source: &oracle::Connection,
             load_buffer: &table_loader::LoadBuffer,
             lob_copy_buffer: &[u8],
             logger: &Logger, err_logger: &ErrLogger,
-            tas: &IndexesService) -> Result<(), String> {
+            tas: &IndexesService, journal: &Journal) -> Result<(), String> {
     let tables_vec = table_info_service.load(schema)?;
 
     for ref table in tables_vec {
-        info(table, logger);
+        let mut line = TableLine::direct(logger);
+        load_table(source, destination, grants_service, schema, table, luna_calc,
+                   load_buffer, lob_copy_buffer, &mut line, err_logger, tas, journal)?;
+    }
 
-        let mut iot = table.iot;
-        let mut exists = false;
+    destination.commit(oracle::OCICommitMode::Immediate).unwrap();    
 
-        if table.temporary {
-            let table_sql = create_table_sql(schema, table, false);
-            if let Err(err) = destination.execute(table_sql) {
-                if err.code == 955 {
-                    exists = true;
-                } else if err.code == 1918 {
-                    err_logger.error(format!("can not create table: {} because user {} does not exists", table.name, schema.name));
-                } else {
-                    err_logger.error(format!("can not create table: {} with error: {}", table.name, err));
-                }
-            }
-        } else if !table.unsupported {
-            let read_sql = create_read_sql(schema, table, luna_calc);
-            let write_sql = create_write_sql(schema, table);
+    Ok(())
+}
 
-            let start: DateTime<Local> = Local::now();
+/// Output of one table: printed as it goes when tables are loaded one by one,
+/// collected and printed as one line when workers load tables concurrently
+pub struct TableLine<'l> {
+    logger:   &'l Logger,
+    buffered: bool,
+    text:     String
+}
 
-            let mut reader = table_loader::TableReader::new(source, read_sql)
-                            .map_err(|err| format!("can not create reader for table: {} with error: {}", table.name, err))?;
+impl <'l> TableLine<'l> {
+    pub fn direct(logger: &'l Logger) -> TableLine<'l> {
+        TableLine { logger, buffered: false, text: String::new() }
+    }
 
-            let mut writer = table_loader::TableWriter::new(destination, write_sql)                     
-                            .map_err(|err| format!("can not create writer for table: {} with error: {}", table.name, err))?;   
+    pub fn buffered<S: Into<String>>(logger: &'l Logger, prefix: S) -> TableLine<'l> {
+        TableLine { logger, buffered: true, text: prefix.into() }
+    }
+
+    fn print<S: Into<String>>(&mut self, text: S) {
+        if self.buffered {
+            self.text += &text.into();
+        } else {
+            self.logger.print(text);
+        }
+    }
+
+    fn flush(&self) {
+        if !self.buffered {
+            self.logger.flush();
+        }
+    }
 
-            let (prefetch_rows, mut lob_processor) = load_buffer.bind(table, source, destination, &reader, &writer)
-                            .map_err(|err| format!("can not bind reader/writer for table: {} with error: {}", table.name, err))?;    
+    fn end(&mut self) {
+        if self.buffered {
+            self.logger.println(self.text.clone());
+            self.text.clear();
+        } else {
+            self.logger.newline();
+        }
+    }
+}
 
-            // let total_rows_cnt = rows_count(source, schema, table, luna_calc)?;
+/// creates and loads one table, then grants it and hands it over for indexes
+pub fn load_table(source: &oracle::Connection,
+                  destination: &oracle::Connection,
+                  grants_service: &mut grants::GrantsService,
+                  schema: &config::Schema,
+                  table: &table::TableInfo,
+                  luna_calc: u32,
+                  load_buffer: &table_loader::LoadBuffer,
+                  lob_copy_buffer: &[u8],
+                  line: &mut TableLine, err_logger: &ErrLogger,
+                  tas: &IndexesService, journal: &Journal) -> Result<(), String> {
+    info(table, line);
+
+    let full_name = format!("{}.{}", schema.name, table.name);
+
+    if journal.is_done(Step::Table, &full_name) {
+        let rows = journal.rows(Step::Table, &full_name).unwrap_or(0);
+        line.print(lpad(format!(" {} rows", rows), 15));
+        line.print(" done before");
+        line.end();
+        if !journal.is_done(Step::Indexes, &full_name) {
+            tas.finalize(schema.name.clone(), table.clone());
+        }
+        return Ok(());
+    }
 
-            reader.execute(source)
-                .map_err(|err| format!("can not execute reader for table: {} with error: {}", table.name, err))?;
+    let mut iot = table.iot;
+
+    // table interrupted by previous run is emptied and loaded again, keeping the organization it was created with
+    let mut reload = false;
+    if journal.is_interrupted(Step::Table, &full_name) && !table.temporary && !table.unsupported {
+        let sql = format!("truncate table {}.{}", &(schema.name), &(table.name));
+        match destination.execute(sql) {
+            Ok(_) => reload = true,
+            Err(ref err) if err.code == 942 => {}
+            Err(err) => {
+                line.end();
+                return Err(format!("can not truncate table: {} with error: {}", table.name, err));
+            }
+        }
+    }
+    if reload && !iot {
+        iot = created_as_iot(destination, &(schema.name), &(table.name))
+            .map_err(|err| { line.end(); err })?;
+    }
 
-            let mut total_rows = 0;
-            let mut first_chunk = true;
+    journal.started(Step::Table, &full_name)?;
 
-            // println!("prefetch {} rows", prefetch_rows);
+    let mut exists = false;
+    let mut total_rows = 0;
 
-            loop {
-                let (rows, done) = reader.fetch(prefetch_rows)
-                            .map_err(|err| format!("can not fetch from table: {} with error: {}", table.name, err))?;
+    if table.temporary {
+        let table_sql = create_table_sql(schema, table, false);
+        if let Err(err) = destination.execute(table_sql) {
+            if err.code == 955 {
+                exists = true;
+            } else if err.code == 1918 {
+                err_logger.error(format!("can not create table: {} because user {} does not exists", table.name, schema.name));
+            } else {
+                err_logger.error(format!("can not create table: {} with error: {}", table.name, err));
+            }
+        }
+    } else if !table.unsupported {
+        let read_sql = create_read_sql(schema, table, luna_calc, None);
+        let write_sql = create_write_sql(schema, table);
 
+        let start: DateTime<Local> = Local::now();
 
-                // println!("fetched {} rows and done: {}", rows, done);                            
+        let mut reader = table_loader::TableReader::new(source, read_sql)
+                        .map_err(|err| format!("can not create reader for table: {} with error: {}", table.name, err))?;
 
-                if first_chunk {
-                    let has_pk = if let Some(ref _pk) = table.primary_key { true } else { false };
-                    iot = iot || (!table.no_iot && has_pk && !table.temporary && !table.has_blob && rows < 5000 && table.indexes.len() == 0 && table.columns.len() < 6);
-                    
-                    let table_sql = create_table_sql(schema, table, iot);
+        let mut writer = table_loader::TableWriter::new(destination, write_sql)                     
+                        .map_err(|err| format!("can not create writer for table: {} with error: {}", table.name, err))?;   
 
-                    // println!("sql: {}", &table_sql);
+        let (prefetch_rows, mut lob_processor) = load_buffer.bind(table, source, destination, &reader, &writer)
+                        .map_err(|err| format!("can not bind reader/writer for table: {} with error: {}", table.name, err))?;    
 
-                    if let Err(err) = destination.execute(table_sql) {
-                        if err.code == 955 {
-                            exists = true;
-                            break;
-                        } else if err.code == 1918 {
-                            logger.newline();
-                            return Err(format!("can not create table: {} because user {} does not exists", table.name, schema.name));
-                        } else {
-                            logger.newline();
-                            return Err(format!("can not create table: {} with error: {}", table.name, err));
-                        }
-                    }
-                }   
+        reader.execute(source)
+            .map_err(|err| format!("can not execute reader for table: {} with error: {}", table.name, err))?;
 
-                if rows > 0 {
-                    if table.has_blob {
-                        lob_processor.copy(lob_copy_buffer)
-                            .map_err(|err| format!("can not copy LOB for table: {} with error: {}", table.name, err))?;
-                    }
+        let mut first_chunk = true;
 
-                    // load_buffer.trans_rom_utf(table, prefetch_rows, rows);
+        // println!("prefetch {} rows", prefetch_rows);
 
-                    writer.execute(destination, rows)
-                        .map_err(|err| format!("can not write to table: {} {} row with error: {}, prefetch: {} rows, first_chunk: {}",
-                                               table.name, total_rows + rows, err, prefetch_rows, first_chunk))?;
+        loop {
+            let (rows, done) = reader.fetch(prefetch_rows, &mut lob_processor, lob_copy_buffer)
+                        .map_err(|err| format!("can not fetch from table: {} with error: {}", table.name, err))?;
 
-                    destination.commit(oracle::OCICommitMode::Nowait)
-                        .map_err(|err| format!("can not commit table: {} with error: {}", table.name, err))?;                                               
 
-                    total_rows += rows;            
-                }
+            // println!("fetched {} rows and done: {}", rows, done);                            
 
-                first_chunk = false;
+            if first_chunk && !reload {
+                let has_pk = if let Some(ref _pk) = table.primary_key { true } else { false };
+                iot = iot || (!table.no_iot && has_pk && !table.temporary && !table.has_blob && rows < 5000 && table.indexes.len() == 0 && table.columns.len() < 6);
                 
-                if done {
-                    break;
+                let table_sql = create_table_sql(schema, table, iot);
+
+                // println!("sql: {}", &table_sql);
+
+                if let Err(err) = destination.execute(table_sql) {
+                    if err.code == 955 {
+                        exists = true;
+                        break;
+                    } else if err.code == 1918 {
+                        line.end();
+                        return Err(format!("can not create table: {} because user {} does not exists", table.name, schema.name));
+                    } else {
+                        line.end();
+                        return Err(format!("can not create table: {} with error: {}", table.name, err));
+                    }
                 }
-            }            
+            }   
 
-            let txt = if exists {
-                format!(" allready exists")
-            } else {
-                // lpad(format!(" {} rows of {}", total_rows, total_rows_cnt), 25)
-                lpad(format!(" {} rows", total_rows), 15)
-            };
-            logger.print(txt);
-
-            if !exists {
-                let end: DateTime<Local> = Local::now();
-                let duration = end - start;
-
-                let secs = duration.num_seconds();
-                let minutes = secs / 60;
-                let secs = secs - minutes * 60;
-
-                if minutes != 0 || secs != 0 {
-                    let t = if minutes == 0 { "".to_string() } else { format!("{} mins, ", minutes) };
-                    logger.print(format!(" {}{} secs", t, secs));
+            if rows > 0 {
+                if table.has_blob {
+                    lob_processor.copy(lob_copy_buffer)
+                        .map_err(|err| format!("can not copy LOB for table: {} with error: {}", table.name, err))?;
                 }
+
+                // load_buffer.trans_rom_utf(table, prefetch_rows, rows);
+
+                writer.execute(destination, rows)
+                    .map_err(|err| format!("can not write to table: {} {} row with error: {}, prefetch: {} rows, first_chunk: {}",
+                                           table.name, total_rows + rows, err, prefetch_rows, first_chunk))?;
+
+                destination.commit(oracle::OCICommitMode::Nowait)
+                    .map_err(|err| format!("can not commit table: {} with error: {}", table.name, err))?;                                               
+
+                total_rows += rows;            
             }
-        }
-        logger.newline();
 
-        if !table.unsupported {
-            grants_service.grant(destination, &(schema.name), &(table.name))?;
-            if !iot && !exists {
-                tas.finalize(schema.name.clone(), table.clone());
+            first_chunk = false;
+            
+            if done {
+                break;
             }
-        } 
+        }            
+
+        let txt = if exists {
+            format!(" allready exists")
+        } else {
+            lpad(format!(" {} rows", total_rows), 15)
+        };
+        line.print(txt);
+
+        if !exists {
+            let end: DateTime<Local> = Local::now();
+            let duration = end - start;
+
+            let secs = duration.num_seconds();
+            let minutes = secs / 60;
+            let secs = secs - minutes * 60;
+
+            if minutes != 0 || secs != 0 {
+                let t = if minutes == 0 { "".to_string() } else { format!("{} mins, ", minutes) };
+                line.print(format!(" {}{} secs", t, secs));
+            }
+        }
     }
+    line.print(converted(table));
+    line.end();
 
-    destination.commit(oracle::OCICommitMode::Immediate).unwrap();    
+    if !table.unsupported {
+        grants_service.grant(&Ddl::Execute(destination), &(schema.name), &(table.name))?;
+        if !iot && !exists {
+            tas.finalize(schema.name.clone(), table.clone());
+        } else {
+            journal.done(Step::Indexes, &full_name)?;
+        }
+    } 
+
+    destination.commit(oracle::OCICommitMode::Immediate)
+        .map_err(|err| format!("can not commit table: {} with error: {}", table.name, err))?;
+    journal.done_rows(Step::Table, &full_name, total_rows)?;
 
     Ok(())
 }
 
-fn info(table: &table::TableInfo, logger: &Logger) {
+/// true when table in destination is organized by index, as a small table with primary key is created so by load
+fn created_as_iot(destination: &oracle::Connection, schema: &str, table: &str) -> Result<bool, String> {
+    let schema_bind = bind!(schema);
+    let table_bind  = bind!(table);
+
+    let sql = "select count(*) from sys.all_tables where owner = :owner and table_name = :table_name and iot_type = 'IOT'";
+    let binding = bindmap! { "owner" => schema_bind, "table_name" => table_bind };
+    let count = destination.query(sql)
+        .bind(binding)
+        .prepare::<u32>()
+        .and_then(|mut query| query.fetch())
+        .map_err(|err| format!("can not load organization of table: {}.{} with error: {}", schema, table, err))?;
+
+    Ok(count.unwrap_or(0) > 0)
+}
+
+/// writes tables of schema to script without rows: tables, their indexes, primary keys and grants
+pub fn script(destination: &Ddl,
+              table_info_service: &mut table::TableInfoService,
+              grants_service: &mut grants::GrantsService,
+              schema: &config::Schema,
+              logger: &Logger) -> Result<(), String> {
+    let tables_vec = table_info_service.load(schema)?;
+
+    for ref table in tables_vec {
+        let mut line = TableLine::direct(logger);
+        info(table, &mut line);
+        line.print(converted(table));
+        line.end();
+
+        if table.unsupported {
+            continue;
+        }
+
+        // rows are unknown, so only tables organized by index in source are created so
+        let table_sql = create_table_sql(schema, table, table.iot);
+        destination.execute(Section::Tables, &(schema.name), table_sql)
+            .map_err(|err| format!("can not create table: {} with error: {}", table.name, err))?;
+
+        if !table.iot {
+            indexes::create_pk(destination, &(schema.name), table)?;
+            indexes::create_indexes(destination, &(schema.name), table)?;
+        }
+        grants_service.grant(destination, &(schema.name), &(table.name))?;
+    }
+
+    Ok(())
+}
+
+/// copies rows of table between two ROWIDs of source; rows are committed once at the end,
+/// so a failed chunk is rolled back and can be copied again on its own
+pub fn copy_chunk(source: &oracle::Connection,
+                  destination: &oracle::Connection,
+                  schema: &config::Schema,
+                  table: &table::TableInfo,
+                  luna_calc: u32,
+                  min_rowid: &str, max_rowid: &str,
+                  load_buffer: &table_loader::LoadBuffer,
+                  lob_copy_buffer: &[u8]) -> Result<u32, String> {
+    let range = format!("rowid between chartorowid('{}') and chartorowid('{}')", min_rowid, max_rowid);
+    let read_sql = create_read_sql(schema, table, luna_calc, Some(&range));
+    let write_sql = create_chunk_write_sql(schema, table);
+
+    copy_rows(source, destination, table, read_sql, write_sql, load_buffer, lob_copy_buffer)
+}
+
+/// merges rows of source table matching filter into existing destination table by primary key,
+/// committed once at the end
+pub fn merge_rows(source: &oracle::Connection,
+                  destination: &oracle::Connection,
+                  schema: &config::Schema,
+                  table: &table::TableInfo,
+                  luna_calc: u32,
+                  filter: &str,
+                  load_buffer: &table_loader::LoadBuffer,
+                  lob_copy_buffer: &[u8]) -> Result<u32, String> {
+    let pk = table.primary_key.as_ref()
+        .ok_or(format!("can not merge table: {} without primary key", table.name))?;
+
+    let read_sql = create_read_sql(schema, table, luna_calc, Some(filter));
+    let write_sql = create_merge_sql(schema, table, pk);
+
+    copy_rows(source, destination, table, read_sql, write_sql, load_buffer, lob_copy_buffer)
+}
+
+fn copy_rows(source: &oracle::Connection,
+             destination: &oracle::Connection,
+             table: &table::TableInfo,
+             read_sql: String, write_sql: String,
+             load_buffer: &table_loader::LoadBuffer,
+             lob_copy_buffer: &[u8]) -> Result<u32, String> {
+    let mut reader = table_loader::TableReader::new(source, read_sql)
+                    .map_err(|err| format!("can not create reader for table: {} with error: {}", table.name, err))?;
+
+    let mut writer = table_loader::TableWriter::new(destination, write_sql)                     
+                    .map_err(|err| format!("can not create writer for table: {} with error: {}", table.name, err))?;   
+
+    let (prefetch_rows, mut lob_processor) = load_buffer.bind(table, source, destination, &reader, &writer)
+                    .map_err(|err| format!("can not bind reader/writer for table: {} with error: {}", table.name, err))?;    
+
+    reader.execute(source)
+        .map_err(|err| format!("can not execute reader for table: {} with error: {}", table.name, err))?;
+
+    let mut total_rows = 0;
+
+    loop {
+        let (rows, done) = reader.fetch(prefetch_rows, &mut lob_processor, lob_copy_buffer)
+                    .map_err(|err| format!("can not fetch from table: {} with error: {}", table.name, err))?;
+
+        if rows > 0 {
+            if table.has_blob {
+                lob_processor.copy(lob_copy_buffer)
+                    .map_err(|err| format!("can not copy LOB for table: {} with error: {}", table.name, err))?;
+            }
+
+            writer.execute(destination, rows)
+                .map_err(|err| format!("can not write to table: {} {} row with error: {}", table.name, total_rows + rows, err))?;
+
+            total_rows += rows;            
+        }
+
+        if done {
+            break;
+        }
+    }
+
+    destination.commit(oracle::OCICommitMode::Immediate)
+        .map_err(|err| format!("can not commit table: {} with error: {}", table.name, err))?;
+
+    Ok(total_rows)
+}
+
+fn info(table: &table::TableInfo, line: &mut TableLine) {
     let tn: &str = &table.name;
-    logger.print(format!("  {}", rpad(tn, 40)));                                        
+    line.print(format!("  {}", rpad(tn, 40)));                                        
     if table.temporary {
-        logger.print("  temporary");
+        line.print("  temporary");
     } else if table.unsupported {
-        logger.print("unsupported");
+        line.print("unsupported");
     } else {
         if let Some(_) = table.primary_key {
-            logger.print("P");
+            line.print("P");
         } else if table.luna_calc {
-            logger.print("L");
+            line.print("L");
         } else {
-            logger.print(" ");
+            line.print(" ");
         }        
     }
-    logger.flush();
+    line.flush();
 }
 
-fn rows_count(source: &oracle::Connection, schema: &config::Schema, table: &table::TableInfo, luna_calc: u32) -> Result<u32, String> {
-    let mut sql = format!("select count(*) from {}.{}", &(schema.name), &(table.name));
-
-    // if table.luna_calc {
-    //     sql = format!("{} where luna_calc is null or luna_calc >= {}", sql, luna_calc);
-    // }
-
-    let mut query = source.query(sql)
-        .prepare::<u32>()
-        .map_err(|err| format!("can not create query for get rows count info: {}", err))?;
-    
-    Ok(query.fetch().map_err(|err| format!("can not get rows count info: {}", err))?.unwrap())
+/// columns of other type in destination, told after table
+pub fn converted(table: &table::TableInfo) -> String {
+    table.columns.iter()
+        .filter_map(|c| match c.col_type {
+            table::ColumnType::Long => Some(format!(" {} LONG as CLOB", c.name)),
+            table::ColumnType::LongRaw => Some(format!(" {} LONG RAW as BLOB", c.name)),
+            _ => None
+        })
+        .join(",")
 }
 
-fn create_read_sql(schema: &config::Schema, table: &table::TableInfo, luna_calc: u32) -> String {
-    let columns = join_columns(table);
+/// reads masked columns by their expressions and only rows matching filter of table and condition
+fn create_read_sql(schema: &config::Schema, table: &table::TableInfo, luna_calc: u32, condition: Option<&str>) -> String {
+    let columns = table.columns.iter()
+        .map(|c| match c.expression {
+            Some(ref expression) => expression.clone(),
+            None => c.select()
+        })
+        .join(",");
 
     let mut sql = "select /*+ ALL_ROWS */ ".to_string() + &columns + " from " + &(schema.name) + "." + &(table.name);
 
+    let conditions = table.filter.iter().map(|f| f.as_str()).chain(condition).map(|c| format!("({})", c)).join(" and ");
+    if !conditions.is_empty() {
+        sql = sql + " where " + &conditions;
+    }
+
     // if table.luna_calc {
     //     sql = format!("{} where luna_calc is null or luna_calc >= {}", sql, luna_calc);
     // }
@@ -212,7 +453,41 @@ fn create_write_sql(schema: &config::Schema, table: &table::TableInfo) -> String
     "insert /*+ APPEND_VALUES */ into ".to_string() + &(schema.name) + "." + &(table.name) + " (" + &columns + ") values (" + &placeholders + ")"
 }
 
-fn create_table_sql(schema: &config::Schema, table: &table::TableInfo, iot: bool) -> String {
+/// chunks of one table are written concurrently, so they can not use direct path insert
+/// what locks the whole table
+fn create_chunk_write_sql(schema: &config::Schema, table: &table::TableInfo) -> String {
+    let columns = join_columns(table);
+    let placeholders = join_placeholders(table);
+    "insert into ".to_string() + &(schema.name) + "." + &(table.name) + " (" + &columns + ") values (" + &placeholders + ")"
+}
+
+/// rows are bound by position in the same order as they are read, as in insert
+fn create_merge_sql(schema: &config::Schema, table: &table::TableInfo, pk: &table::PrimaryKey) -> String {
+    let values = table.columns.iter().enumerate()
+        .map(|(i, c)| format!("{} \"{}\"", c.value(&format!(":{}", i + 1)), c.name))
+        .join(",");
+    let on = pk.columns.iter()
+        .map(|c| format!("d.\"{0}\" = s.\"{0}\"", c))
+        .join(" and ");
+    let update = table.columns.iter()
+        .filter(|c| !pk.columns.contains(&c.name))
+        .map(|c| format!("d.\"{0}\" = s.\"{0}\"", c.name))
+        .join(",");
+    let columns = join_columns(table);
+    let source_columns = table.columns.iter()
+        .map(|c| format!("s.\"{}\"", c.name))
+        .join(",");
+
+    let mut sql = format!("merge into {}.{} d using (select {} from dual) s on ({})",
+                          schema.name, table.name, values, on);
+    if !update.is_empty() {
+        sql += &format!(" when matched then update set {}", update);
+    }
+    sql += &format!(" when not matched then insert ({}) values ({})", columns, source_columns);
+    sql
+}
+
+pub fn create_table_sql(schema: &config::Schema, table: &table::TableInfo, iot: bool) -> String {
     let mut sql = "create ".to_string();
 
     if table.temporary {
@@ -229,8 +504,20 @@ fn create_table_sql(schema: &config::Schema, table: &table::TableInfo, iot: bool
                     // let ss = format!("({})", &c.buffer_len);
                     s += &ss;
                 }
+                "NCHAR" | "NVARCHAR2" | "RAW" | "UROWID" => {
+                    s += &format!("{}({})", ctn, &c.col_len);
+                }
+                "FLOAT" => {
+                    s += ctn;
+                    if c.data_precision > 0 {
+                        s += &format!("({})", &c.data_precision);
+                    }
+                }
                 "LONG" => {
-                    s += "VARCHAR2(4000)";
+                    s += "CLOB";
+                }
+                "LONG RAW" => {
+                    s += "BLOB";
                 }
                 "NUMBER" => {
                     if c.data_precision > 0 {
@@ -253,13 +540,17 @@ fn create_table_sql(schema: &config::Schema, table: &table::TableInfo, iot: bool
 
     sql += &tbl;
 
+    let tablespace = table.tablespace.as_ref().map(|ts| format!(" tablespace \"{}\"", ts)).unwrap_or(String::new());
+
     if iot {
         if let Some(ref pk) = table.primary_key {
             let pk_columns = pk.columns.iter().join(",");
             let ss = ", constraint ".to_string() + &(pk.name) + " primary key (" + &pk_columns + ")\n) organization index";
             sql += &ss;
+            sql += &tablespace;
         } else {
-            sql += ") tablespace \"DATA\"";
+            sql += ")";
+            sql += &tablespace;
         }
     } else {
         //// println!("has_pk: {}, temporary: {}, has_blob: {}, fetched: {}, indexes: {}", has_pk, table.temporary, table.has_blob, table.has_blob, table.indexes.len());
@@ -267,8 +558,27 @@ fn create_table_sql(schema: &config::Schema, table: &table::TableInfo, iot: bool
         if table.temporary {
             let ss = ") on commit ".to_string() + if table.backed_up { "preserve rows" } else { "delete rows" };
             sql += &ss;
+            return sql;
         } else {
-            sql += ") tablespace \"DATA\"";
+            sql += ")";
+            sql += &tablespace;
+        }
+    }
+
+    match table.logging {
+        Some(true) => sql += " logging",
+        Some(false) => sql += " nologging",
+        None => {}
+    }
+    if let Some(ref compress) = table.compress {
+        if !iot {
+            sql = sql + " " + compress;
+        }
+    }
+
+    for c in &table.columns {
+        if let Some(ref ts) = c.lob_tablespace {
+            sql += &format!("\n lob (\"{}\") store as (tablespace \"{}\")", c.name, ts);
         }
     }
 
@@ -286,5 +596,5 @@ fn join_placeholders(table: &table::TableInfo) -> String {
         .iter()
         // .filter(|c| c.col_type != table::ColumnType::Clob && c.col_type != table::ColumnType::Blob)
         .enumerate()
-        .map(|(i,_)| format!(":{}", i + 1) ).join(",")
+        .map(|(i, c)| c.value(&format!(":{}", i + 1)) ).join(",")
 }
\ No newline at end of file
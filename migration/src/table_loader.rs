@@ -12,6 +12,7 @@ use oracle::oci;
 use oracle::Connection;
 use oracle::{ LobDescriptor, lob_copy };
 use oracle::OCITempLobType;
+use oracle::OCILobPiece;
 
 pub struct TableReader {
     stmt_handle:  *mut oci::OCIStmt,
@@ -36,7 +37,16 @@ pub struct LoadBuffer {
 
 pub struct LobProcessor<'a> {
     // pairs of lob locators -> src, dst
-    lob_locators: Vec<(LobDescriptor<'a>, LobDescriptor<'a>)>
+    lob_locators: Vec<(LobDescriptor<'a>, LobDescriptor<'a>)>,
+    // LONG or LONG RAW column, table has at most one
+    long:         Option<LongColumn<'a>>
+}
+
+/// LONG column fetched piece by piece into temporary LOB of destination
+struct LongColumn<'a> {
+    define_handle: *mut oci::OCIDefine,
+    indicator:     *const libc::c_short,
+    lob:           LobDescriptor<'a>
 }
 
 impl TableReader {
@@ -52,21 +62,54 @@ impl TableReader {
         oci::oci_stmt_execute(conn.service_handle, self.stmt_handle, self.error_handle, 0)
     }    
 
-    pub fn fetch(&mut self, prefetch_rows: u32) -> Result<(u32, bool), oci::OracleError> {
+    /// fetch of LONG needs data for each piece, buffer is handed over for every piece
+    /// and what previous piece left in it is written to LOB, so LONG of any length is copied
+    pub fn fetch(&mut self, prefetch_rows: u32, lob_processor: &mut LobProcessor, buffer: &[u8]) -> Result<(u32, bool), oci::OracleError> {
         let mut done = false;
+        let mut pieces = 0;
+        let mut piece_len: u32 = 0;
+
+        loop {
+            if let Err(error) = 
+                    oci::oci_stmt_fetch(self.stmt_handle,
+                                        self.error_handle,
+                                        prefetch_rows,
+                                        oci::OCIOrientation::FetchNext, 0) {
+
+                if error.code == 100 {
+                    done = true;
+                } else if error.code == 99 && lob_processor.long.is_some() {
+                    let long = lob_processor.long.as_mut().unwrap();
+                    let (handle, piece) = oci::oci_stmt_get_piece_info(self.stmt_handle, self.error_handle)?;
+                    if handle != long.define_handle as *mut oci::c_void {
+                        return Err(error);
+                    }
 
-        if let Err(error) = 
-                oci::oci_stmt_fetch(self.stmt_handle,
-                                    self.error_handle,
-                                    prefetch_rows,
-                                    oci::OCIOrientation::FetchNext, 0) {
+                    if pieces == 0 {
+                        // LOB still holds value of previous row
+                        long.lob.trim(0)?;
+                    } else {
+                        let lob_piece = if pieces == 1 { OCILobPiece::FirstPiece } else { OCILobPiece::NextPiece };
+                        long.lob.write_bytes(lob_piece, &buffer[..piece_len as usize])?;
+                    }
 
-            if error.code == 100 {
-                done = true;
-            } else {
-                return Err(error);
+                    piece_len = buffer.len() as u32;
+                    oci::oci_stmt_set_piece_info(handle, self.error_handle, buffer.as_ptr(), &mut piece_len, piece, long.indicator)?;
+                    pieces += 1;
+                    continue;
+                } else {
+                    return Err(error);
+                }
             }
+            break;
+        }
 
+        if pieces > 0 {
+            let long = lob_processor.long.as_mut().unwrap();
+            if !long.lob.is_null() {
+                let lob_piece = if pieces == 1 { OCILobPiece::OnePiece } else { OCILobPiece::LastPiece };
+                long.lob.write_bytes(lob_piece, &buffer[..piece_len as usize])?;
+            }
         }
 
         let mut rows_fetched: u32 = 0;
@@ -164,10 +207,17 @@ impl LoadBuffer {
         let prefetch_rows = self.get_prefetch_rows(table_info);
 
         let mut lob_locators: Vec<(LobDescriptor<'a>,LobDescriptor<'a>)> = Vec::new();
+        let mut long = None;
 
         let charset_id = oci::OCICharset::EE8ISO8859P2 as u16;
         let charset_id_ptr = unsafe { mem::transmute::<&u16, *mut oci::c_void>(&charset_id) };
 
+        // national characters are fetched and written in AL32UTF8, so none is lost on the way
+        let ncharset_id = oci::OCICharset::AL32UTF8 as u16;
+        let ncharset_id_ptr = unsafe { mem::transmute::<&u16, *mut oci::c_void>(&ncharset_id) };
+        let charset_form = oci::OCICharsetForm::Nchar as u8;
+        let charset_form_ptr = unsafe { mem::transmute::<&u8, *mut oci::c_void>(&charset_form) };
+
         unsafe {
             let value = self.values_size / 8;   
             let value_ptr = mem::transmute::<&u32, *mut oci::c_void>(&value);
@@ -185,7 +235,24 @@ impl LoadBuffer {
                 let ind_p: *const libc::c_short = self.indicators_p.offset(offset_i);
                 offset_i += prefetch_rows as isize;
 
-                if m.col_type != ColumnType::Clob && m.col_type != ColumnType::Blob {
+                if m.col_type.is_long() {
+                    let (lob_type, data_type) = if m.col_type == ColumnType::Long {
+                        (OCITempLobType::Clob, oci::OCIDataType::Clob)
+                    } else {
+                        (OCITempLobType::Blob, oci::OCIDataType::Blob)
+                    };
+
+                    let dst_lob = LobDescriptor::new(destination, true, lob_type, ind_p)?;
+                    let dst_locator_ptr: *const u8 = mem::transmute( &(*dst_lob.locator) );
+
+                    let define_handle = oci::oci_stmt_define_dynamic(reader_stmt_handle, reader_error_handle,
+                        i + 1, ind_p, m.oci_data_type.to_owned())?;
+
+                    oci::oci_stmt_bind_by_pos(writer_stmt_handle, writer_error_handle,
+                        i + 1, dst_locator_ptr, ind_p, -1, ptr::null(), data_type)?;
+
+                    long = Some(LongColumn { define_handle, indicator: ind_p, lob: dst_lob });
+                } else if !m.col_type.is_lob() {
                     let value_p: *const u8 = self.values_p.offset(offset);
 
                     let size_p: *const libc::c_ushort = self.ret_lengths_p.offset(offset_s);
@@ -193,13 +260,13 @@ impl LoadBuffer {
                     offset += (m.buffer_len * prefetch_rows) as isize;
                     offset_s += prefetch_rows as isize;
 
-                    oci::oci_stmt_define_by_pos(reader_stmt_handle, reader_error_handle,
+                    let define_handle = oci::oci_stmt_define_by_pos(reader_stmt_handle, reader_error_handle,
                         i + 1, value_p, ind_p, m.buffer_len as isize, size_p, m.oci_data_type.to_owned())?;
                         
                     let bind_handle = oci::oci_stmt_bind_by_pos(writer_stmt_handle, writer_error_handle,
                         i + 1, value_p, ind_p, m.buffer_len as isize, size_p, m.oci_data_type.to_owned())?;
 
-                    if m.col_type == ColumnType::Varchar || m.col_type == ColumnType::Long { 
+                    if m.col_type == ColumnType::Varchar { 
                         oci::oci_attr_set(bind_handle as *mut oci::c_void,
                                           oci::OCIHandleType::Bind,
                                           charset_id_ptr as *mut oci::c_void,
@@ -207,10 +274,22 @@ impl LoadBuffer {
                                           oci::OCIAttribute::CharsetId,
                                           writer_error_handle)?;
                     }
+
+                    if m.col_type == ColumnType::NVarchar {
+                        // form must be set before character set
+                        oci::oci_attr_set(define_handle as *mut oci::c_void, oci::OCIHandleType::Define,
+                                          charset_form_ptr, 0, oci::OCIAttribute::CharsetForm, reader_error_handle)?;
+                        oci::oci_attr_set(define_handle as *mut oci::c_void, oci::OCIHandleType::Define,
+                                          ncharset_id_ptr, 0, oci::OCIAttribute::CharsetId, reader_error_handle)?;
+                        oci::oci_attr_set(bind_handle as *mut oci::c_void, oci::OCIHandleType::Bind,
+                                          charset_form_ptr, 0, oci::OCIAttribute::CharsetForm, writer_error_handle)?;
+                        oci::oci_attr_set(bind_handle as *mut oci::c_void, oci::OCIHandleType::Bind,
+                                          ncharset_id_ptr, 0, oci::OCIAttribute::CharsetId, writer_error_handle)?;
+                    }
                         
                 } else {
                     let lob_type = 
-                        if m.col_type == ColumnType::Clob { OCITempLobType::Clob } else { OCITempLobType::Blob };
+                        if m.col_type == ColumnType::Blob { OCITempLobType::Blob } else { OCITempLobType::Clob };
 
                     let src_lob = LobDescriptor::new(source, false, OCITempLobType::Default, ind_p)?;
                     let dst_lob = LobDescriptor::new(destination, true, lob_type, ind_p)?;
@@ -228,7 +307,7 @@ impl LoadBuffer {
                 }
             }
         }
-        Ok( (prefetch_rows, LobProcessor { lob_locators }) )
+        Ok( (prefetch_rows, LobProcessor { lob_locators, long }) )
     }
 
     pub fn check_lengths(&self, table_info: &table::TableInfo, prefetch_rows: u32, actual_rows: u32) {
@@ -236,7 +315,7 @@ impl LoadBuffer {
             let mut offset_i = 0;
             let mut offset_s = 0;
             for (i, m) in table_info.columns.iter().enumerate() {
-                if m.col_type != ColumnType::Clob && m.col_type != ColumnType::Blob {
+                if !m.col_type.is_lob() && !m.col_type.is_long() {
                     for row in 0..actual_rows {
                         let ind_p: *const libc::c_short = self.indicators_p.offset(offset_i + row as isize);
 
@@ -274,7 +353,7 @@ impl LoadBuffer {
         let mut s_offset: isize = index;
         unsafe {
             for (i, m) in table_info.columns.iter().enumerate() {
-                if m.col_type != ColumnType::Clob && m.col_type != ColumnType::Blob { 
+                if !m.col_type.is_lob() && !m.col_type.is_long() { 
                     if m.col_type == ColumnType::Varchar { 
                         let offset = v_offset + (m.buffer_len as isize * index); 
                         let value_p: *const u8 = self.values_p.offset(offset);
@@ -0,0 +1,66 @@
+use config;
+
+/// Tablespaces used when config does not set them, as destination was always laid out so
+const TABLES:    &'static str = "DATA";
+const INDEXES:   &'static str = "GRAND_INDEX";
+const TEMPORARY: &'static str = "TEMP";
+
+/// global tablespaces apply to every schema, fields set by schema win
+pub fn inherit(schema: &mut config::Schema, conf: &config::Config) {
+    let global = match conf.tablespaces {
+        Some(ref global) => global.clone(),
+        None => return
+    };
+
+    let mut ts = schema.tablespaces.take().unwrap_or(config::Tablespaces::default());
+
+    let mut remap = global.remap.unwrap_or_default();
+    remap.extend(ts.remap.take().unwrap_or_default());
+    ts.remap = Some(remap);
+    ts.tables = ts.tables.or(global.tables);
+    ts.indexes = ts.indexes.or(global.indexes);
+    ts.lobs = ts.lobs.or(global.lobs);
+    ts.temporary = ts.temporary.or(global.temporary);
+
+    schema.tablespaces = Some(ts);
+}
+
+/// Destination of tablespace in source: remapped when remap lists it, default of kind otherwise
+pub struct Remap<'c> {
+    tablespaces: Option<&'c config::Tablespaces>
+}
+
+impl <'c> Remap<'c> {
+    pub fn of(schema: &'c config::Schema) -> Remap<'c> {
+        Remap { tablespaces: schema.tablespaces.as_ref() }
+    }
+
+    pub fn table(&self, source: Option<&str>) -> String {
+        self.remap(source, |ts| ts.tables.as_ref()).unwrap_or(TABLES.to_string())
+    }
+
+    pub fn index(&self, source: Option<&str>) -> String {
+        self.remap(source, |ts| ts.indexes.as_ref()).unwrap_or(INDEXES.to_string())
+    }
+
+    /// none keeps LOB in tablespace of its table
+    pub fn lob(&self, source: Option<&str>) -> Option<String> {
+        self.remap(source, |ts| ts.lobs.as_ref())
+    }
+
+    /// default tablespace of user
+    pub fn user(&self) -> String {
+        self.remap(None, |ts| ts.tables.as_ref()).unwrap_or(TABLES.to_string())
+    }
+
+    pub fn temporary(&self) -> String {
+        self.remap(None, |ts| ts.temporary.as_ref()).unwrap_or(TEMPORARY.to_string())
+    }
+
+    fn remap<F>(&self, source: Option<&str>, default: F) -> Option<String>
+        where F: Fn(&config::Tablespaces) -> Option<&String> {
+        let ts = self.tablespaces?;
+        let remapped = source.and_then(|s| ts.remap.as_ref().and_then(|remap| remap.get(s)));
+        remapped.or(default(ts)).cloned()
+    }
+}
@@ -0,0 +1,85 @@
+use itertools::Itertools;
+
+use config::Transform;
+use table::{ ColumnInfo, ColumnType };
+
+const FIRST_NAMES: [&'static str; 16] = [
+    "Andrei", "Ana", "Mihai", "Maria", "Ion", "Elena", "Radu", "Ioana",
+    "Vlad", "Cristina", "Dan", "Irina", "George", "Laura", "Paul", "Simona"
+];
+
+const LAST_NAMES: [&'static str; 16] = [
+    "Popescu", "Ionescu", "Dumitru", "Stan", "Stoica", "Gheorghe", "Rusu", "Munteanu",
+    "Matei", "Constantin", "Marin", "Tudor", "Dobre", "Barbu", "Nistor", "Florea"
+];
+
+/// Expression which is read instead of column, so masked values never leave source.
+/// Expression has the same type as column, as buffers are defined by type of column;
+/// hash and fake name are deterministic, so equal values stay equal after masking.
+pub fn expression(column: &ColumnInfo, transform: &Transform) -> Result<String, String> {
+    let name = format!("\"{}\"", column.name);
+    let unsupported = || Err(format!("transform {:?} is not supported for column: {} of type: {}", transform, column.name, column.col_type_name));
+
+    let expr = match *transform {
+        Transform::Constant(ref value) => match column.col_type {
+            ColumnType::Varchar | ColumnType::NVarchar | ColumnType::Long => format!("'{}'", value.replace("'", "''")),
+            ColumnType::Int32 | ColumnType::Int64 | ColumnType::Float64 => {
+                value.parse::<f64>()
+                    .map_err(|_| format!("constant: {} of column: {} is not a number", value, column.name))?;
+                value.clone()
+            }
+            ColumnType::DateTime => format!("to_date('{}', 'YYYY-MM-DD HH24:MI:SS')", value.replace("'", "''")),
+            ColumnType::Clob => format!("to_clob('{}')", value.replace("'", "''")),
+            _ => return unsupported()
+        },
+        Transform::Null => {
+            if !column.nullable {
+                return Err(format!("column: {} is not null and can not be set to null", column.name));
+            }
+            match column.col_type {
+                ColumnType::Varchar => format!("cast(null as varchar2({}))", column.buffer_len),
+                ColumnType::Long => "cast(null as varchar2(1))".to_string(),
+                ColumnType::LongRaw => "cast(null as raw(1))".to_string(),
+                ColumnType::NVarchar => format!("cast(null as nvarchar2({}))", column.col_len),
+                ColumnType::Int32 | ColumnType::Int64 | ColumnType::Float64 => "cast(null as number)".to_string(),
+                ColumnType::DateTime => "cast(null as date)".to_string(),
+                ColumnType::Clob => "to_clob(null)".to_string(),
+                ColumnType::Blob => "to_blob(null)".to_string(),
+                _ => return unsupported()
+            }
+        }
+        Transform::Hash => match column.col_type {
+            ColumnType::Varchar | ColumnType::NVarchar => format!("substr(to_char(ora_hash({})), 1, {})", name, column.col_len),
+            ColumnType::Int32 | ColumnType::Int64 | ColumnType::Float64 => {
+                // hash must fit into precision of column, NUMBER(p,s) with s >= p has no integer digits
+                if column.data_precision > 0 {
+                    match column.data_precision.checked_sub(column.data_scale) {
+                        Some(digits) if digits > 0 => format!("mod(ora_hash({}), power(10, {}))", name, digits),
+                        _ => return unsupported()
+                    }
+                } else {
+                    format!("ora_hash({})", name)
+                }
+            }
+            _ => return unsupported()
+        },
+        Transform::FakeName => match column.col_type {
+            ColumnType::Varchar | ColumnType::NVarchar => format!("substr({} || ' ' || {}, 1, {})",
+                                           pick(&name, &FIRST_NAMES, 0), pick(&name, &LAST_NAMES, 1), column.col_len),
+            _ => return unsupported()
+        }
+    };
+
+    // null stays null, whatever transform
+    match *transform {
+        Transform::Constant(_) | Transform::Null => Ok(expr),
+        _ => Ok(format!("case when {} is null then null else {} end", name, expr))
+    }
+}
+
+fn pick(name: &str, values: &[&str], seed: u32) -> String {
+    let cases = values.iter().enumerate()
+        .map(|(i, v)| format!("{}, '{}'", i, v))
+        .join(", ");
+    format!("decode(ora_hash({}, {}, {}), {})", name, values.len() - 1, seed, cases)
+}
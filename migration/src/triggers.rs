@@ -2,12 +2,15 @@ use std::result::Result;
 use itertools::Itertools;
 
 use oracle::*;
+use ddl::{ Ddl, Section };
+use rules::Selection;
 
 use logger::*;
 
 pub fn load(source: &Connection,
-            destination: &Connection,
+            destination: &Ddl,
             schemas: &Vec<String>,
+            selection: &Selection,
             err_logger: &ErrLogger) -> Result<(), String> {
 
     let schemas = schemas.iter().map(|s| format!("'{}'", s)).join(",");
@@ -32,6 +35,11 @@ pub fn load(source: &Connection,
             continue;
         }
 
+        // triggers of tables which are not migrated can not be created
+        if !selection.selects(&owner, &trigger_name) || !selection.selects(&owner, &table_name) {
+            continue;
+        }
+
         let mut vv = trigger_type.split_whitespace();
 
         let timing = vv.next().unwrap();
@@ -40,7 +48,7 @@ pub fn load(source: &Connection,
 
         let sql = format!("create or replace trigger {}.{}\n {} {} on {}.{}\n {}\n{}", &owner, &trigger_name, timing, trigger_event, &owner, &table_name, scope, text);
 
-        if let Err(err) = destination.execute(sql.clone()) {
+        if let Err(err) = destination.execute(Section::Code, &owner, sql.clone()) {
             let err_text = if err.code == 24344 {
                 format!("create trigger: {}.{} on {}: compilation with error", &owner, &trigger_name, &table_name)
             } else {
@@ -0,0 +1,147 @@
+use itertools::Itertools;
+
+use oracle::*;
+use config;
+use logger::*;
+use table;
+
+struct CountStruct(String, String);
+impl MetaQuery for CountStruct {
+    fn create(values: &ResultSet) -> CountStruct {
+        let s0 = &(values[0]);
+        let s1 = &(values[1]);
+        CountStruct(s0.into(), s1.into())
+    }
+
+    fn meta() -> Vec<MetaType> {
+        vec![ string_meta(40), string_meta(40) ]
+    }
+}
+
+/// Rows and checksum of table on one side
+#[derive(PartialEq)]
+struct Totals {
+    rows:     String,
+    checksum: String
+}
+
+/// Compares rows count of every table in source and destination, with checksums also
+/// an order independent sum of column hashes. Mismatching tables are listed at the end
+/// and make verification fail.
+pub fn verify(source: &Connection, destination: &Connection,
+              table_info_service: &mut table::TableInfoService,
+              schemas: &[&config::Schema], checksums: bool,
+              logger: &Logger, err_logger: &ErrLogger) -> Result<(), String> {
+    let mut tables = 0;
+    let mut mismatches = Vec::new();
+
+    logger.println(if checksums { "VERIFY rows and checksums..." } else { "VERIFY rows..." });
+
+    for schema in schemas {
+        logger.newline();
+        logger.println(format!("schema [ {} ]", schema.name));
+
+        for table in table_info_service.load(schema)? {
+            if table.temporary || table.unsupported {
+                continue;
+            }
+            tables += 1;
+
+            let tn: &str = &table.name;
+            logger.print(format!("  {}", rpad(tn, 40)));
+            logger.flush();
+
+            let expected = totals(source, &create_verify_sql(schema, &table, checksums, true))
+                .map_err(|err| format!("can not verify table: {}.{} in source with error: {}", schema.name, table.name, err))?;
+
+            let found = match totals(destination, &create_verify_sql(schema, &table, checksums, false)) {
+                Ok(found) => found,
+                Err(ref err) if err.code == 942 => {
+                    logger.println(" MISSING");
+                    mismatches.push(format!("{}.{}: missing in destination", schema.name, table.name));
+                    continue;
+                }
+                Err(err) => return Err(format!("can not verify table: {}.{} in destination with error: {}", schema.name, table.name, err))
+            };
+
+            logger.print(lpad(format!(" {} rows", expected.rows), 15));
+            if expected == found {
+                logger.println(" ok");
+            } else {
+                logger.println(" MISMATCH");
+                let text = if expected.rows != found.rows {
+                    format!("{}.{}: {} rows in source, {} rows in destination", schema.name, table.name, expected.rows, found.rows)
+                } else {
+                    format!("{}.{}: checksum {} in source, {} in destination", schema.name, table.name, expected.checksum, found.checksum)
+                };
+                mismatches.push(text);
+            }
+        }
+    }
+
+    logger.newline();
+    logger.println(format!("VERIFY: {} tables, {} ok, {} mismatching", tables, tables - mismatches.len(), mismatches.len()));
+
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+
+    for text in &mismatches {
+        logger.println(format!("  {}", text));
+        err_logger.error(format!("verification failed for {}", text));
+    }
+
+    Err(format!("verification failed for {} of {} tables", mismatches.len(), tables))
+}
+
+fn totals(conn: &Connection, sql: &str) -> Result<Totals, OracleError> {
+    let mut query = conn.query(sql.to_string()).prepare::<CountStruct>()?;
+    let cs = query.fetch()?.unwrap();
+    Ok( Totals { rows: cs.0, checksum: cs.1 } )
+}
+
+/// In source only rows matching filter of table are counted and masked columns are hashed
+/// by their expressions. Texts are hashed in AL32UTF8, as source and destination have different
+/// character sets; of LOBs only length and beginning are hashed, LONG, LONG RAW and XMLTYPE columns are not hashed at all,
+/// as XML may be stored in destination formatted differently.
+fn create_verify_sql(schema: &config::Schema, table: &table::TableInfo, checksums: bool, source: bool) -> String {
+    let checksum = if checksums {
+        let hashes = table.columns.iter().enumerate()
+            .filter_map(|(i, c)| column_hash(c, source).map(|hash| format!("nvl({}, {})", hash, i + 1)))
+            .join(" + ");
+        if hashes.is_empty() {
+            "null".to_string()
+        } else {
+            format!("to_char(sum({}))", hashes)
+        }
+    } else {
+        "null".to_string()
+    };
+
+    let mut sql = format!("select to_char(count(*)), {} from {}.{}", checksum, schema.name, table.name);
+    if source {
+        if let Some(ref filter) = table.filter {
+            sql = sql + " where " + filter;
+        }
+    }
+    sql
+}
+
+fn column_hash(column: &table::ColumnInfo, source: bool) -> Option<String> {
+    let name = match column.expression {
+        Some(ref expression) if source => format!("({})", expression),
+        _ => format!("\"{}\"", column.name)
+    };
+    let hash = match column.col_type {
+        table::ColumnType::Int32 | table::ColumnType::Int64 |
+        table::ColumnType::Float64 | table::ColumnType::DateTime |
+        table::ColumnType::Timestamp | table::ColumnType::Interval |
+        table::ColumnType::Raw | table::ColumnType::Rowid => format!("ora_hash({})", name),
+        table::ColumnType::Varchar | table::ColumnType::NVarchar => format!("ora_hash(convert({}, 'AL32UTF8'))", name),
+        table::ColumnType::Clob => format!("ora_hash(convert(dbms_lob.substr({0}, 1000, 1), 'AL32UTF8')) + dbms_lob.getlength({0})", name),
+        table::ColumnType::Blob => format!("ora_hash(dbms_lob.substr({0}, 2000, 1)) + dbms_lob.getlength({0})", name),
+        table::ColumnType::Xml | table::ColumnType::Long | table::ColumnType::LongRaw |
+        table::ColumnType::Unsupported => return None
+    };
+    Some(hash)
+}
@@ -0,0 +1,292 @@
+use std::sync::{ Arc, Mutex };
+use std::sync::atomic::{ AtomicBool, AtomicUsize, Ordering };
+use std::thread;
+
+use oracle;
+use config;
+use executor;
+use grants;
+use indexes::IndexesService;
+use journal::{ Journal, Step };
+use logger::*;
+use table;
+use table_executor;
+use table_executor::TableLine;
+use table_loader::LoadBuffer;
+use ddl::Ddl;
+
+/// attempts to copy one chunk before migration fails
+const CHUNK_ATTEMPTS: u32 = 3;
+
+/// Table or chunk of table waiting for a worker
+pub struct Job {
+    pub schema: config::Schema,
+    pub table:  table::TableInfo,
+    /// allocated bytes in source, bigger tables are loaded first
+    pub bytes:  u64,
+    pub chunk:  Option<Chunk>
+}
+
+/// Rows of table between two ROWIDs, copied and committed on its own
+pub struct Chunk {
+    /// journal object: `SCHEMA.TABLE#min_rowid:max_rowid`
+    key:       String,
+    min_rowid: String,
+    max_rowid: String,
+    index:     usize,
+    table:     Arc<ChunkedTable>
+}
+
+/// State shared by chunks of one table; worker which copies the last chunk finishes the table
+struct ChunkedTable {
+    count:     usize,
+    remaining: AtomicUsize,
+    rows:      AtomicUsize
+}
+
+/// Workers which load tables concurrently. OCI handles can not be shared between threads,
+/// so every worker opens own connections and has own load buffer.
+pub struct Pool {
+    pub workers: u32,
+    source:      config::Addr,
+    destination: config::Addr,
+    buffer_size: u32,
+    luna_calc:   u32,
+    /// tables above this many bytes are split into chunks
+    chunk_above: Option<u64>,
+    chunks:      u32
+}
+
+impl Pool {
+    pub fn new(workers: u32, source: config::Addr, destination: config::Addr, buffer_size: u32, luna_calc: u32,
+               chunk_above_mb: Option<u64>, chunks: Option<u32>) -> Pool {
+        let chunk_above = chunk_above_mb.map(|mb| mb * 1024 * 1024);
+        let chunks = chunks.unwrap_or(workers);
+        Pool { workers, source, destination, buffer_size, luna_calc, chunk_above, chunks }
+    }
+
+    /// jobs of one table: whole table, or its chunks when table is big enough.
+    /// Chunked table is created here, before workers write into it concurrently.
+    pub fn jobs(&self, source: &oracle::Connection, destination: &oracle::Connection,
+                grants_service: &mut grants::GrantsService,
+                schema: &config::Schema, table: table::TableInfo, bytes: u64,
+                tas: &IndexesService, journal: &Journal) -> Result<Vec<Job>, String> {
+        let full_name = format!("{}.{}", schema.name, table.name);
+
+        let chunked = match self.chunk_above {
+            Some(above) => bytes > above && self.chunks > 1 && !table.iot && !table.temporary && !table.unsupported
+                           && !journal.is_done(Step::Table, &full_name),
+            None => false
+        };
+        if !chunked {
+            return Ok(vec![Job { schema: schema.clone(), table, bytes, chunk: None }]);
+        }
+
+        let ranges = table::load_rowid_ranges(source, &(schema.name), &(table.name), self.chunks)?;
+        if ranges.len() < 2 {
+            return Ok(vec![Job { schema: schema.clone(), table, bytes, chunk: None }]);
+        }
+
+        let interrupted = journal.is_interrupted(Step::Table, &full_name);
+        if let Err(err) = destination.execute(table_executor::create_table_sql(schema, &table, false)) {
+            if err.code == 955 && !interrupted {
+                // table existing before migration is reported by usual load
+                return Ok(vec![Job { schema: schema.clone(), table, bytes, chunk: None }]);
+            } else if err.code == 1918 {
+                return Err(format!("can not create table: {} because user {} does not exists", table.name, schema.name));
+            } else if err.code != 955 {
+                return Err(format!("can not create table: {} with error: {}", table.name, err));
+            }
+        }
+        journal.started(Step::Table, &full_name)?;
+
+        let prefix = format!("{}#", full_name);
+        let keys = ranges.iter().map(|&(ref lo, ref hi)| format!("{}{}:{}", prefix, lo, hi)).collect::<Vec<String>>();
+
+        // chunks done before are kept only when extents of table are the same as then
+        let mut reload = false;
+        let done = keys.iter().filter(|key| journal.is_done(Step::Chunk, key)).count();
+        if interrupted && (done == 0 || done != journal.count_done(Step::Chunk, &prefix)) {
+            // reset is recorded first: chunks journaled as done must never outlive their rows
+            journal.reset(Step::Chunk, &prefix)?;
+            let sql = format!("truncate table {}.{}", &(schema.name), &(table.name));
+            destination.execute(sql)
+                .map_err(|err| format!("can not truncate table: {} with error: {}", table.name, err))?;
+            reload = true;
+        }
+
+        let is_done = |key: &String| !reload && journal.is_done(Step::Chunk, key);
+        let rows = keys.iter().filter(|key| is_done(key))
+            .map(|key| journal.rows(Step::Chunk, key).unwrap_or(0) as usize).sum();
+        let pending = keys.iter().filter(|key| !is_done(key)).count();
+
+        let chunked_table = Arc::new(ChunkedTable {
+            count:     keys.len(),
+            remaining: AtomicUsize::new(pending),
+            rows:      AtomicUsize::new(rows)
+        });
+
+        if pending == 0 {
+            finish(destination, grants_service, schema, &table, rows as u32, tas, journal)?;
+            return Ok(Vec::new());
+        }
+
+        let chunk_bytes = bytes / keys.len() as u64;
+        let jobs = keys.into_iter().zip(ranges.into_iter()).enumerate()
+            .filter(|&(_, (ref key, _))| !is_done(key))
+            .map(|(index, (key, (min_rowid, max_rowid)))| Job {
+                schema: schema.clone(),
+                table:  table.clone(),
+                bytes:  chunk_bytes,
+                chunk:  Some(Chunk { key, min_rowid, max_rowid, index, table: chunked_table.clone() })
+            }).collect();
+
+        Ok(jobs)
+    }
+
+    /// loads all jobs, largest first; after first failed job workers do not start other jobs
+    pub fn load(&self, mut jobs: Vec<Job>,
+                logger: &Logger, err_logger: &ErrLogger,
+                tas: &IndexesService, journal: &Journal) -> Result<(), String> {
+        jobs.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+
+        let total = jobs.len();
+        let queue = Arc::new(Mutex::new(jobs.into_iter().enumerate()));
+        let failed = Arc::new(AtomicBool::new(false));
+
+        let mut handles = Vec::new();
+
+        for worker in 1 .. self.workers + 1 {
+            let queue = queue.clone();
+            let failed = failed.clone();
+            let source = self.source.clone();
+            let destination = self.destination.clone();
+            let buffer_size = self.buffer_size;
+            let luna_calc = self.luna_calc;
+            let logger = logger.clone();
+            let err_logger = err_logger.clone();
+            let tas = tas.clone();
+            let journal = journal.clone();
+
+            let handle = thread::spawn(move|| {
+                let result = work(worker, &queue, &failed, source, destination, buffer_size, luna_calc, total,
+                                  &logger, &err_logger, &tas, &journal);
+                if result.is_err() {
+                    failed.store(true, Ordering::SeqCst);
+                }
+                result
+            });
+            handles.push(handle);
+        }
+
+        let mut errors = Vec::new();
+        for (i, handle) in handles.into_iter().enumerate() {
+            match handle.join() {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => errors.push(err),
+                Err(_) => errors.push(format!("worker {} panicked", i + 1))
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+fn work(worker: u32,
+        queue: &Mutex<::std::iter::Enumerate<::std::vec::IntoIter<Job>>>,
+        failed: &AtomicBool,
+        source: config::Addr, destination: config::Addr,
+        buffer_size: u32, luna_calc: u32, total: usize,
+        logger: &Logger, err_logger: &ErrLogger,
+        tas: &IndexesService, journal: &Journal) -> Result<(), String> {
+    let source = executor::connect_source(source)
+        .map_err(|err| format!("worker {}: {}", worker, err))?;
+    let destination = executor::connect_destination(destination)
+        .map_err(|err| format!("worker {}: {}", worker, err))?;
+
+    let mut grants_service = grants::GrantsService::new(&source)?;
+    let load_buffer = LoadBuffer::new(buffer_size);
+    let lob_copy_buffer = vec![0u8; 1024 * 1024];
+
+    loop {
+        if failed.load(Ordering::SeqCst) {
+            break;
+        }
+
+        let next = queue.lock().unwrap().next();
+        let (i, job) = match next {
+            Some(next) => next,
+            None => break
+        };
+
+        let prefix = format!("[{}/{} w{}] {}", i + 1, total, worker, job.schema.name);
+
+        match job.chunk {
+            None => {
+                let mut line = TableLine::buffered(logger, prefix);
+                table_executor::load_table(&source, &destination, &mut grants_service, &job.schema, &job.table, luna_calc,
+                                           &load_buffer, &lob_copy_buffer, &mut line, err_logger, tas, journal)
+                    .map_err(|err| format!("worker {}: {}", worker, err))?;
+            }
+            Some(ref chunk) => {
+                let rows = copy_chunk(worker, &source, &destination, &job, chunk, luna_calc,
+                                      &load_buffer, &lob_copy_buffer, err_logger)?;
+                journal.done_rows(Step::Chunk, &chunk.key, rows)
+                    .map_err(|err| format!("worker {}: {}", worker, err))?;
+                logger.println(format!("{}   {} chunk {}/{} {} rows", prefix, rpad(&(job.table.name), 40),
+                                       chunk.index + 1, chunk.table.count, rows));
+
+                let table_rows = chunk.table.rows.fetch_add(rows as usize, Ordering::SeqCst) + rows as usize;
+                if chunk.table.remaining.fetch_sub(1, Ordering::SeqCst) == 1 {
+                    logger.println(format!("{}   {} {} rows in {} chunks{}", prefix, rpad(&(job.table.name), 40),
+                                           table_rows, chunk.table.count, table_executor::converted(&job.table)));
+                    finish(&destination, &mut grants_service, &job.schema, &job.table, table_rows as u32, tas, journal)
+                        .map_err(|err| format!("worker {}: {}", worker, err))?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// copies chunk, rolling back and copying it again when it fails
+fn copy_chunk(worker: u32,
+              source: &oracle::Connection, destination: &oracle::Connection,
+              job: &Job, chunk: &Chunk, luna_calc: u32,
+              load_buffer: &LoadBuffer, lob_copy_buffer: &[u8],
+              err_logger: &ErrLogger) -> Result<u32, String> {
+    let mut attempt = 1;
+    loop {
+        let result = table_executor::copy_chunk(source, destination, &job.schema, &job.table, luna_calc,
+                                                &(chunk.min_rowid), &(chunk.max_rowid), load_buffer, lob_copy_buffer);
+        match result {
+            Ok(rows) => return Ok(rows),
+            Err(err) => {
+                destination.rollback()
+                    .map_err(|err| format!("worker {}: can not rollback chunk: {} with error: {}", worker, chunk.key, err))?;
+                if attempt == CHUNK_ATTEMPTS {
+                    return Err(format!("worker {}: chunk: {} failed {} times, last error: {}", worker, chunk.key, attempt, err));
+                }
+                err_logger.error(format!("worker {}: chunk: {} failed, copied again, error: {}", worker, chunk.key, err));
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// all chunks of table are copied: grants, indexes and journal as after load of whole table
+fn finish(destination: &oracle::Connection, grants_service: &mut grants::GrantsService,
+          schema: &config::Schema, table: &table::TableInfo, rows: u32,
+          tas: &IndexesService, journal: &Journal) -> Result<(), String> {
+    grants_service.grant(&Ddl::Execute(destination), &(schema.name), &(table.name))?;
+    destination.commit(oracle::OCICommitMode::Immediate)
+        .map_err(|err| format!("can not commit table: {} with error: {}", table.name, err))?;
+    tas.finalize(schema.name.clone(), table.clone());
+    journal.done_rows(Step::Table, &format!("{}.{}", schema.name, table.name), rows)?;
+    Ok(())
+}